@@ -1,12 +1,16 @@
 use argon2::Error as ArgonError;
+use reqwest::Error as ReqwestError;
+use reqwest_middleware::Error as MiddlewareReqwestError;
+use serde::Serialize;
 use warp::{
     filters::{body::BodyDeserializeError, cors::CorsForbidden},
     http::StatusCode,
-    reject::Reject,
+    reject::{
+        InvalidHeader, InvalidQuery, LengthRequired, MethodNotAllowed, MissingHeader,
+        PayloadTooLarge, Reject, UnsupportedMediaType,
+    },
     Rejection, Reply,
 };
-use reqwest::Error as ReqwestError;
-use reqwest_middleware::Error as MiddlewareReqwestError;
 
 use tracing::{event, instrument, Level};
 
@@ -16,7 +20,13 @@ pub enum Error {
     MissingParameters,
     WrongPassword,
     CannotDecryptToken,
+    TokenExpired,
     Unauthorized,
+    NotFound,
+    PreconditionFailed,
+    PreconditionRequired,
+    InvalidBody(String),
+    ValidationError(Vec<FieldError>),
     ArgonLibraryError(ArgonError),
     DatabaseQueryError(sqlx::Error),
     MigrationError(sqlx::migrate::MigrateError),
@@ -24,7 +34,16 @@ pub enum Error {
     MiddlewareReqwestAPIError(MiddlewareReqwestError),
     ClientError(APILayerError),
     ServerError(APILayerError),
-
+    /// 외부 API 가 계속 실패해서 서킷 브레이커가 호출을 막고 있다
+    CircuitOpen,
+    /// 외부 API 호출이 동시 실행 한도를 넘었다
+    UpstreamBusy,
+    /// 외부 API 가 제한 시간 안에 응답하지 않았다
+    UpstreamTimeout,
+    /// 새 질문과 비슷한 질문이 이미 있다
+    PossibleDuplicate(Vec<DuplicateCandidate>),
+    /// 설정값이 없거나 잘못됐다
+    InvalidConfig(String),
 }
 
 #[derive(Debug, Clone)]
@@ -33,16 +52,36 @@ pub struct APILayerError {
     pub message: String,
 }
 
+/// 요청 본문에서 검증에 실패한 필드 하나
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+/// 새 질문과 비슷한 기존 질문 하나
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct DuplicateCandidate {
+    pub id: i32,
+    pub title: String,
+    pub score: f32,
+}
+
+impl std::fmt::Display for FieldError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}: {}", self.field, self.message)
+    }
+}
+
 impl std::fmt::Display for APILayerError {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         write!(f, "Status: {}, Message: {}", self.status, self.message)
     }
 }
 
-
 impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        match &*self {
+        match self {
             Error::ParseError(ref err) => {
                 write!(f, "Cannot parse parameter: {}", err)
             }
@@ -51,7 +90,18 @@ impl std::fmt::Display for Error {
                 write!(f, "Wrong password")
             }
             Error::CannotDecryptToken => write!(f, "Cannot decrypt error"),
+            Error::TokenExpired => write!(f, "Token has expired"),
             Error::Unauthorized => write!(f, "No permission to change the underlying resource"),
+            Error::NotFound => write!(f, "Resource not found"),
+            Error::PreconditionFailed => {
+                write!(f, "Resource was modified since it was last read")
+            }
+            Error::PreconditionRequired => write!(f, "Missing If-Match header"),
+            Error::InvalidBody(err) => write!(f, "Cannot deserialize request body: {}", err),
+            Error::ValidationError(errors) => {
+                let fields: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+                write!(f, "Invalid request: {}", fields.join(", "))
+            }
             Error::ArgonLibraryError(_) => {
                 write!(f, "Cannot verify password")
             }
@@ -65,7 +115,7 @@ impl std::fmt::Display for Error {
             }
             Error::MiddlewareReqwestAPIError(err) => {
                 write!(f, "External API error: {}", err)
-            },
+            }
 
             Error::ClientError(err) => {
                 write!(f, "External Client error: {}", err)
@@ -73,7 +123,20 @@ impl std::fmt::Display for Error {
             Error::ServerError(err) => {
                 write!(f, "External Server error: {}", err)
             }
-
+            Error::CircuitOpen => {
+                write!(f, "External API is temporarily unavailable")
+            }
+            Error::UpstreamBusy => {
+                write!(f, "Too many concurrent calls to external API")
+            }
+            Error::UpstreamTimeout => {
+                write!(f, "External API timed out")
+            }
+            Error::PossibleDuplicate(_) => write!(
+                f,
+                "Similar questions already exist, pass force=true to post anyway"
+            ),
+            Error::InvalidConfig(message) => write!(f, "Invalid configuration: {}", message),
         }
     }
 }
@@ -81,93 +144,677 @@ impl std::fmt::Display for Error {
 impl Reject for Error {}
 impl Reject for APILayerError {}
 
-const DUPLICATE_KEY: u32 = 23505;
-
-#[instrument]
-pub async fn return_error(r: Rejection) -> Result<impl Reply, Rejection> {
-    if let Some(crate::Error::DatabaseQueryError(e)) = r.find() {
-        event!(Level::ERROR, "Database query error");
-        match e {
-            sqlx::Error::Database(err) => {
-                if err.code().unwrap().parse::<u32>().unwrap() == 
-                    DUPLICATE_KEY {
-                    Ok(warp::reply::with_status(
-                        "Account already exists".to_string(),
-                        StatusCode::UNPROCESSABLE_ENTITY,
-                    ))
-                } else {
-                    Ok(warp::reply::with_status(
-                        "Cannot update data".to_string(),
-                        StatusCode::UNPROCESSABLE_ENTITY,
-                    ))
-                }
-            }
-            _ => Ok(warp::reply::with_status(
-                "Cannot update data".to_string(),
-                StatusCode::UNPROCESSABLE_ENTITY,
-            )),
-        }
-
-    } else if let Some(crate::Error::ReqwestAPIError(e)) = r.find() {
-        event!(Level::ERROR, "{}", e);
-        Ok(warp::reply::with_status(
-            "Internal Server Error".to_string(),
-            StatusCode::INTERNAL_SERVER_ERROR,
-        ))
-    } else if let Some(crate::Error::Unauthorized) = r.find() {
-        event!(Level::ERROR, "Not matching account id");
-        Ok(warp::reply::with_status(
-            "No permission to change underlying resource".to_string(),
-            StatusCode::UNAUTHORIZED,
-        ))
-    } else if let Some(crate::Error::WrongPassword) = r.find() {
-        event!(Level::ERROR, "Entered wrong password");
-        Ok(warp::reply::with_status(
-            "Wrong E-Mail/Password combination".to_string(),
-            StatusCode::UNAUTHORIZED,
-        ))
-
-    } else if let Some(crate::Error::MiddlewareReqwestAPIError(e)) = r.find() {
-        event!(Level::ERROR, "{}", e);
-        Ok(warp::reply::with_status(
-            "Internal Server Error".to_string(),
-            StatusCode::INTERNAL_SERVER_ERROR,
-        ))
-    } else if let Some(crate::Error::ClientError(e)) = r.find() {
-        event!(Level::ERROR, "{}", e);
-        Ok(warp::reply::with_status(
-            "Internal Server Error".to_string(),
-            StatusCode::INTERNAL_SERVER_ERROR,
-        ))
-    } else if let Some(crate::Error::ServerError(e)) = r.find() {
-        event!(Level::ERROR, "{}", e);
-        Ok(warp::reply::with_status(
-            "Internal Server Error".to_string(),
-            StatusCode::INTERNAL_SERVER_ERROR,
-        ))
+const DUPLICATE_KEY: &str = "23505";
+const FOREIGN_KEY_VIOLATION: &str = "23503";
+const STRING_DATA_RIGHT_TRUNCATION: &str = "22001";
+const CHECK_VIOLATION: &str = "23514";
+const ACCOUNTS_EMAIL_KEY: &str = "accounts_email_key";
+
+impl Error {
+    /// 클라이언트가 분기할 수 있는 고정된 에러 코드
+    /// 한 번 배포된 코드는 바꾸지 않는다
+    pub fn code(&self) -> &'static str {
+        self.classify().1
+    }
+
+    /// 에러에 해당하는 HTTP 상태 코드
+    pub fn status(&self) -> StatusCode {
+        self.classify().0
+    }
+
+    fn classify(&self) -> (StatusCode, &'static str) {
+        match self {
+            Error::ParseError(_) => (StatusCode::BAD_REQUEST, "invalid_parameter"),
+            Error::MissingParameters => (StatusCode::BAD_REQUEST, "missing_parameters"),
+            Error::InvalidBody(_) => (StatusCode::BAD_REQUEST, "invalid_body"),
+            Error::ValidationError(_) => (StatusCode::BAD_REQUEST, "validation_failed"),
+            Error::WrongPassword => (StatusCode::UNAUTHORIZED, "wrong_credentials"),
+            Error::CannotDecryptToken => (StatusCode::UNAUTHORIZED, "invalid_token"),
+            Error::TokenExpired => (StatusCode::UNAUTHORIZED, "token_expired"),
+            Error::Unauthorized => (StatusCode::FORBIDDEN, "forbidden"),
+            Error::NotFound => (StatusCode::NOT_FOUND, "not_found"),
+            Error::PreconditionFailed => (StatusCode::PRECONDITION_FAILED, "version_mismatch"),
+            Error::PreconditionRequired => {
+                (StatusCode::PRECONDITION_REQUIRED, "precondition_required")
+            }
+            Error::ArgonLibraryError(_) => {
+                (StatusCode::INTERNAL_SERVER_ERROR, "password_hash_error")
+            }
+            Error::DatabaseQueryError(e) => classify_database_error(e),
+            Error::MigrationError(_) => (StatusCode::INTERNAL_SERVER_ERROR, "migration_error"),
+            Error::ReqwestAPIError(e) => classify_reqwest_error(e),
+            Error::MiddlewareReqwestAPIError(MiddlewareReqwestError::Reqwest(e)) => {
+                classify_reqwest_error(e)
+            }
+            Error::MiddlewareReqwestAPIError(_) => (StatusCode::BAD_GATEWAY, "upstream_error"),
+            Error::ClientError(_) => (StatusCode::BAD_GATEWAY, "upstream_rejected"),
+            Error::ServerError(_) => (StatusCode::SERVICE_UNAVAILABLE, "upstream_unavailable"),
+            Error::CircuitOpen => (StatusCode::SERVICE_UNAVAILABLE, "upstream_unavailable"),
+            Error::UpstreamBusy => (StatusCode::SERVICE_UNAVAILABLE, "upstream_busy"),
+            Error::UpstreamTimeout => (StatusCode::GATEWAY_TIMEOUT, "upstream_timeout"),
+            Error::PossibleDuplicate(_) => (StatusCode::CONFLICT, "possible_duplicate"),
+            Error::InvalidConfig(_) => (StatusCode::INTERNAL_SERVER_ERROR, "invalid_config"),
+        }
+    }
+
+    /// 응답 본문에 넣을 설명
+    /// 5xx 에러는 내부 정보(쿼리, 외부 API 주소 등)를 노출하지 않도록 None 이다
+    pub fn detail(&self) -> Option<String> {
+        match self {
+            Error::DatabaseQueryError(_) if self.code() == "account_exists" => {
+                Some("Account already exists".to_string())
+            }
+            _ if self.status().is_server_error() => None,
+            _ => Some(self.to_string()),
+        }
+    }
+}
+
+fn classify_database_error(e: &sqlx::Error) -> (StatusCode, &'static str) {
+    match e {
+        sqlx::Error::RowNotFound => (StatusCode::NOT_FOUND, "not_found"),
+        sqlx::Error::Database(err) => match (err.code().as_deref(), err.constraint()) {
+            (Some(DUPLICATE_KEY), Some(ACCOUNTS_EMAIL_KEY)) => {
+                (StatusCode::CONFLICT, "account_exists")
+            }
+            (Some(DUPLICATE_KEY), _) => (StatusCode::CONFLICT, "duplicate_resource"),
+            (Some(FOREIGN_KEY_VIOLATION), _) => (StatusCode::CONFLICT, "invalid_reference"),
+            (Some(STRING_DATA_RIGHT_TRUNCATION), _) | (Some(CHECK_VIOLATION), _) => {
+                (StatusCode::UNPROCESSABLE_ENTITY, "invalid_data")
+            }
+            _ => (StatusCode::INTERNAL_SERVER_ERROR, "database_error"),
+        },
+        sqlx::Error::PoolTimedOut
+        | sqlx::Error::PoolClosed
+        | sqlx::Error::Io(_)
+        | sqlx::Error::Tls(_) => (StatusCode::SERVICE_UNAVAILABLE, "database_unavailable"),
+        _ => (StatusCode::INTERNAL_SERVER_ERROR, "database_error"),
+    }
+}
+
+fn classify_reqwest_error(e: &ReqwestError) -> (StatusCode, &'static str) {
+    if e.is_timeout() || e.is_connect() {
+        (StatusCode::SERVICE_UNAVAILABLE, "upstream_unavailable")
+    } else {
+        (StatusCode::BAD_GATEWAY, "upstream_error")
+    }
+}
+
+/// RFC 7807 `application/problem+json` 응답 본문
+#[derive(Serialize, Debug)]
+struct Problem<'a> {
+    #[serde(rename = "type")]
+    problem_type: &'static str,
+    title: &'static str,
+    status: u16,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    detail: Option<String>,
+    code: &'static str,
+    request_id: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    errors: Option<&'a [FieldError]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    candidates: Option<&'a [DuplicateCandidate]>,
+}
+
+impl<'a> Problem<'a> {
+    fn new(
+        status: StatusCode,
+        code: &'static str,
+        detail: Option<String>,
+        request_id: &'a str,
+    ) -> Self {
+        Problem {
+            problem_type: "about:blank",
+            title: status.canonical_reason().unwrap_or("Unknown Error"),
+            status: status.as_u16(),
+            detail,
+            code,
+            request_id,
+            errors: None,
+            candidates: None,
+        }
+    }
+
+    fn into_response(self) -> warp::reply::Response {
+        let status = StatusCode::from_u16(self.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        warp::reply::with_header(
+            warp::reply::with_status(warp::reply::json(&self), status),
+            "content-type",
+            "application/problem+json",
+        )
+        .into_response()
+    }
+}
+
+/// 거절된 요청을 problem+json 응답으로 바꾼다
+/// `request_id` 는 응답 본문과 로그에 함께 남아 클라이언트 보고와 서버 로그를 연결한다
+#[instrument(skip(r))]
+pub async fn return_error(r: Rejection, request_id: String) -> Result<impl Reply, Rejection> {
+    let problem = if let Some(error) = r.find::<Error>() {
+        let (status, code) = error.classify();
+        if status.is_server_error() {
+            event!(Level::ERROR, code, request_id = %request_id, "{:?}", error);
+        } else {
+            event!(Level::WARN, code, request_id = %request_id, "{}", error);
+        }
+
+        let mut problem = Problem::new(status, code, error.detail(), &request_id);
+        match error {
+            Error::ValidationError(errors) => problem.errors = Some(errors),
+            Error::PossibleDuplicate(candidates) => problem.candidates = Some(candidates),
+            _ => {}
+        }
+        problem
+    } else {
+        let (status, code, detail) = classify_rejection(&r);
+        event!(Level::WARN, code, request_id = %request_id, "{:?}", r);
+        Problem::new(status, code, detail, &request_id)
+    };
+
+    Ok(problem.into_response())
+}
+
+fn rejection(
+    status: StatusCode,
+    code: &'static str,
+    error: &impl ToString,
+) -> (StatusCode, &'static str, Option<String>) {
+    (status, code, Some(error.to_string()))
+}
+
+/// warp 가 직접 만든 거절을 분류한다
+fn classify_rejection(r: &Rejection) -> (StatusCode, &'static str, Option<String>) {
+    if r.is_not_found() {
+        (StatusCode::NOT_FOUND, "route_not_found", None)
     } else if let Some(error) = r.find::<CorsForbidden>() {
-        event!(Level::ERROR, "CORS forbidden error: {}", error);
-        Ok(warp::reply::with_status(
-            error.to_string(),
-            StatusCode::FORBIDDEN,
-        ))
+        rejection(StatusCode::FORBIDDEN, "cors_forbidden", error)
     } else if let Some(error) = r.find::<BodyDeserializeError>() {
-        event!(Level::ERROR, "Cannot deserizalize request body: {}", error);
-        Ok(warp::reply::with_status(
-            error.to_string(),
-            StatusCode::UNPROCESSABLE_ENTITY,
-        ))
-    } else if let Some(error) = r.find::<Error>() {
-        event!(Level::ERROR, "{}", error);
-        Ok(warp::reply::with_status(
-            error.to_string(),
-            StatusCode::UNPROCESSABLE_ENTITY,
-        ))
+        rejection(StatusCode::BAD_REQUEST, "invalid_body", error)
+    } else if let Some(error) = r.find::<MissingHeader>() {
+        if error.name().eq_ignore_ascii_case("authorization") {
+            rejection(StatusCode::UNAUTHORIZED, "missing_token", error)
+        } else {
+            rejection(StatusCode::BAD_REQUEST, "missing_header", error)
+        }
+    } else if let Some(error) = r.find::<InvalidHeader>() {
+        rejection(StatusCode::BAD_REQUEST, "invalid_header", error)
+    } else if let Some(error) = r.find::<InvalidQuery>() {
+        rejection(StatusCode::BAD_REQUEST, "invalid_parameter", error)
+    } else if let Some(error) = r.find::<UnsupportedMediaType>() {
+        rejection(
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            "unsupported_media_type",
+            error,
+        )
+    } else if let Some(error) = r.find::<LengthRequired>() {
+        rejection(StatusCode::LENGTH_REQUIRED, "length_required", error)
+    } else if let Some(error) = r.find::<PayloadTooLarge>() {
+        rejection(StatusCode::PAYLOAD_TOO_LARGE, "payload_too_large", error)
+    } else if let Some(error) = r.find::<MethodNotAllowed>() {
+        // 라우트는 경로가 끝까지 맞은 다음에 메서드를 검사하므로 없는 경로는 여기까지 오지 않는다
+        // 경로가 맞은 다른 라우트의 거절과 합쳐져 있을 수 있으므로 다른 거절을 먼저 본다
+        rejection(StatusCode::METHOD_NOT_ALLOWED, "method_not_allowed", error)
     } else {
-        event!(Level::WARN, "Requested route was not found");
-        Ok(warp::reply::with_status(
-            "Route not found".to_string(),
-            StatusCode::NOT_FOUND,
-        ))
+        (StatusCode::INTERNAL_SERVER_ERROR, "internal_error", None)
+    }
+}
+
+#[cfg(test)]
+mod handle_errors_tests {
+    use super::{return_error, APILayerError, DuplicateCandidate, Error, FieldError, StatusCode};
+    use serde_json::Value;
+    use std::borrow::Cow;
+    use warp::{Filter, Rejection, Reply};
+
+    /// 코드와 제약 조건을 마음대로 정할 수 있는 DB 에러
+    #[derive(Debug)]
+    struct FakeDatabaseError {
+        code: Option<&'static str>,
+        constraint: Option<&'static str>,
+    }
+
+    impl std::fmt::Display for FakeDatabaseError {
+        fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            write!(f, "fake database error")
+        }
+    }
+
+    impl std::error::Error for FakeDatabaseError {}
+
+    impl sqlx::error::DatabaseError for FakeDatabaseError {
+        fn message(&self) -> &str {
+            "fake database error"
+        }
+
+        fn code(&self) -> Option<Cow<'_, str>> {
+            self.code.map(Cow::Borrowed)
+        }
+
+        fn constraint(&self) -> Option<&str> {
+            self.constraint
+        }
+
+        fn as_error(&self) -> &(dyn std::error::Error + Send + Sync + 'static) {
+            self
+        }
+
+        fn as_error_mut(&mut self) -> &mut (dyn std::error::Error + Send + Sync + 'static) {
+            self
+        }
+
+        fn into_error(self: Box<Self>) -> Box<dyn std::error::Error + Send + Sync + 'static> {
+            self
+        }
+    }
+
+    fn database_error(code: Option<&'static str>, constraint: Option<&'static str>) -> Error {
+        Error::DatabaseQueryError(sqlx::Error::Database(Box::new(FakeDatabaseError {
+            code,
+            constraint,
+        })))
+    }
+
+    fn reqwest_error() -> reqwest::Error {
+        reqwest::Client::new().get("not a url").build().unwrap_err()
+    }
+
+    fn api_layer_error() -> APILayerError {
+        APILayerError {
+            status: 500,
+            message: "upstream broke".to_string(),
+        }
+    }
+
+    async fn render(r: Rejection) -> (StatusCode, Value) {
+        let res = return_error(r, "test-request".to_string())
+            .await
+            .unwrap()
+            .into_response();
+        let status = res.status();
+        assert_eq!(
+            res.headers()["content-type"],
+            "application/problem+json",
+            "status {}",
+            status
+        );
+
+        let body = warp::hyper::body::to_bytes(res.into_body()).await.unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    async fn assert_error(error: Error, status: StatusCode, code: &str) -> Value {
+        let (actual_status, body) = render(warp::reject::custom(error)).await;
+        assert_eq!(actual_status, status, "code {}", code);
+        assert_eq!(body["status"], status.as_u16());
+        assert_eq!(body["code"], code);
+        assert_eq!(body["type"], "about:blank");
+        assert_eq!(body["request_id"], "test-request");
+        body
+    }
+
+    #[tokio::test]
+    async fn every_variant_maps_to_a_problem() {
+        let cases = vec![
+            (
+                Error::ParseError("x".parse::<u32>().unwrap_err()),
+                StatusCode::BAD_REQUEST,
+                "invalid_parameter",
+            ),
+            (
+                Error::MissingParameters,
+                StatusCode::BAD_REQUEST,
+                "missing_parameters",
+            ),
+            (
+                Error::WrongPassword,
+                StatusCode::UNAUTHORIZED,
+                "wrong_credentials",
+            ),
+            (
+                Error::CannotDecryptToken,
+                StatusCode::UNAUTHORIZED,
+                "invalid_token",
+            ),
+            (
+                Error::TokenExpired,
+                StatusCode::UNAUTHORIZED,
+                "token_expired",
+            ),
+            (Error::Unauthorized, StatusCode::FORBIDDEN, "forbidden"),
+            (Error::NotFound, StatusCode::NOT_FOUND, "not_found"),
+            (
+                Error::PreconditionFailed,
+                StatusCode::PRECONDITION_FAILED,
+                "version_mismatch",
+            ),
+            (
+                Error::PreconditionRequired,
+                StatusCode::PRECONDITION_REQUIRED,
+                "precondition_required",
+            ),
+            (
+                Error::InvalidBody("expected value".to_string()),
+                StatusCode::BAD_REQUEST,
+                "invalid_body",
+            ),
+            (
+                Error::ArgonLibraryError(argon2::Error::OutputTooShort),
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "password_hash_error",
+            ),
+            (
+                Error::MigrationError(sqlx::migrate::MigrateError::VersionMissing(1)),
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "migration_error",
+            ),
+            (
+                Error::ReqwestAPIError(reqwest_error()),
+                StatusCode::BAD_GATEWAY,
+                "upstream_error",
+            ),
+            (
+                Error::MiddlewareReqwestAPIError(reqwest_middleware::Error::Reqwest(
+                    reqwest_error(),
+                )),
+                StatusCode::BAD_GATEWAY,
+                "upstream_error",
+            ),
+            (
+                Error::ClientError(api_layer_error()),
+                StatusCode::BAD_GATEWAY,
+                "upstream_rejected",
+            ),
+            (
+                Error::ServerError(api_layer_error()),
+                StatusCode::SERVICE_UNAVAILABLE,
+                "upstream_unavailable",
+            ),
+            (
+                Error::CircuitOpen,
+                StatusCode::SERVICE_UNAVAILABLE,
+                "upstream_unavailable",
+            ),
+            (
+                Error::UpstreamBusy,
+                StatusCode::SERVICE_UNAVAILABLE,
+                "upstream_busy",
+            ),
+            (
+                Error::UpstreamTimeout,
+                StatusCode::GATEWAY_TIMEOUT,
+                "upstream_timeout",
+            ),
+            (
+                Error::PossibleDuplicate(Vec::new()),
+                StatusCode::CONFLICT,
+                "possible_duplicate",
+            ),
+            (
+                Error::InvalidConfig("PASETO key not set".to_string()),
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "invalid_config",
+            ),
+        ];
+
+        for (error, status, code) in cases {
+            assert_error(error, status, code).await;
+        }
+    }
+
+    #[tokio::test]
+    async fn server_errors_hide_details() {
+        let body = assert_error(
+            Error::ServerError(api_layer_error()),
+            StatusCode::SERVICE_UNAVAILABLE,
+            "upstream_unavailable",
+        )
+        .await;
+        assert!(body.get("detail").is_none());
+    }
+
+    #[tokio::test]
+    async fn validation_errors_list_fields() {
+        let body = assert_error(
+            Error::ValidationError(vec![FieldError {
+                field: "title".to_string(),
+                message: "must not be empty".to_string(),
+            }]),
+            StatusCode::BAD_REQUEST,
+            "validation_failed",
+        )
+        .await;
+        assert_eq!(body["errors"][0]["field"], "title");
+        assert_eq!(body["errors"][0]["message"], "must not be empty");
+    }
+
+    #[tokio::test]
+    async fn possible_duplicates_list_candidates() {
+        let body = assert_error(
+            Error::PossibleDuplicate(vec![DuplicateCandidate {
+                id: 7,
+                title: "Composing warp filters".to_string(),
+                score: 0.5,
+            }]),
+            StatusCode::CONFLICT,
+            "possible_duplicate",
+        )
+        .await;
+        assert_eq!(body["candidates"][0]["id"], 7);
+        assert_eq!(body["candidates"][0]["title"], "Composing warp filters");
+        assert_eq!(body["candidates"][0]["score"], 0.5);
+        assert!(body.get("errors").is_none());
+    }
+
+    #[tokio::test]
+    async fn database_error_kinds() {
+        let body = assert_error(
+            database_error(Some("23505"), Some("accounts_email_key")),
+            StatusCode::CONFLICT,
+            "account_exists",
+        )
+        .await;
+        assert_eq!(body["detail"], "Account already exists");
+
+        let cases = vec![
+            (
+                database_error(Some("23505"), Some("questions_pkey")),
+                StatusCode::CONFLICT,
+                "duplicate_resource",
+            ),
+            (
+                database_error(Some("23503"), Some("answers_account_id_fkey")),
+                StatusCode::CONFLICT,
+                "invalid_reference",
+            ),
+            (
+                database_error(Some("22001"), None),
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "invalid_data",
+            ),
+            // 코드가 없거나 숫자가 아닌 코드도 패닉 없이 처리되어야 한다
+            (
+                database_error(None, None),
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "database_error",
+            ),
+            (
+                database_error(Some("XX000"), None),
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "database_error",
+            ),
+            (
+                Error::DatabaseQueryError(sqlx::Error::RowNotFound),
+                StatusCode::NOT_FOUND,
+                "not_found",
+            ),
+            (
+                Error::DatabaseQueryError(sqlx::Error::PoolTimedOut),
+                StatusCode::SERVICE_UNAVAILABLE,
+                "database_unavailable",
+            ),
+            (
+                Error::DatabaseQueryError(sqlx::Error::PoolClosed),
+                StatusCode::SERVICE_UNAVAILABLE,
+                "database_unavailable",
+            ),
+            (
+                Error::DatabaseQueryError(sqlx::Error::Io(std::io::Error::new(
+                    std::io::ErrorKind::ConnectionRefused,
+                    "connection refused",
+                ))),
+                StatusCode::SERVICE_UNAVAILABLE,
+                "database_unavailable",
+            ),
+            (
+                Error::DatabaseQueryError(sqlx::Error::Protocol("unexpected message".to_string())),
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "database_error",
+            ),
+            (
+                Error::DatabaseQueryError(sqlx::Error::ColumnNotFound("version".to_string())),
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "database_error",
+            ),
+        ];
+
+        for (error, status, code) in cases {
+            assert_error(error, status, code).await;
+        }
+    }
+
+    #[tokio::test]
+    async fn warp_rejections() {
+        let (status, body) = render(warp::reject::not_found()).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body["code"], "route_not_found");
+
+        let missing_token = warp::test::request()
+            .filter(&warp::header::<String>("authorization"))
+            .await
+            .unwrap_err();
+        let (status, body) = render(missing_token).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(body["code"], "missing_token");
+
+        let bad_body = warp::test::request()
+            .body("{")
+            .filter(&warp::body::json::<Value>())
+            .await
+            .unwrap_err();
+        let (status, body) = render(bad_body).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["code"], "invalid_body");
+
+        let wrong_method = warp::test::request()
+            .method("DELETE")
+            .filter(&warp::get())
+            .await
+            .unwrap_err();
+        let (status, body) = render(wrong_method).await;
+        assert_eq!(status, StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(body["code"], "method_not_allowed");
+    }
+
+    /// 앱처럼 경로를 먼저, 메서드를 나중에 검사하는 라우트
+    async fn route(method: &str, path: &str, body: &str) -> (StatusCode, Value) {
+        let questions = warp::path("questions").and(warp::path::end());
+        let question = warp::path("questions")
+            .and(warp::path::param::<i32>())
+            .and(warp::path::end());
+        let routes = questions
+            .and(warp::get())
+            .map(warp::reply)
+            .or(questions
+                .and(warp::post())
+                .and(warp::body::json::<Value>())
+                .map(|_| warp::reply()))
+            .or(question.and(warp::put()).and_then(|_| async {
+                Err::<String, _>(warp::reject::custom(Error::PreconditionFailed))
+            }))
+            .or(question.and(warp::patch()).and_then(|_| async {
+                Err::<String, _>(warp::reject::custom(Error::InvalidBody(
+                    "invalid type: null".to_string(),
+                )))
+            }));
+
+        let rejection = match warp::test::request()
+            .method(method)
+            .path(path)
+            .body(body)
+            .filter(&routes)
+            .await
+        {
+            Ok(_) => panic!("expected {} {} to be rejected", method, path),
+            Err(r) => r,
+        };
+        render(rejection).await
+    }
+
+    #[tokio::test]
+    async fn route_rejections() {
+        let cases = vec![
+            (
+                "GET",
+                "/nowhere",
+                "",
+                StatusCode::NOT_FOUND,
+                "route_not_found",
+            ),
+            (
+                "POST",
+                "/questions/1/nowhere",
+                "",
+                StatusCode::NOT_FOUND,
+                "route_not_found",
+            ),
+            (
+                "DELETE",
+                "/questions",
+                "",
+                StatusCode::METHOD_NOT_ALLOWED,
+                "method_not_allowed",
+            ),
+            (
+                "POST",
+                "/questions",
+                "{",
+                StatusCode::BAD_REQUEST,
+                "invalid_body",
+            ),
+            (
+                "PATCH",
+                "/questions/1",
+                "",
+                StatusCode::BAD_REQUEST,
+                "invalid_body",
+            ),
+            (
+                "PUT",
+                "/questions/1",
+                "",
+                StatusCode::PRECONDITION_FAILED,
+                "version_mismatch",
+            ),
+        ];
+
+        for (method, path, body, status, code) in cases {
+            let (actual_status, problem) = route(method, path, body).await;
+            assert_eq!(actual_status, status, "{} {}", method, path);
+            assert_eq!(problem["code"], code, "{} {}", method, path);
+        }
+    }
+
+    #[tokio::test]
+    async fn custom_error_wins_over_combined_rejections() {
+        let filter = warp::post().map(warp::reply).or(warp::get()
+            .and_then(|| async { Err::<String, _>(warp::reject::custom(Error::NotFound)) }));
+        let rejection = match warp::test::request().filter(&filter).await {
+            Ok(_) => panic!("expected the request to be rejected"),
+            Err(r) => r,
+        };
+        let (status, body) = render(rejection).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body["code"], "not_found");
     }
 }
@@ -0,0 +1,293 @@
+//! 통합 테스트 하네스
+//!
+//! 테스트마다 새 스키마에 마이그레이션을 적용하고, 빈 포트에 앱과 APILayer, LLM 목 서버를 띄운다.
+//! DB 연결 정보는 앱과 같은 `POSTGRES_*` 환경 변수(또는 `.env`)에서 읽는다.
+//! Postgres 에 연결할 수 없으면 실패한다. `SKIP_DB_TESTS=1` 이면 대신 건너뛴다.
+
+use clap::Parser;
+use mock_server::{fixtures, Mock, MockResponse, MockServer};
+use serde_json::{json, Value};
+use sqlx::{Connection, Executor, PgConnection};
+use std::env;
+use std::time::Duration;
+use warp_chatbot::{config::Config, connect_store, oneshot, OneshotHandler};
+
+/// 목 APILayer 가 받아 주는 키
+pub const API_KEY: &str = "integration-test-key";
+/// 목 APILayer 가 가리는 단어
+pub const BAD_WORD: &str = "shitty";
+pub const PASSWORD: &str = "password123";
+/// 테스트 앱이 토큰을 만드는 키
+const PASETO_KEY: &str = "RANDOM WORDS WINTER MACINTOSH PC";
+/// 목 LLM 이 돌려주는 답의 앞부분
+pub const LLM_ANSWER: &str = "According to the archive";
+/// 프롬프트에 이 글이 있으면 목 LLM 이 LLM_DELAY 만큼 기다린 뒤에 답한다
+pub const SLOW_QUESTION: &str = "Tuning slow warp handlers";
+pub const LLM_DELAY: Duration = Duration::from_secs(2);
+
+pub struct TestApp {
+    /// `http://127.0.0.1:PORT`
+    pub url: String,
+    pub client: reqwest::Client,
+    pub apilayer: MockServer,
+    /// 프롬프트의 첫 번째 참고 자료를 인용해서 답하는 LLM (OpenAI, Anthropic 형식, SLOW_QUESTION 은 늦게 답한다)
+    pub llm: MockServer,
+    /// 앱을 띄운 설정 (임베딩 백필처럼 앱 밖에서 같은 스키마를 쓸 때)
+    pub config: Config,
+    db_url: String,
+    schema: String,
+    handler: Option<OneshotHandler>,
+}
+
+impl TestApp {
+    /// SKIP_DB_TESTS 를 설정했고 Postgres 에 연결할 수 없으면 None 을 돌려준다 (테스트를 건너뛴다)
+    pub async fn spawn() -> Option<TestApp> {
+        TestApp::spawn_with(|_| {}).await
+    }
+
+    /// 목 서버 주소까지 채운 설정을 앱을 띄우기 전에 고친다
+    pub async fn spawn_with(configure: impl FnOnce(&mut Config)) -> Option<TestApp> {
+        dotenv::dotenv().ok();
+
+        let mut config = Config::parse_from(["integration-tests"]);
+        config.paseto_key = PASETO_KEY.to_string();
+        config.db_user = env::var("POSTGRES_USER").unwrap_or(config.db_user);
+        config.db_password = env::var("POSTGRES_PASSWORD").unwrap_or(config.db_password);
+        config.db_host = env::var("POSTGRES_HOST").unwrap_or(config.db_host);
+        config.db_port = env::var("POSTGRES_PORT")
+            .ok()
+            .and_then(|port| port.parse().ok())
+            .unwrap_or(config.db_port);
+        config.db_name = env::var("POSTGRES_DB").unwrap_or(config.db_name);
+        let db_url = format!(
+            "postgres://{}:{}@{}:{}/{}",
+            config.db_user, config.db_password, config.db_host, config.db_port, config.db_name
+        );
+
+        let mut conn = match PgConnection::connect(&db_url).await {
+            Ok(conn) => conn,
+            Err(e) if env::var_os("SKIP_DB_TESTS").is_some() => {
+                eprintln!(
+                    "skipping integration test: Postgres at {}:{} is unavailable: {}",
+                    config.db_host, config.db_port, e
+                );
+                return None;
+            }
+            Err(e) => panic!(
+                "Postgres at {}:{} is unavailable (set SKIP_DB_TESTS=1 to skip): {}",
+                config.db_host, config.db_port, e
+            ),
+        };
+        let schema = format!("it_{}", uuid::Uuid::new_v4().to_simple());
+        conn.execute(format!("CREATE SCHEMA \"{}\"", schema).as_str())
+            .await
+            .expect("test schema cannot be created");
+        config.db_schema = Some(schema.clone());
+
+        let apilayer = MockServer::builder()
+            .mock(fixtures::apilayer::bad_words(API_KEY, &[BAD_WORD]))
+            .start();
+        config.api_layer_url = apilayer.url();
+        config.bad_words_api_key = API_KEY.to_string();
+
+        let llm = MockServer::builder()
+            .mock(fixtures::llm::chat_fn(cite_first_label))
+            .mock(fixtures::llm::messages_fn(cite_first_label))
+            .mock(
+                Mock::post("/v1/chat/completions")
+                    .body_contains(SLOW_QUESTION)
+                    .respond_with(MockResponse::sse(["[DONE]"]).delay(LLM_DELAY)),
+            )
+            .start();
+        config.llm_url = llm.url();
+        // 임베딩 워커는 시작할 때만 돌게 해서 테스트가 백필 결과를 정확히 셀 수 있게 한다
+        config.embedding_interval_secs = 24 * 60 * 60;
+        configure(&mut config);
+
+        let store = connect_store(&config)
+            .await
+            .expect("test schema cannot be migrated");
+        let handler = oneshot(&config, store)
+            .await
+            .expect("test server cannot be started");
+
+        Some(TestApp {
+            url: format!("http://{}", handler.addr),
+            client: reqwest::Client::new(),
+            apilayer,
+            llm,
+            config,
+            db_url,
+            schema,
+            handler: Some(handler),
+        })
+    }
+
+    pub fn get(&self, path: &str) -> reqwest::RequestBuilder {
+        self.client.get(format!("{}{}", self.url, path))
+    }
+
+    pub fn post(&self, path: &str) -> reqwest::RequestBuilder {
+        self.client.post(format!("{}{}", self.url, path))
+    }
+
+    pub fn put(&self, path: &str) -> reqwest::RequestBuilder {
+        self.client.put(format!("{}{}", self.url, path))
+    }
+
+    pub fn patch(&self, path: &str) -> reqwest::RequestBuilder {
+        self.client.patch(format!("{}{}", self.url, path))
+    }
+
+    pub fn delete(&self, path: &str) -> reqwest::RequestBuilder {
+        self.client.delete(format!("{}{}", self.url, path))
+    }
+
+    pub async fn register(&self, email: &str, password: &str) -> reqwest::Response {
+        self.post("/registration")
+            .json(&json!({ "email": email, "password": password }))
+            .send()
+            .await
+            .expect("registration request failed")
+    }
+
+    pub async fn login(&self, email: &str, password: &str) -> reqwest::Response {
+        self.post("/login")
+            .json(&json!({ "email": email, "password": password }))
+            .send()
+            .await
+            .expect("login request failed")
+    }
+
+    /// 가입하고 로그인해서 토큰을 돌려준다
+    pub async fn sign_up(&self, email: &str) -> String {
+        assert_eq!(self.register(email, PASSWORD).await.status(), 200);
+        let res = self.login(email, PASSWORD).await;
+        assert_eq!(res.status(), 200);
+        res.json::<String>().await.expect("token is not a string")
+    }
+
+    /// 가입한 계정을 모더레이터로 만든다 (API 로는 바꿀 수 없다)
+    pub async fn make_moderator(&self, email: &str) {
+        let mut conn = PgConnection::connect(&self.db_url)
+            .await
+            .expect("test database is unavailable");
+        sqlx::query(&format!(
+            "UPDATE \"{}\".accounts SET is_moderator = true WHERE email = $1",
+            self.schema
+        ))
+        .bind(email)
+        .execute(&mut conn)
+        .await
+        .expect("moderator can't be set");
+    }
+
+    /// 테스트 스키마에 바로 연결한다 (API 로 볼 수 없는 상태를 확인할 때)
+    pub async fn db(&self) -> PgConnection {
+        let mut conn = PgConnection::connect(&self.db_url)
+            .await
+            .expect("test database is unavailable");
+        conn.execute(format!("SET search_path TO \"{}\", public", self.schema).as_str())
+            .await
+            .expect("search path can't be set");
+        conn
+    }
+
+    /// `file` 파트 하나로 문서를 올린다
+    pub async fn upload_document(
+        &self,
+        token: &str,
+        filename: &str,
+        content: impl Into<Vec<u8>>,
+    ) -> reqwest::Response {
+        let form = reqwest::multipart::Form::new().part(
+            "file",
+            reqwest::multipart::Part::bytes(content.into()).file_name(filename.to_string()),
+        );
+        self.post("/documents")
+            .header("Authorization", token)
+            .multipart(form)
+            .send()
+            .await
+            .expect("upload document request failed")
+    }
+
+    pub async fn add_question(&self, token: &str, question: &Value) -> reqwest::Response {
+        self.post("/questions")
+            .header("Authorization", token)
+            .json(question)
+            .send()
+            .await
+            .expect("add question request failed")
+    }
+
+    /// 질문을 만들고 응답 본문을 돌려준다
+    pub async fn create_question(&self, token: &str, title: &str) -> Value {
+        let res = self
+            .add_question(
+                token,
+                &json!({ "title": title, "content": "How can I test?", "tags": ["test"] }),
+            )
+            .await;
+        assert_eq!(res.status(), 200);
+        res.json().await.expect("question is not JSON")
+    }
+
+    pub async fn add_answer(
+        &self,
+        token: &str,
+        question_id: i64,
+        content: &str,
+    ) -> reqwest::Response {
+        self.post("/answers")
+            .header("Authorization", token)
+            .form(&[
+                ("content", content.to_string()),
+                ("question_id", question_id.to_string()),
+            ])
+            .send()
+            .await
+            .expect("add answer request failed")
+    }
+}
+
+/// 서버를 내리고 스키마를 지운다
+/// 패닉으로 끝난 테스트에서도 불리도록 Drop 에서 별도 런타임으로 정리한다
+impl Drop for TestApp {
+    fn drop(&mut self) {
+        if let Some(handler) = self.handler.take() {
+            let _ = handler.sender.send(1);
+        }
+
+        let db_url = self.db_url.clone();
+        let schema = self.schema.clone();
+        let _ = std::thread::spawn(move || {
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .ok()?;
+            runtime.block_on(async {
+                let mut conn = PgConnection::connect(&db_url).await.ok()?;
+                conn.execute(format!("DROP SCHEMA IF EXISTS \"{}\" CASCADE", schema).as_str())
+                    .await
+                    .ok()
+            })
+        })
+        .join();
+    }
+}
+
+/// 프롬프트의 첫 번째 `[라벨]` 을 인용해서 답한다
+fn cite_first_label(messages: &[Value]) -> String {
+    let prompt = messages
+        .last()
+        .and_then(|message| message["content"].as_str())
+        .unwrap_or_default();
+    match prompt
+        .split_once('[')
+        .and_then(|(_, rest)| rest.split_once(']'))
+    {
+        Some((label, _)) => format!("{} [{}]", LLM_ANSWER, label),
+        None => LLM_ANSWER.to_string(),
+    }
+}
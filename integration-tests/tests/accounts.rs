@@ -0,0 +1,84 @@
+use integration_tests::{TestApp, PASSWORD};
+use serde_json::Value;
+
+#[tokio::test]
+async fn register_and_login() {
+    let Some(app) = TestApp::spawn().await else {
+        return;
+    };
+
+    let res = app.register("test@email.com", PASSWORD).await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.json::<Value>().await.unwrap(), "Account added");
+
+    let res = app.login("test@email.com", PASSWORD).await;
+    assert_eq!(res.status(), 200);
+    let token = res.json::<String>().await.unwrap();
+    assert!(!token.is_empty());
+}
+
+#[tokio::test]
+async fn duplicate_registration_conflicts() {
+    let Some(app) = TestApp::spawn().await else {
+        return;
+    };
+
+    assert_eq!(app.register("dup@email.com", PASSWORD).await.status(), 200);
+    let res = app.register("dup@email.com", PASSWORD).await;
+    assert_eq!(res.status(), 409);
+    let body: Value = res.json().await.unwrap();
+    assert_eq!(body["code"], "account_exists");
+}
+
+#[tokio::test]
+async fn invalid_registration_reports_fields() {
+    let Some(app) = TestApp::spawn().await else {
+        return;
+    };
+
+    let res = app.register("not-an-email", "short").await;
+    assert_eq!(res.status(), 400);
+    let body: Value = res.json().await.unwrap();
+    assert_eq!(body["code"], "validation_failed");
+    let fields: Vec<&str> = body["errors"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|error| error["field"].as_str().unwrap())
+        .collect();
+    assert_eq!(fields, vec!["email", "password"]);
+}
+
+#[tokio::test]
+async fn wrong_password_is_rejected() {
+    let Some(app) = TestApp::spawn().await else {
+        return;
+    };
+
+    app.register("user@email.com", PASSWORD).await;
+    let res = app.login("user@email.com", "wrongpassword1").await;
+    assert_eq!(res.status(), 401);
+    let body: Value = res.json().await.unwrap();
+    assert_eq!(body["code"], "wrong_credentials");
+
+    let res = app.login("nobody@email.com", PASSWORD).await;
+    assert_eq!(res.status(), 404);
+}
+
+#[tokio::test]
+async fn invalid_token_is_rejected() {
+    let Some(app) = TestApp::spawn().await else {
+        return;
+    };
+
+    let res = app
+        .post("/questions")
+        .header("Authorization", "not-a-token")
+        .json(&serde_json::json!({ "title": "t", "content": "c" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 401);
+    let body: Value = res.json().await.unwrap();
+    assert_eq!(body["code"], "invalid_token");
+}
@@ -0,0 +1,50 @@
+use integration_tests::TestApp;
+use serde_json::Value;
+
+#[tokio::test]
+async fn answer_lifecycle() {
+    let Some(app) = TestApp::spawn().await else {
+        return;
+    };
+    let token = app.sign_up("answers@email.com").await;
+    let question_id = app.create_question(&token, "Answer me").await["id"]
+        .as_i64()
+        .unwrap();
+
+    let res = app.add_answer(&token, question_id, "Here is how").await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.text().await.unwrap(), "Answer added");
+
+    // 답변 id 는 스키마마다 1 부터 시작한다
+    let res = app
+        .delete("/answers/1")
+        .header("Authorization", &token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 200);
+
+    let res = app
+        .post("/answers/1/restore")
+        .header("Authorization", &token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 200);
+}
+
+#[tokio::test]
+async fn answer_requires_an_existing_question() {
+    let Some(app) = TestApp::spawn().await else {
+        return;
+    };
+    let token = app.sign_up("orphan@email.com").await;
+
+    let res = app.add_answer(&token, 9999, "Nobody asked").await;
+    assert!(res.status().is_client_error());
+    let body: Value = res.json().await.unwrap();
+    assert!(body["code"].is_string());
+
+    let res = app.add_answer(&token, 9999, "").await;
+    assert_eq!(res.status(), 400);
+}
@@ -0,0 +1,150 @@
+use integration_tests::{TestApp, LLM_ANSWER};
+use serde_json::{json, Value};
+
+#[tokio::test]
+async fn answers_from_the_archive_with_citations() {
+    let Some(app) = TestApp::spawn().await else {
+        return;
+    };
+    let token = app.sign_up("chat@email.com").await;
+    let warp_id = app.create_question(&token, "Composing warp filters").await["id"]
+        .as_i64()
+        .unwrap();
+    let pasta_id = app.create_question(&token, "Cooking pasta").await["id"]
+        .as_i64()
+        .unwrap();
+    let res = app
+        .add_answer(&token, warp_id, "Chain filters with and")
+        .await;
+    assert_eq!(res.status(), 200);
+
+    let res = app
+        .post("/chat")
+        .header("Authorization", &token)
+        .json(&json!({ "question": "composing warp filters" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 200);
+    let body: Value = res.json().await.unwrap();
+
+    assert_eq!(
+        body["answer"],
+        format!("{} [Q{}]", LLM_ANSWER, warp_id).as_str()
+    );
+    let citations = body["citations"].as_array().unwrap();
+    assert_eq!(citations.len(), 2, "question and its answer: {}", body);
+    assert!(citations
+        .iter()
+        .all(|citation| citation["question_id"] == warp_id && citation["cited"] == true));
+    assert!(citations.iter().any(|citation| citation["answer_id"] == 1));
+    assert!(!body
+        .to_string()
+        .contains(&format!("\"question_id\":{}", pasta_id)));
+
+    let prompt = app.llm.requests()[0].json().unwrap();
+    let context = prompt["messages"][1]["content"].as_str().unwrap();
+    assert!(context.contains("Chain filters with and"));
+    assert!(!context.contains("Cooking pasta"));
+}
+
+#[tokio::test]
+async fn does_not_call_the_model_without_context() {
+    let Some(app) = TestApp::spawn().await else {
+        return;
+    };
+    let token = app.sign_up("chat-empty@email.com").await;
+    app.create_question(&token, "Composing warp filters").await;
+
+    let res = app
+        .post("/chat")
+        .header("Authorization", &token)
+        .json(&json!({ "question": "quantum chromodynamics" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 200);
+    let body: Value = res.json().await.unwrap();
+    assert_eq!(body["citations"], json!([]));
+    assert_eq!(app.llm.requests().len(), 0);
+}
+
+#[tokio::test]
+async fn rejects_invalid_requests() {
+    let Some(app) = TestApp::spawn().await else {
+        return;
+    };
+    let token = app.sign_up("chat-invalid@email.com").await;
+
+    let res = app
+        .post("/chat")
+        .header("Authorization", &token)
+        .json(&json!({ "question": " ", "top_k": 0 }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 400);
+    let body: Value = res.json().await.unwrap();
+    assert_eq!(body["code"], "validation_failed");
+    let fields: Vec<&str> = body["errors"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|error| error["field"].as_str().unwrap())
+        .collect();
+    assert_eq!(fields, vec!["question", "top_k"]);
+
+    let res = app
+        .post("/chat")
+        .json(&json!({ "question": "warp" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 401);
+}
+
+#[tokio::test]
+async fn filters_by_source_type_and_updated_at() {
+    let Some(app) = TestApp::spawn().await else {
+        return;
+    };
+    let token = app.sign_up("chat-filter@email.com").await;
+    let question_id = app.create_question(&token, "Composing warp filters").await["id"]
+        .as_i64()
+        .unwrap();
+    let res = app
+        .add_answer(&token, question_id, "Chain warp filters with and")
+        .await;
+    assert_eq!(res.status(), 200);
+
+    let chat = |filter: Value| {
+        app.post("/chat")
+            .header("Authorization", &token)
+            .json(&json!({ "question": "warp filters", "filter": filter }))
+            .send()
+    };
+
+    let body: Value = chat(json!({ "source_type": ["answer"] }))
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let citations = body["citations"].as_array().unwrap();
+    assert_eq!(citations.len(), 1, "{}", body);
+    assert_eq!(citations[0]["answer_id"], 1);
+
+    let body: Value = chat(json!({ "updated_after": "2999-01-01T00:00:00Z" }))
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(body["citations"], json!([]));
+    assert_eq!(app.llm.requests().len(), 1);
+
+    let res = chat(json!({ "source_type": ["video"] })).await.unwrap();
+    assert_eq!(res.status(), 400);
+    let body: Value = res.json().await.unwrap();
+    assert_eq!(body["code"], "invalid_body");
+}
@@ -0,0 +1,192 @@
+use integration_tests::{TestApp, LLM_ANSWER};
+use serde_json::{json, Value};
+use warp_chatbot::connect_store;
+use warp_chatbot::types::conversation::ConversationId;
+
+async fn chat(app: &TestApp, token: &str, body: Value) -> reqwest::Response {
+    app.post("/chat")
+        .header("Authorization", token)
+        .json(&body)
+        .send()
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn follow_ups_carry_the_conversation() {
+    let Some(app) = TestApp::spawn().await else {
+        return;
+    };
+    let token = app.sign_up("conversation@email.com").await;
+    let warp_id = app.create_question(&token, "Composing warp filters").await["id"]
+        .as_i64()
+        .unwrap();
+
+    let res = chat(
+        &app,
+        &token,
+        json!({ "question": "composing warp filters" }),
+    )
+    .await;
+    assert_eq!(res.status(), 200);
+    let first: Value = res.json().await.unwrap();
+    let id = first["conversation_id"].as_i64().unwrap();
+
+    // 후속 질문만으로는 찾을 수 없지만 직전 질문과 함께 검색한다
+    let res = chat(
+        &app,
+        &token,
+        json!({ "question": "and then?", "conversation_id": id }),
+    )
+    .await;
+    assert_eq!(res.status(), 200);
+    let second: Value = res.json().await.unwrap();
+    assert_eq!(second["conversation_id"], id);
+    assert_eq!(second["answer"], format!("{} [Q{}]", LLM_ANSWER, warp_id));
+
+    let prompt = app.llm.requests()[1].json().unwrap();
+    let messages = prompt["messages"].as_array().unwrap();
+    assert_eq!(messages.len(), 4);
+    assert_eq!(messages[1]["content"], "composing warp filters");
+    assert_eq!(messages[2]["role"], "assistant");
+    assert_eq!(messages[2]["content"], first["answer"]);
+
+    let list: Vec<Value> = app
+        .get("/conversations")
+        .header("Authorization", &token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(list.len(), 1);
+    assert_eq!(list[0]["id"], id);
+    assert_eq!(list[0]["title"], "composing warp filters");
+
+    let res = app
+        .get(&format!("/conversations/{}", id))
+        .header("Authorization", &token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 200);
+    let detail: Value = res.json().await.unwrap();
+    let messages = detail["messages"].as_array().unwrap();
+    let roles: Vec<_> = messages.iter().map(|m| m["role"].clone()).collect();
+    assert_eq!(roles, ["user", "assistant", "user", "assistant"]);
+    assert_eq!(messages[2]["content"], "and then?");
+    assert_eq!(messages[3]["citations"][0]["question_id"], warp_id);
+    assert_eq!(messages[0]["citations"], json!([]));
+
+    let res = app
+        .delete(&format!("/conversations/{}", id))
+        .header("Authorization", &token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 200);
+    let res = app
+        .get(&format!("/conversations/{}", id))
+        .header("Authorization", &token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 404);
+}
+
+#[tokio::test]
+async fn conversations_are_private() {
+    let Some(app) = TestApp::spawn().await else {
+        return;
+    };
+    let owner = app.sign_up("owner@email.com").await;
+    let other = app.sign_up("other@email.com").await;
+    app.create_question(&owner, "Composing warp filters").await;
+    let res = chat(&app, &owner, json!({ "question": "warp filters" })).await;
+    let id = res.json::<Value>().await.unwrap()["conversation_id"]
+        .as_i64()
+        .unwrap();
+
+    let res = app
+        .get(&format!("/conversations/{}", id))
+        .header("Authorization", &other)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 404);
+    let res = chat(
+        &app,
+        &other,
+        json!({ "question": "warp filters", "conversation_id": id }),
+    )
+    .await;
+    assert_eq!(res.status(), 404);
+    let res = app
+        .delete(&format!("/conversations/{}", id))
+        .header("Authorization", &other)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 404);
+
+    let list: Vec<Value> = app
+        .get("/conversations")
+        .header("Authorization", &other)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert!(list.is_empty());
+    let res = app.get("/conversations").send().await.unwrap();
+    assert_eq!(res.status(), 401);
+}
+
+#[tokio::test]
+async fn history_loads_only_recent_messages() {
+    let Some(app) = TestApp::spawn().await else {
+        return;
+    };
+    let store = connect_store(&app.config).await.unwrap();
+    let token = app.sign_up("recent@email.com").await;
+    app.create_question(&token, "Composing warp filters").await;
+    let first: Value = chat(&app, &token, json!({ "question": "warp filters" }))
+        .await
+        .json()
+        .await
+        .unwrap();
+    let id = first["conversation_id"].as_i64().unwrap() as i32;
+    let second: Value = chat(
+        &app,
+        &token,
+        json!({ "question": "and then?", "conversation_id": id }),
+    )
+    .await
+    .json()
+    .await
+    .unwrap();
+
+    // 한도를 넘기는 첫 메시지(and then?)까지만 읽는다
+    let last = second["answer"].as_str().unwrap().chars().count() as i64;
+    let recent = store
+        .get_recent_messages(&ConversationId(id), None, last + 1)
+        .await
+        .unwrap();
+    let contents: Vec<_> = recent.iter().map(|m| m.content.as_str()).collect();
+    assert_eq!(contents, ["and then?", second["answer"].as_str().unwrap()]);
+
+    // 요약한 메시지는 읽지 않는다
+    let all = store
+        .get_recent_messages(&ConversationId(id), None, 10_000)
+        .await
+        .unwrap();
+    assert_eq!(all.len(), 4);
+    let after = store
+        .get_recent_messages(&ConversationId(id), Some(&all[1].id), 10_000)
+        .await
+        .unwrap();
+    assert_eq!(after.len(), 2);
+    assert!(after[0].id > all[1].id);
+}
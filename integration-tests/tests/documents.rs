@@ -0,0 +1,152 @@
+use integration_tests::{TestApp, LLM_ANSWER};
+use serde_json::{json, Value};
+use warp_chatbot::{backfill_embeddings, connect_store, import_documents};
+
+const GUIDE: &str = "# Deployment\n\nRead this first.\n\n## Docker\n\nBuild the image with docker build and start it with compose.\n";
+
+async fn ask(app: &TestApp, token: &str, body: Value) -> Value {
+    let res = app
+        .post("/chat")
+        .header("Authorization", token)
+        .json(&body)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 200);
+    res.json().await.unwrap()
+}
+
+#[tokio::test]
+async fn uploaded_documents_are_cited_by_chat() {
+    let Some(app) = TestApp::spawn().await else {
+        return;
+    };
+    let token = app.sign_up("docs@email.com").await;
+    app.make_moderator("docs@email.com").await;
+
+    let res = app.upload_document(&token, "deploy.md", GUIDE).await;
+    assert_eq!(res.status(), 200);
+    let document: Value = res.json().await.unwrap();
+    let id = document["id"].as_i64().unwrap();
+    assert_eq!(document["title"], "deploy");
+    assert_eq!(document["source_type"], "markdown");
+    assert_eq!(document["chunks"], 2);
+
+    let res = ask(
+        &app,
+        &token,
+        json!({ "question": "How do I build the docker image?" }),
+    )
+    .await;
+    assert_eq!(res["answer"], format!("{} [D{}]", LLM_ANSWER, id));
+    assert_eq!(res["citations"][0]["document_id"], id);
+    assert_eq!(res["citations"][0]["question_id"], Value::Null);
+    assert_eq!(res["citations"][0]["title"], "deploy > Deployment > Docker");
+    assert_eq!(res["citations"][0]["cited"], true);
+
+    // README 처럼 md 로도 거를 수 있다
+    let res = ask(
+        &app,
+        &token,
+        json!({ "question": "docker compose", "filter": { "source_type": ["md"] } }),
+    )
+    .await;
+    assert_eq!(res["citations"][0]["document_id"], id);
+    let res = ask(
+        &app,
+        &token,
+        json!({ "question": "docker compose", "filter": { "source_type": ["question", "pdf"] } }),
+    )
+    .await;
+    assert!(res["citations"].as_array().unwrap().is_empty());
+
+    let res = app
+        .delete(&format!("/documents/{}", id))
+        .header("Authorization", &token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 200);
+    let res = ask(&app, &token, json!({ "question": "docker compose" })).await;
+    assert!(res["citations"].as_array().unwrap().is_empty());
+
+    let res = app
+        .delete(&format!("/documents/{}", id))
+        .header("Authorization", &token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 404);
+}
+
+#[tokio::test]
+async fn only_moderators_upload_supported_files() {
+    let Some(app) = TestApp::spawn().await else {
+        return;
+    };
+    let user = app.sign_up("user@email.com").await;
+    let moderator = app.sign_up("moderator@email.com").await;
+    app.make_moderator("moderator@email.com").await;
+
+    let res = app.upload_document(&user, "deploy.md", GUIDE).await;
+    assert_eq!(res.status(), 403);
+
+    let res = app
+        .upload_document(&moderator, "slides.pptx", "not text")
+        .await;
+    assert_eq!(res.status(), 400);
+    let body: Value = res.json().await.unwrap();
+    assert_eq!(body["code"], "validation_failed");
+    assert_eq!(body["errors"][0]["field"], "file");
+
+    let res = app
+        .post("/documents")
+        .header("Authorization", &moderator)
+        .json(&json!({ "file": GUIDE }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 400);
+
+    let res = app
+        .post("/documents")
+        .multipart(reqwest::multipart::Form::new().text("file", GUIDE))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 401);
+}
+
+#[tokio::test]
+async fn cli_import_is_embedded_and_searchable() {
+    let Some(app) = TestApp::spawn().await else {
+        return;
+    };
+    let store = connect_store(&app.config).await.unwrap();
+    let token = app.sign_up("import@email.com").await;
+    let dir = std::env::temp_dir().join(format!("import-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("runbook.txt");
+    std::fs::write(&path, "Rotate the paseto key every quarter.").unwrap();
+
+    let imported = import_documents(&app.config, store.clone(), &[path])
+        .await
+        .unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert_eq!(imported, vec![("runbook.txt".to_string(), 1)]);
+    assert_eq!(
+        backfill_embeddings(&app.config, store.clone())
+            .await
+            .unwrap(),
+        1
+    );
+
+    let res = ask(
+        &app,
+        &token,
+        json!({ "question": "When to rotate the key?" }),
+    )
+    .await;
+    assert_eq!(res["citations"][0]["title"], "runbook");
+    assert_eq!(res["citations"][0]["cited"], true);
+}
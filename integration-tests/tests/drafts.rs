@@ -0,0 +1,316 @@
+use integration_tests::{TestApp, BAD_WORD, LLM_ANSWER, PASSWORD};
+use mock_server::{fixtures, Mock, MockResponse, MockServer};
+use serde_json::{json, Value};
+use std::time::{Duration, Instant};
+use warp_chatbot::config::ProfanityAction;
+
+async fn drafts(app: &TestApp, token: &str) -> Vec<Value> {
+    let res = app
+        .get("/answers/drafts")
+        .header("Authorization", token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 200);
+    res.json().await.unwrap()
+}
+
+/// 워커가 초안을 count 개 만들 때까지 기다린다
+async fn wait_for_drafts(app: &TestApp, token: &str, count: usize) -> Vec<Value> {
+    let deadline = Instant::now() + Duration::from_secs(10);
+    loop {
+        let drafts = drafts(app, token).await;
+        if drafts.len() >= count || Instant::now() > deadline {
+            return drafts;
+        }
+        tokio::time::sleep(Duration::from_millis(200)).await;
+    }
+}
+
+#[tokio::test]
+async fn drafts_answers_for_unanswered_questions() {
+    let Some(app) = TestApp::spawn_with(|config| {
+        config.ai_drafts = true;
+        config.ai_draft_delay_mins = 0;
+        config.ai_draft_interval_secs = 1;
+    })
+    .await
+    else {
+        return;
+    };
+    let token = app.sign_up("owner@email.com").await;
+    let other = app.sign_up("other@email.com").await;
+    let composing = app.create_question(&token, "Composing warp filters").await["id"].clone();
+    let testing = app.create_question(&token, "Testing warp handlers").await["id"].clone();
+
+    let pending = wait_for_drafts(&app, &token, 2).await;
+    assert_eq!(pending.len(), 2);
+    assert_eq!(pending[0]["question_id"], composing);
+    assert_eq!(pending[1]["question_id"], testing);
+    for draft in &pending {
+        assert_eq!(draft["is_ai"], true);
+        assert_eq!(draft["status"], "pending");
+        // 인용 라벨은 풀어 볼 인용 목록이 없으니 지운다
+        assert_eq!(draft["content"], LLM_ANSWER);
+    }
+    let accepted = pending[0]["id"].as_i64().unwrap();
+    let dismissed = pending[1]["id"].as_i64().unwrap();
+
+    let notifications: Vec<Value> = app
+        .get("/notifications")
+        .header("Authorization", &token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(notifications.len(), 2);
+    assert!(notifications
+        .iter()
+        .any(|notification| notification["answer_id"] == accepted));
+
+    // 질문 작성자만 결정할 수 있다
+    let res = app
+        .post(&format!("/answers/{}/accept", accepted))
+        .header("Authorization", &other)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 403);
+
+    let res = app
+        .post(&format!("/answers/{}/accept", accepted))
+        .header("Authorization", &token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 200);
+    let answer: Value = res.json().await.unwrap();
+    assert_eq!(answer["status"], "published");
+    assert_eq!(answer["is_ai"], true);
+
+    let res = app
+        .post(&format!("/answers/{}/dismiss", dismissed))
+        .header("Authorization", &token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.json::<Value>().await.unwrap()["status"], "rejected");
+
+    // 결정한 초안은 목록에서 빠지고 다시 결정할 수 없으며, 새 초안도 만들지 않는다
+    tokio::time::sleep(Duration::from_millis(1500)).await;
+    assert!(drafts(&app, &token).await.is_empty());
+    let res = app
+        .post(&format!("/answers/{}/accept", dismissed))
+        .header("Authorization", &token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 404);
+
+    // 모델 계정으로는 로그인할 수 없다
+    let res = app.login("assistant@ai.invalid", PASSWORD).await;
+    assert_eq!(res.status(), 404);
+}
+
+#[tokio::test]
+async fn waits_for_the_ai_account() {
+    let Some(app) = TestApp::spawn_with(|config| {
+        config.ai_drafts = true;
+        config.ai_draft_delay_mins = 0;
+        config.ai_draft_interval_secs = 1;
+    })
+    .await
+    else {
+        return;
+    };
+    let mut db = app.db().await;
+    sqlx::query("UPDATE accounts SET is_ai = FALSE")
+        .execute(&mut db)
+        .await
+        .unwrap();
+    let token = app.sign_up("owner@email.com").await;
+    app.create_question(&token, "Composing warp filters").await;
+    app.create_question(&token, "Testing warp handlers").await;
+
+    // 모델 계정이 없으면 초안을 버리고 질문도 만들어 본 것으로 남기지 않는다
+    tokio::time::sleep(Duration::from_millis(2500)).await;
+    assert!(drafts(&app, &token).await.is_empty());
+    let drafted: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM questions WHERE ai_drafted_at IS NOT NULL")
+            .fetch_one(&mut db)
+            .await
+            .unwrap();
+    assert_eq!(drafted, 0);
+
+    sqlx::query("UPDATE accounts SET is_ai = TRUE WHERE email = 'assistant@ai.invalid'")
+        .execute(&mut db)
+        .await
+        .unwrap();
+    assert_eq!(wait_for_drafts(&app, &token, 2).await.len(), 2);
+}
+
+#[tokio::test]
+async fn keeps_drafting_when_a_question_fails() {
+    let rejected_title = "Rejected by the model";
+    // 다른 질문의 프롬프트에는 참고 자료로 들어가므로 질문으로 보낸 것만 거절한다
+    let rejected_prompt = format!("질문: {}", rejected_title);
+    let llm = MockServer::builder()
+        .mock(fixtures::llm::chat(LLM_ANSWER))
+        .mock(
+            Mock::post("/v1/chat/completions")
+                .body_contains(&rejected_prompt)
+                .respond_with(MockResponse::json(
+                    400,
+                    &json!({ "error": { "message": "prompt rejected" } }),
+                )),
+        )
+        .start();
+    let rejected_calls = || {
+        llm.requests()
+            .iter()
+            .filter(|request| request.body_text().contains(&rejected_prompt))
+            .count()
+    };
+    let Some(app) = TestApp::spawn_with(|config| {
+        config.llm_url = llm.url();
+        config.ai_drafts = true;
+        config.ai_draft_delay_mins = 0;
+        config.ai_draft_interval_secs = 1;
+        config.ai_draft_max_attempts = 2;
+    })
+    .await
+    else {
+        return;
+    };
+    let token = app.sign_up("owner@email.com").await;
+    let rejected = app.create_question(&token, rejected_title).await["id"].clone();
+    let composing = app.create_question(&token, "Composing warp filters").await["id"].clone();
+    let testing = app.create_question(&token, "Testing warp handlers").await["id"].clone();
+
+    // 먼저 올라온 질문이 실패해도 뒤의 질문에는 초안을 단다
+    let pending = wait_for_drafts(&app, &token, 2).await;
+    assert_eq!(pending.len(), 2);
+    assert_eq!(pending[0]["question_id"], composing);
+    assert_eq!(pending[1]["question_id"], testing);
+
+    // 정해진 횟수만큼 실패하면 더는 시도하지 않는다
+    let mut db = app.db().await;
+    let deadline = Instant::now() + Duration::from_secs(10);
+    let (attempts, given_up) = loop {
+        let row: (i32, bool) = sqlx::query_as(
+            "SELECT ai_draft_attempts, ai_drafted_at IS NOT NULL FROM questions WHERE id = $1",
+        )
+        .bind(rejected.as_i64().unwrap() as i32)
+        .fetch_one(&mut db)
+        .await
+        .unwrap();
+        if row.1 || Instant::now() > deadline {
+            break row;
+        }
+        tokio::time::sleep(Duration::from_millis(200)).await;
+    };
+    assert!(given_up);
+    assert_eq!(attempts, 2);
+    assert_eq!(rejected_calls(), 2);
+    tokio::time::sleep(Duration::from_millis(1500)).await;
+    assert_eq!(rejected_calls(), 2);
+    assert_eq!(drafts(&app, &token).await.len(), 2);
+}
+
+/// 비속어가 섞인 초안을 질문 두 개에 달게 하고 첫 번째 초안을 돌려준다
+async fn profane_draft(
+    configure: impl FnOnce(&mut warp_chatbot::config::Config),
+) -> Option<(TestApp, MockServer, String, Value)> {
+    let llm = MockServer::builder()
+        .mock(fixtures::llm::chat(&format!("A {} answer [Q1]", BAD_WORD)))
+        .start();
+    let url = llm.url();
+    let app = TestApp::spawn_with(|config| {
+        config.llm_url = url;
+        config.ai_drafts = true;
+        config.ai_draft_delay_mins = 0;
+        config.ai_draft_interval_secs = 1;
+        configure(config);
+    })
+    .await?;
+    let token = app.sign_up("owner@email.com").await;
+    app.create_question(&token, "Composing warp filters").await;
+    app.create_question(&token, "Testing warp handlers").await;
+
+    let pending = wait_for_drafts(&app, &token, 2).await;
+    assert_eq!(pending.len(), 2);
+    // 비속어 검사는 받아들일 때 한다
+    assert_eq!(pending[0]["content"], format!("A {} answer", BAD_WORD));
+    let draft = pending[0].clone();
+    Some((app, llm, token, draft))
+}
+
+#[tokio::test]
+async fn accepting_a_draft_censors_profanity() {
+    let Some((app, _llm, token, draft)) = profane_draft(|_| {}).await else {
+        return;
+    };
+
+    let res = app
+        .post(&format!("/answers/{}/accept", draft["id"]))
+        .header("Authorization", &token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 200);
+    let answer: Value = res.json().await.unwrap();
+    assert_eq!(answer["status"], "published");
+    assert_eq!(
+        answer["content"],
+        format!("A {} answer", "*".repeat(BAD_WORD.len()))
+    );
+}
+
+#[tokio::test]
+async fn accepting_a_profane_draft_queues_it() {
+    let Some((app, _llm, token, draft)) = profane_draft(|config| {
+        config.profanity_action = ProfanityAction::Queue;
+    })
+    .await
+    else {
+        return;
+    };
+    let moderator = app.sign_up("moderator@email.com").await;
+    app.make_moderator("moderator@email.com").await;
+
+    let res = app
+        .post(&format!("/answers/{}/accept", draft["id"]))
+        .header("Authorization", &token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 200);
+    let answer: Value = res.json().await.unwrap();
+    assert_eq!(answer["status"], "pending");
+
+    // 검토를 기다리는 초안은 목록에서 빠지고 다시 결정할 수 없다
+    assert_eq!(drafts(&app, &token).await.len(), 1);
+    let res = app
+        .post(&format!("/answers/{}/dismiss", draft["id"]))
+        .header("Authorization", &token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 404);
+
+    let items: Vec<Value> = app
+        .get("/moderation/queue")
+        .header("Authorization", &moderator)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0]["answer_id"], draft["id"]);
+    assert_eq!(items[0]["matched_words"], json!([BAD_WORD]));
+}
@@ -0,0 +1,131 @@
+use integration_tests::TestApp;
+use mock_server::{fixtures, Mock, MockResponse, MockServer};
+use serde_json::{json, Value};
+use warp_chatbot::config::EmbedderKind;
+use warp_chatbot::{backfill_embeddings, connect_store};
+
+#[tokio::test]
+async fn backfill_embeds_new_and_changed_text() {
+    let Some(app) = TestApp::spawn().await else {
+        return;
+    };
+    let store = connect_store(&app.config).await.unwrap();
+    let token = app.sign_up("embeddings@email.com").await;
+    let question = app.create_question(&token, "Composing warp filters").await;
+    let id = question["id"].as_i64().unwrap();
+    assert_eq!(
+        app.add_answer(&token, id, "Chain them with and")
+            .await
+            .status(),
+        200
+    );
+
+    assert_eq!(
+        backfill_embeddings(&app.config, store.clone())
+            .await
+            .unwrap(),
+        2
+    );
+    assert_eq!(
+        backfill_embeddings(&app.config, store.clone())
+            .await
+            .unwrap(),
+        0
+    );
+
+    // 글을 고치면 그 글만 다시 만든다
+    let res = app.get(&format!("/questions/{}", id)).send().await.unwrap();
+    let etag = res.headers()["etag"].to_str().unwrap().to_string();
+    let res = app
+        .put(&format!("/questions/{}", id))
+        .header("Authorization", &token)
+        .header("If-Match", &etag)
+        .json(&json!({
+            "id": id,
+            "title": "Composing warp filters",
+            "content": "Updated content",
+            "tags": ["test"],
+            "version": 1,
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 200);
+    assert_eq!(
+        backfill_embeddings(&app.config, store.clone())
+            .await
+            .unwrap(),
+        1
+    );
+
+    // 삭제한 글은 만들지 않는다
+    let res = app
+        .delete(&format!("/questions/{}", id))
+        .header("Authorization", &token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 200);
+    // 지운 질문과 그 답변의 벡터도 지운다
+    let mut db = app.db().await;
+    let left: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM embeddings")
+        .fetch_one(&mut db)
+        .await
+        .unwrap();
+    assert_eq!(left, 0);
+    let question = app.create_question(&token, "Another question").await;
+    assert!(question["id"].as_i64().unwrap() > id);
+    assert_eq!(backfill_embeddings(&app.config, store).await.unwrap(), 1);
+
+    let res = app
+        .post("/chat")
+        .header("Authorization", &token)
+        .json(&json!({ "question": "another question" }))
+        .send()
+        .await
+        .unwrap();
+    let body: Value = res.json().await.unwrap();
+    assert_eq!(body["citations"][0]["title"], "Another question");
+}
+
+#[tokio::test]
+async fn rejected_text_does_not_block_the_rest() {
+    let embeddings = MockServer::builder()
+        .mock(fixtures::llm::embeddings(8))
+        .mock(
+            Mock::post("/v1/embeddings")
+                .body_contains("Rejected")
+                .respond_with(MockResponse::json(
+                    400,
+                    &json!({"error": {"message": "input is not allowed"}}),
+                )),
+        )
+        .start();
+    let url = embeddings.url();
+    let Some(app) = TestApp::spawn_with(|config| {
+        config.embedder = EmbedderKind::Http;
+        config.embedding_url = url;
+        config.embedding_dimensions = 8;
+    })
+    .await
+    else {
+        return;
+    };
+    let store = connect_store(&app.config).await.unwrap();
+    let token = app.sign_up("rejected@email.com").await;
+    app.create_question(&token, "Rejected question").await;
+    app.create_question(&token, "Accepted question").await;
+
+    // 거절당한 글은 건너뛰고 뒤에 있는 글의 벡터는 만든다
+    assert_eq!(
+        backfill_embeddings(&app.config, store.clone())
+            .await
+            .unwrap(),
+        1
+    );
+
+    // 바뀌지 않은 글은 다시 보내지 않는다
+    let sent = embeddings.requests().len();
+    assert_eq!(backfill_embeddings(&app.config, store).await.unwrap(), 0);
+    assert_eq!(embeddings.requests().len(), sent);
+}
@@ -0,0 +1,79 @@
+use integration_tests::{TestApp, LLM_ANSWER};
+use serde_json::{json, Value};
+use warp_chatbot::config::LlmProvider;
+
+async fn ask(app: &TestApp, token: &str, question: &str) -> Value {
+    let res = app
+        .post("/chat")
+        .header("Authorization", token)
+        .json(&json!({ "question": question }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 200);
+    res.json().await.unwrap()
+}
+
+#[tokio::test]
+async fn reports_token_usage() {
+    let Some(app) = TestApp::spawn().await else {
+        return;
+    };
+    let token = app.sign_up("usage@email.com").await;
+    app.create_question(&token, "Composing warp filters").await;
+
+    let res = ask(&app, &token, "composing warp filters").await;
+    assert!(res["usage"]["input_tokens"].as_u64().unwrap() > 0);
+    assert_eq!(res["usage"]["output_tokens"], 5);
+
+    // 모델을 부르지 않은 답에는 없다
+    let res = ask(&app, &token, "kubernetes").await;
+    assert_eq!(res["usage"], Value::Null);
+}
+
+#[tokio::test]
+async fn answers_with_anthropic_messages_api() {
+    let Some(app) = TestApp::spawn_with(|config| {
+        config.llm_provider = LlmProvider::Anthropic;
+        config.llm_api_key = "anthropic-key".to_string();
+    })
+    .await
+    else {
+        return;
+    };
+    let token = app.sign_up("anthropic@email.com").await;
+    let id = app.create_question(&token, "Composing warp filters").await["id"]
+        .as_i64()
+        .unwrap();
+
+    let res = ask(&app, &token, "composing warp filters").await;
+    assert_eq!(res["answer"], format!("{} [Q{}]", LLM_ANSWER, id));
+    assert_eq!(res["citations"][0]["cited"], true);
+
+    let request = &app.llm.requests()[0];
+    assert_eq!(request.path, "/v1/messages");
+    assert_eq!(request.header("x-api-key"), Some("anthropic-key"));
+    assert!(request.json().unwrap()["system"].is_string());
+}
+
+#[tokio::test]
+async fn falls_back_when_the_model_is_down() {
+    let Some(app) = TestApp::spawn_with(|config| {
+        // 아무도 듣지 않는 포트
+        config.llm_url = "http://127.0.0.1:9".to_string();
+        config.llm_fallback_provider = Some(LlmProvider::Fake);
+        config.llm_fake_replies = vec!["From the fallback model".to_string()];
+    })
+    .await
+    else {
+        return;
+    };
+    let token = app.sign_up("fallback@email.com").await;
+    app.create_question(&token, "Composing warp filters").await;
+
+    let res = ask(&app, &token, "composing warp filters").await;
+    assert_eq!(res["answer"], "From the fallback model");
+    assert_eq!(res["usage"]["output_tokens"], 4);
+    assert_eq!(res["citations"][0]["cited"], false);
+    assert!(app.llm.requests().is_empty());
+}
@@ -0,0 +1,323 @@
+use integration_tests::{TestApp, BAD_WORD};
+use serde_json::{json, Value};
+use warp_chatbot::config::ProfanityAction;
+
+async fn queue(app: &TestApp, token: &str) -> Vec<Value> {
+    let res = app
+        .get("/moderation/queue")
+        .header("Authorization", token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 200);
+    res.json().await.unwrap()
+}
+
+async fn decide(app: &TestApp, token: &str, id: &Value, decision: Value) -> reqwest::Response {
+    app.post(&format!("/moderation/queue/{}", id))
+        .header("Authorization", token)
+        .json(&decision)
+        .send()
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn moderators_approve_and_reject_queued_posts() {
+    let Some(app) = TestApp::spawn_with(|config| {
+        config.profanity_action = ProfanityAction::Queue;
+    })
+    .await
+    else {
+        return;
+    };
+    let author = app.sign_up("author@email.com").await;
+    let moderator = app.sign_up("moderator@email.com").await;
+    app.make_moderator("moderator@email.com").await;
+
+    let mut ids = Vec::new();
+    for title in ["Approved", "Rejected"] {
+        let res = app
+            .add_question(
+                &author,
+                &json!({ "title": format!("{} {}", title, BAD_WORD), "content": "content" }),
+            )
+            .await;
+        assert_eq!(res.status(), 200);
+        let question: Value = res.json().await.unwrap();
+        assert_eq!(question["status"], "pending");
+        ids.push(question["id"].clone());
+    }
+
+    // 모더레이터만 대기열을 보고 결정할 수 있다
+    let res = app
+        .get("/moderation/queue")
+        .header("Authorization", &author)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 403);
+
+    let items = queue(&app, &moderator).await;
+    assert_eq!(items.len(), 2);
+    assert_eq!(items[0]["question_id"], ids[0]);
+    assert_eq!(items[0]["matched_words"], json!([BAD_WORD]));
+    let (approved, rejected) = (items[0]["id"].clone(), items[1]["id"].clone());
+
+    let res = decide(&app, &author, &approved, json!({ "decision": "approve" })).await;
+    assert_eq!(res.status(), 403);
+
+    let res = decide(
+        &app,
+        &moderator,
+        &approved,
+        json!({ "decision": "approve" }),
+    )
+    .await;
+    assert_eq!(res.status(), 200);
+    let res = app
+        .get(&format!("/questions/{}", ids[0]))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 200);
+    let question: Value = res.json().await.unwrap();
+    assert_eq!(question["status"], "published");
+
+    let res = decide(
+        &app,
+        &moderator,
+        &rejected,
+        json!({ "decision": "reject", "note": "offensive" }),
+    )
+    .await;
+    assert_eq!(res.status(), 200);
+    // 거절한 질문은 걸린 원문을 그대로 두므로 작성자와 모더레이터에게만 보인다
+    let res = app
+        .get(&format!("/questions/{}", ids[1]))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 404);
+    for token in [&author, &moderator] {
+        let question: Value = app
+            .get(&format!("/questions/{}", ids[1]))
+            .header("Authorization", token)
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(question["status"], "rejected");
+    }
+
+    // 목록에는 공개한 질문만 나온다
+    let questions: Vec<Value> = app
+        .get("/questions")
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(questions.len(), 1);
+    assert_eq!(questions[0]["id"], ids[0]);
+
+    // 결정한 항목은 대기열에서 빠지고 다시 결정할 수 없다
+    assert!(queue(&app, &moderator).await.is_empty());
+    let res = decide(
+        &app,
+        &moderator,
+        &approved,
+        json!({ "decision": "approve" }),
+    )
+    .await;
+    assert_eq!(res.status(), 404);
+    let res = decide(
+        &app,
+        &moderator,
+        &json!(9999),
+        json!({ "decision": "approve" }),
+    )
+    .await;
+    assert_eq!(res.status(), 404);
+    let body: Value = res.json().await.unwrap();
+    assert_eq!(body["code"], "not_found");
+}
+
+#[tokio::test]
+async fn queued_questions_and_their_revisions_are_hidden() {
+    let Some(app) = TestApp::spawn_with(|config| {
+        config.profanity_action = ProfanityAction::Queue;
+    })
+    .await
+    else {
+        return;
+    };
+    let author = app.sign_up("author@email.com").await;
+    let other = app.sign_up("other@email.com").await;
+    let moderator = app.sign_up("moderator@email.com").await;
+    app.make_moderator("moderator@email.com").await;
+
+    let res = app
+        .add_question(
+            &author,
+            &json!({ "title": format!("Queued {}", BAD_WORD), "content": "content" }),
+        )
+        .await;
+    let question: Value = res.json().await.unwrap();
+    assert_eq!(question["status"], "pending");
+    let id = question["id"].clone();
+
+    for path in [
+        format!("/questions/{}", id),
+        format!("/questions/{}/revisions", id),
+    ] {
+        let res = app.get(&path).send().await.unwrap();
+        assert_eq!(res.status(), 404, "{}", path);
+        let res = app
+            .get(&path)
+            .header("Authorization", &other)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), 404, "{}", path);
+        for token in [&author, &moderator] {
+            let res = app
+                .get(&path)
+                .header("Authorization", token)
+                .send()
+                .await
+                .unwrap();
+            assert_eq!(res.status(), 200, "{}", path);
+        }
+    }
+}
+
+/// 로그인하지 않고 본 질문
+async fn published(app: &TestApp, id: &Value) -> Value {
+    let res = app.get(&format!("/questions/{}", id)).send().await.unwrap();
+    assert_eq!(res.status(), 200);
+    res.json().await.unwrap()
+}
+
+async fn edit(app: &TestApp, token: &str, id: &Value, version: i64, title: &str) -> Value {
+    let res = app
+        .put(&format!("/questions/{}", id))
+        .header("Authorization", token)
+        .header("If-Match", format!("\"{}\"", version))
+        .json(&json!({ "id": id, "title": title, "content": "content", "version": version }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 200);
+    res.json().await.unwrap()
+}
+
+#[tokio::test]
+async fn queued_edits_leave_the_published_question_alone() {
+    let Some(app) = TestApp::spawn_with(|config| {
+        config.profanity_action = ProfanityAction::Queue;
+    })
+    .await
+    else {
+        return;
+    };
+    let author = app.sign_up("author@email.com").await;
+    let moderator = app.sign_up("moderator@email.com").await;
+    app.make_moderator("moderator@email.com").await;
+    let id = app.create_question(&author, "Published title").await["id"].clone();
+
+    // 걸린 수정은 검토를 기다리고 공개한 질문은 그대로다
+    let held = edit(&app, &author, &id, 1, &format!("Edited {}", BAD_WORD)).await;
+    assert_eq!(held["status"], "pending");
+    assert_eq!(held["version"], 1);
+    let question = published(&app, &id).await;
+    assert_eq!(question["title"], "Published title");
+    assert_eq!(question["status"], "published");
+    let questions: Vec<Value> = app
+        .get("/questions")
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(questions.len(), 1);
+
+    // 검토를 기다리는 수정은 작성자와 모더레이터만 이력에서 본다
+    let revisions: Vec<Value> = app
+        .get(&format!("/questions/{}/revisions", id))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(revisions.len(), 1);
+    let revisions: Vec<Value> = app
+        .get(&format!("/questions/{}/revisions", id))
+        .header("Authorization", &author)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(revisions.len(), 2);
+    assert_eq!(revisions[1]["status"], "pending");
+
+    let items = queue(&app, &moderator).await;
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0]["title"], format!("Edited {}", BAD_WORD));
+    assert_eq!(items[0]["revision_id"], revisions[1]["id"]);
+
+    // 거절하면 공개한 질문이 그대로 남는다
+    let res = decide(
+        &app,
+        &moderator,
+        &items[0]["id"],
+        json!({ "decision": "reject" }),
+    )
+    .await;
+    assert_eq!(res.status(), 200);
+    let question = published(&app, &id).await;
+    assert_eq!(question["title"], "Published title");
+    assert_eq!(question["version"], 1);
+
+    // 공개하면 수정을 반영한다
+    edit(&app, &author, &id, 1, &format!("Second {}", BAD_WORD)).await;
+    let items = queue(&app, &moderator).await;
+    assert_eq!(items.len(), 1);
+    let res = decide(
+        &app,
+        &moderator,
+        &items[0]["id"],
+        json!({ "decision": "edit", "title": "Second edit" }),
+    )
+    .await;
+    assert_eq!(res.status(), 200);
+    let question = published(&app, &id).await;
+    assert_eq!(question["title"], "Second edit");
+    assert_eq!(question["status"], "published");
+    assert_eq!(question["version"], 2);
+
+    let revisions: Vec<Value> = app
+        .get(&format!("/questions/{}/revisions", id))
+        .header("Authorization", &author)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let statuses: Vec<&str> = revisions
+        .iter()
+        .map(|revision| revision["status"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        statuses,
+        ["published", "rejected", "published", "published"]
+    );
+}
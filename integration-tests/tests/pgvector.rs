@@ -0,0 +1,88 @@
+use integration_tests::TestApp;
+use warp_chatbot::config::VectorStoreKind;
+use warp_chatbot::handle_errors::Error;
+use warp_chatbot::types::chat::RetrievalFilter;
+use warp_chatbot::{backfill_embeddings, connect_store, oneshot};
+
+/// DB 에 pgvector 확장이 있는지 (없으면 pgvector 를 쓰는 테스트는 건너뛴다)
+async fn pgvector_available(app: &TestApp) -> bool {
+    let mut db = app.db().await;
+    sqlx::query_scalar(
+        "SELECT EXISTS (SELECT 1 FROM pg_available_extensions WHERE name = 'vector')",
+    )
+    .fetch_one(&mut db)
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn pgvector_is_required_when_configured() {
+    let Some(mut app) = TestApp::spawn().await else {
+        return;
+    };
+    if pgvector_available(&app).await {
+        return;
+    }
+    let store = connect_store(&app.config).await.unwrap();
+    assert!(!store.has_pgvector().await.unwrap());
+
+    // 메모리 저장소로 대신하지 않고 시작하지 않는다
+    app.config.vector_store = VectorStoreKind::Pgvector;
+    match oneshot(&app.config, store).await {
+        Err(Error::InvalidConfig(message)) => assert!(message.contains("pgvector")),
+        Err(other) => panic!("expected invalid config, got {:?}", other),
+        Ok(_) => panic!("expected invalid config, got a running server"),
+    }
+}
+
+#[tokio::test]
+async fn pgvector_finds_the_nearest_passages() {
+    let Some(mut app) = TestApp::spawn().await else {
+        return;
+    };
+    if !pgvector_available(&app).await {
+        return;
+    }
+    let store = connect_store(&app.config).await.unwrap();
+    assert!(store.has_pgvector().await.unwrap());
+
+    let token = app.sign_up("pgvector@email.com").await;
+    let question = app.create_question(&token, "Composing warp filters").await;
+    let id = question["id"].as_i64().unwrap();
+    app.create_question(&token, "Tuning the connection pool")
+        .await;
+
+    app.config.vector_store = VectorStoreKind::Pgvector;
+    assert_eq!(
+        backfill_embeddings(&app.config, store.clone())
+            .await
+            .unwrap(),
+        2
+    );
+
+    // 저장한 벡터로 찾으면 그 글이 가장 가깝다
+    let mut db = app.db().await;
+    let (model, embedding): (String, Vec<f32>) = sqlx::query_as(
+        "SELECT model, embedding FROM embeddings WHERE source = 'question' AND entity_id = $1",
+    )
+    .bind(id as i32)
+    .fetch_one(&mut db)
+    .await
+    .unwrap();
+    let found = store
+        .search_embeddings(&model, &embedding, 5, &RetrievalFilter::default())
+        .await
+        .unwrap();
+    assert_eq!(found.len(), 2);
+    assert_eq!(found[0].title, "Composing warp filters");
+    assert!((found[0].score - 1.0).abs() < 1e-4);
+    assert!(found[0].score > found[1].score);
+
+    match store
+        .search_embeddings(&model, &[], 5, &RetrievalFilter::default())
+        .await
+    {
+        Err(Error::InvalidConfig(_)) => {}
+        other => panic!("expected invalid config, got {:?}", other),
+    }
+}
@@ -0,0 +1,473 @@
+use integration_tests::{TestApp, BAD_WORD};
+use serde_json::{json, Value};
+
+#[tokio::test]
+async fn question_crud() {
+    let Some(app) = TestApp::spawn().await else {
+        return;
+    };
+    let token = app.sign_up("crud@email.com").await;
+
+    let question = app.create_question(&token, "First Question").await;
+    let id = question["id"].as_i64().unwrap();
+    assert_eq!(question["title"], "First Question");
+    assert_eq!(question["version"], 1);
+
+    let res = app.get(&format!("/questions/{}", id)).send().await.unwrap();
+    assert_eq!(res.status(), 200);
+    let etag = res.headers()["etag"].to_str().unwrap().to_string();
+    assert_eq!(res.json::<Value>().await.unwrap()["id"], id);
+
+    // PUT 은 If-Match 로 버전을 확인한다
+    let res = app
+        .put(&format!("/questions/{}", id))
+        .header("Authorization", &token)
+        .header("If-Match", &etag)
+        .json(&json!({
+            "id": id,
+            "title": "Updated Question",
+            "content": "Updated content",
+            "tags": ["updated"],
+            "version": 1,
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 200);
+    let updated: Value = res.json().await.unwrap();
+    assert_eq!(updated["title"], "Updated Question");
+    assert_eq!(updated["version"], 2);
+
+    // 지난 ETag 로는 수정할 수 없다
+    let res = app
+        .patch(&format!("/questions/{}", id))
+        .header("Authorization", &token)
+        .header("If-Match", &etag)
+        .header("Content-Type", "application/merge-patch+json")
+        .body(r#"{"title": "Stale"}"#)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 412);
+
+    let res = app
+        .patch(&format!("/questions/{}", id))
+        .header("Authorization", &token)
+        .header("Content-Type", "application/merge-patch+json")
+        .body(r#"{"tags": ["patched"]}"#)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 200);
+    let patched: Value = res.json().await.unwrap();
+    assert_eq!(patched["title"], "Updated Question");
+    assert_eq!(patched["tags"], json!(["patched"]));
+
+    let res = app
+        .get(&format!("/questions/{}/revisions", id))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 200);
+    assert!(!res.json::<Vec<Value>>().await.unwrap().is_empty());
+
+    let res = app
+        .delete(&format!("/questions/{}", id))
+        .header("Authorization", &token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 200);
+    let res = app.get(&format!("/questions/{}", id)).send().await.unwrap();
+    assert_eq!(res.status(), 404);
+
+    let res = app
+        .post(&format!("/questions/{}/restore", id))
+        .header("Authorization", &token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 200);
+    let res = app.get(&format!("/questions/{}", id)).send().await.unwrap();
+    assert_eq!(res.status(), 200);
+}
+
+#[tokio::test]
+async fn soft_deleted_questions_keep_their_revisions() {
+    let Some(app) = TestApp::spawn().await else {
+        return;
+    };
+    let owner = app.sign_up("owner@email.com").await;
+    let other = app.sign_up("other@email.com").await;
+    let moderator = app.sign_up("moderator@email.com").await;
+    app.make_moderator("moderator@email.com").await;
+    let id = app.create_question(&owner, "Deleted").await["id"]
+        .as_i64()
+        .unwrap();
+    let revisions = format!("/questions/{}/revisions", id);
+    let restore = format!("/questions/{}/restore", id);
+
+    let res = app
+        .delete(&format!("/questions/{}", id))
+        .header("Authorization", &owner)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 200);
+    // 이미 삭제한 질문은 다시 삭제할 수 없다
+    let res = app
+        .delete(&format!("/questions/{}", id))
+        .header("Authorization", &owner)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 404);
+
+    // 삭제한 질문의 이력은 작성자와 모더레이터에게만 보인다
+    assert_eq!(app.get(&revisions).send().await.unwrap().status(), 404);
+    let res = app
+        .get(&revisions)
+        .header("Authorization", &other)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 404);
+    for token in [&owner, &moderator] {
+        let res = app
+            .get(&revisions)
+            .header("Authorization", token)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), 200);
+        let body: Vec<Value> = res.json().await.unwrap();
+        assert_eq!(body.len(), 1);
+        assert_eq!(body[0]["title"], "Deleted");
+    }
+
+    let res = app
+        .post(&restore)
+        .header("Authorization", &other)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 403);
+    let res = app
+        .post(&restore)
+        .header("Authorization", &moderator)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 200);
+    // 삭제되지 않은 질문은 복구할 것이 없다
+    let res = app
+        .post(&restore)
+        .header("Authorization", &owner)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 404);
+    assert_eq!(app.get(&revisions).send().await.unwrap().status(), 200);
+}
+
+#[tokio::test]
+async fn stale_updates_skip_the_profanity_check() {
+    let Some(app) = TestApp::spawn().await else {
+        return;
+    };
+    let token = app.sign_up("stale@email.com").await;
+    let id = app.create_question(&token, "Stale").await["id"]
+        .as_i64()
+        .unwrap();
+    let checked = app.apilayer.received("POST /bad_words");
+
+    let res = app
+        .put(&format!("/questions/{}", id))
+        .header("Authorization", &token)
+        .header("If-Match", "\"7\"")
+        .json(&json!({
+            "id": id,
+            "title": "Stale update",
+            "content": "Stale content",
+            "version": 7,
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 412);
+    assert_eq!(app.apilayer.received("POST /bad_words"), checked);
+}
+
+#[tokio::test]
+async fn merge_patch_tells_null_from_missing() {
+    let Some(app) = TestApp::spawn().await else {
+        return;
+    };
+    let token = app.sign_up("patch@email.com").await;
+    let id = app.create_question(&token, "Patched").await["id"]
+        .as_i64()
+        .unwrap();
+    let patch = |body: &'static str| {
+        app.patch(&format!("/questions/{}", id))
+            .header("Authorization", &token)
+            .header("Content-Type", "application/merge-patch+json")
+            .body(body)
+            .send()
+    };
+
+    // 빠진 필드는 그대로 두고 null 인 tags 는 지운다
+    let res = patch(r#"{"tags": null}"#).await.unwrap();
+    assert_eq!(res.status(), 200);
+    let patched: Value = res.json().await.unwrap();
+    assert_eq!(patched["title"], "Patched");
+    assert_eq!(patched["content"], "How can I test?");
+    assert_eq!(patched["tags"], Value::Null);
+
+    // title 은 지울 수 없다
+    for body in [r#"{"title": null}"#, "not json"] {
+        let res = patch(body).await.unwrap();
+        assert_eq!(res.status(), 400);
+        let body: Value = res.json().await.unwrap();
+        assert_eq!(body["code"], "invalid_body");
+    }
+}
+
+#[tokio::test]
+async fn profanity_is_censored_through_mock_apilayer() {
+    let Some(app) = TestApp::spawn().await else {
+        return;
+    };
+    let token = app.sign_up("censor@email.com").await;
+
+    let res = app
+        .add_question(
+            &token,
+            &json!({
+                "title": format!("A {} title", BAD_WORD),
+                "content": "clean content",
+            }),
+        )
+        .await;
+    assert_eq!(res.status(), 200);
+    let question: Value = res.json().await.unwrap();
+    assert_eq!(question["title"], "A ****** title");
+    assert_eq!(question["content"], "clean content");
+
+    // 제목과 내용을 한 번에 검사한다
+    assert_eq!(app.apilayer.received("POST /bad_words"), 1);
+    let request = &app.apilayer.requests()[0];
+    assert_eq!(request.header("apikey"), Some(integration_tests::API_KEY));
+}
+
+#[tokio::test]
+async fn persistent_profanity_cache_drops_expired_results() {
+    let Some(app) = TestApp::spawn_with(|config| {
+        config.profanity_cache_persistent = true;
+        config.profanity_cache_ttl_secs = 60;
+    })
+    .await
+    else {
+        return;
+    };
+    let token = app.sign_up("cache@email.com").await;
+    let mut db = app.db().await;
+    sqlx::query(
+        "INSERT INTO profanity_cache (content_hash, censored_content, created_on)
+         VALUES ('expired', 'old', NOW() - INTERVAL '2 minutes')",
+    )
+    .execute(&mut db)
+    .await
+    .unwrap();
+
+    app.create_question(&token, "Cached").await;
+
+    let hashes: Vec<String> = sqlx::query_scalar("SELECT content_hash FROM profanity_cache")
+        .fetch_all(&mut db)
+        .await
+        .unwrap();
+    assert_eq!(hashes.len(), 2);
+    assert!(!hashes.contains(&"expired".to_string()));
+}
+
+#[tokio::test]
+async fn only_the_owner_can_change_a_question() {
+    let Some(app) = TestApp::spawn().await else {
+        return;
+    };
+    let owner = app.sign_up("owner@email.com").await;
+    let other = app.sign_up("other@email.com").await;
+    let id = app.create_question(&owner, "Owned").await["id"]
+        .as_i64()
+        .unwrap();
+
+    let res = app
+        .delete(&format!("/questions/{}", id))
+        .header("Authorization", &other)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 403);
+    let body: Value = res.json().await.unwrap();
+    assert_eq!(body["code"], "forbidden");
+}
+
+#[tokio::test]
+async fn pagination() {
+    let Some(app) = TestApp::spawn().await else {
+        return;
+    };
+    let token = app.sign_up("pages@email.com").await;
+    for i in 0..5 {
+        app.create_question(&token, &format!("Question {}", i))
+            .await;
+    }
+
+    let all: Vec<Value> = app
+        .get("/questions")
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(all.len(), 5);
+
+    let page: Vec<Value> = app
+        .get("/questions?limit=2&offset=1")
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(page.len(), 2);
+
+    let res = app.get("/questions?limit=2").send().await.unwrap();
+    assert_eq!(res.status(), 400);
+    let body: Value = res.json().await.unwrap();
+    assert_eq!(body["code"], "missing_parameters");
+
+    let res = app
+        .get("/questions?limit=two&offset=0")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 400);
+    let body: Value = res.json().await.unwrap();
+    assert_eq!(body["code"], "invalid_parameter");
+}
+
+#[tokio::test]
+async fn error_cases() {
+    let Some(app) = TestApp::spawn().await else {
+        return;
+    };
+    let token = app.sign_up("errors@email.com").await;
+
+    let res = app.get("/questions/9999").send().await.unwrap();
+    assert_eq!(res.status(), 404);
+    let body: Value = res.json().await.unwrap();
+    assert_eq!(body["code"], "not_found");
+
+    let res = app.get("/nowhere").send().await.unwrap();
+    assert_eq!(res.status(), 404);
+    let body: Value = res.json().await.unwrap();
+    assert_eq!(body["code"], "route_not_found");
+
+    let res = app.delete("/questions").send().await.unwrap();
+    assert_eq!(res.status(), 405);
+    let body: Value = res.json().await.unwrap();
+    assert_eq!(body["code"], "method_not_allowed");
+
+    let res = app
+        .add_question(&token, &json!({ "title": "", "content": "content" }))
+        .await;
+    assert_eq!(res.status(), 400);
+    let body: Value = res.json().await.unwrap();
+    assert_eq!(body["errors"][0]["field"], "title");
+
+    // 토큰 없이 질문을 만들 수 없다
+    let res = app
+        .post("/questions")
+        .json(&json!({ "title": "t", "content": "c" }))
+        .send()
+        .await
+        .unwrap();
+    assert!(res.status().is_client_error());
+}
+
+#[tokio::test]
+async fn suggests_similar_questions() {
+    let Some(app) = TestApp::spawn().await else {
+        return;
+    };
+    let token = app.sign_up("similar@email.com").await;
+    let composing = app.create_question(&token, "Composing warp filters").await["id"].clone();
+    let testing = app.create_question(&token, "Testing warp handlers").await["id"].clone();
+    app.create_question(&token, "Deploying to kubernetes").await;
+
+    let res = app
+        .post("/questions/similar")
+        .header("Authorization", &token)
+        .json(&json!({ "title": "How do I compose warp filters?", "limit": 2 }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 200);
+    let similar: Vec<Value> = res.json().await.unwrap();
+    assert_eq!(similar.len(), 2);
+    assert_eq!(similar[0]["id"], composing);
+    assert_eq!(similar[0]["title"], "Composing warp filters");
+    assert_eq!(similar[1]["id"], testing);
+    assert!(similar[0]["score"].as_f64().unwrap() > similar[1]["score"].as_f64().unwrap());
+
+    let res = app
+        .post("/questions/similar")
+        .header("Authorization", &token)
+        .json(&json!({ "title": " ", "limit": 100 }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 400);
+    let body: Value = res.json().await.unwrap();
+    assert_eq!(body["errors"][0]["field"], "title");
+    assert_eq!(body["errors"][1]["field"], "limit");
+}
+
+#[tokio::test]
+async fn warns_about_possible_duplicates() {
+    let Some(app) = TestApp::spawn_with(|config| config.reject_duplicates = true).await else {
+        return;
+    };
+    let token = app.sign_up("duplicate@email.com").await;
+    let id = app.create_question(&token, "Composing warp filters").await["id"].clone();
+    let duplicate = json!({ "title": "composing warp filters", "content": "How can I test?" });
+
+    let res = app.add_question(&token, &duplicate).await;
+    assert_eq!(res.status(), 409);
+    let body: Value = res.json().await.unwrap();
+    assert_eq!(body["code"], "possible_duplicate");
+    assert_eq!(body["candidates"][0]["id"], id);
+    assert_eq!(body["candidates"][0]["title"], "Composing warp filters");
+
+    // 다른 질문은 막지 않는다
+    app.create_question(&token, "Testing warp handlers").await;
+
+    let res = app
+        .post("/questions?force=true")
+        .header("Authorization", &token)
+        .json(&duplicate)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 200);
+    let questions: Vec<Value> = app
+        .get("/questions")
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(questions.len(), 3);
+}
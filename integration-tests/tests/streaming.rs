@@ -0,0 +1,211 @@
+use futures_util::{SinkExt, StreamExt};
+use integration_tests::{TestApp, LLM_ANSWER, LLM_DELAY, SLOW_QUESTION};
+use serde_json::{json, Value};
+use tokio::net::TcpStream;
+use tokio_tungstenite::tungstenite::{client::IntoClientRequest, Message};
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+
+type Socket = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+async fn connect(app: &TestApp, token: &str) -> Socket {
+    let mut request = format!("{}/chat/ws", app.url.replacen("http", "ws", 1))
+        .into_client_request()
+        .unwrap();
+    request
+        .headers_mut()
+        .insert("Authorization", token.parse().unwrap());
+    tokio_tungstenite::connect_async(request).await.unwrap().0
+}
+
+/// done 이나 error 가 올 때까지 받은 이벤트
+async fn receive(socket: &mut Socket) -> Vec<Value> {
+    let mut events = Vec::new();
+    while let Some(message) = socket.next().await {
+        let event: Value = match message.unwrap() {
+            Message::Text(text) => serde_json::from_str(&text).unwrap(),
+            _ => continue,
+        };
+        let last = event["type"] != "token";
+        events.push(event);
+        if last {
+            break;
+        }
+    }
+    events
+}
+
+fn answer(events: &[Value]) -> String {
+    events
+        .iter()
+        .filter(|event| event["type"] == "token")
+        .map(|event| event["text"].as_str().unwrap())
+        .collect()
+}
+
+#[tokio::test]
+async fn streams_chat_over_sse() {
+    let Some(app) = TestApp::spawn().await else {
+        return;
+    };
+    let token = app.sign_up("sse@email.com").await;
+    let id = app.create_question(&token, "Composing warp filters").await["id"]
+        .as_i64()
+        .unwrap();
+
+    let res = app
+        .post("/chat")
+        .header("Authorization", &token)
+        .header("Accept", "text/event-stream")
+        .json(&json!({ "question": "composing warp filters" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.headers()["content-type"], "text/event-stream");
+    let body = res.text().await.unwrap();
+
+    let events: Vec<(String, Value)> = body
+        .split("\n\n")
+        .filter_map(|event| {
+            let name = event.lines().find_map(|line| line.strip_prefix("event:"))?;
+            let data = event.lines().find_map(|line| line.strip_prefix("data:"))?;
+            Some((name.to_string(), serde_json::from_str(data).unwrap()))
+        })
+        .collect();
+    let (name, done) = events.last().unwrap();
+    assert_eq!(name, "done");
+    let tokens: Vec<Value> = events[..events.len() - 1]
+        .iter()
+        .map(|(name, data)| {
+            assert_eq!(name, "token");
+            data.clone()
+        })
+        .collect();
+    assert!(tokens.len() > 1);
+    let expected = format!("{} [Q{}]", LLM_ANSWER, id);
+    assert_eq!(answer(&tokens), expected);
+    assert_eq!(done["answer"], expected);
+    assert_eq!(done["citations"][0]["cited"], true);
+    assert_eq!(app.llm.requests()[0].json().unwrap()["stream"], true);
+
+    let conversation: Value = app
+        .get(&format!("/conversations/{}", done["conversation_id"]))
+        .header("Authorization", &token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(conversation["messages"][1]["content"], expected);
+
+    // 스트리밍을 시작하기 전의 에러는 그대로 problem+json 이다
+    let res = app
+        .post("/chat")
+        .header("Authorization", &token)
+        .header("Accept", "text/event-stream")
+        .json(&json!({ "question": " " }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 400);
+}
+
+#[tokio::test]
+async fn chats_over_websocket() {
+    let Some(app) = TestApp::spawn().await else {
+        return;
+    };
+    let token = app.sign_up("ws@email.com").await;
+    let id = app.create_question(&token, "Composing warp filters").await["id"]
+        .as_i64()
+        .unwrap();
+    let mut socket = connect(&app, &token).await;
+
+    socket
+        .send(Message::text(
+            json!({ "question": "composing warp filters" }).to_string(),
+        ))
+        .await
+        .unwrap();
+    let events = receive(&mut socket).await;
+    let done = events.last().unwrap();
+    assert_eq!(done["type"], "done");
+    assert_eq!(answer(&events), format!("{} [Q{}]", LLM_ANSWER, id));
+    let conversation_id = done["conversation_id"].clone();
+
+    // conversation_id 를 빼면 같은 연결의 대화를 이어 간다
+    socket
+        .send(Message::text(
+            json!({ "question": "and then?" }).to_string(),
+        ))
+        .await
+        .unwrap();
+    let events = receive(&mut socket).await;
+    assert_eq!(events.last().unwrap()["conversation_id"], conversation_id);
+
+    socket.send(Message::text("not json")).await.unwrap();
+    let events = receive(&mut socket).await;
+    assert_eq!(events[0]["type"], "error");
+    assert_eq!(events[0]["status"], 400);
+    assert_eq!(events[0]["code"], "invalid_body");
+
+    socket
+        .send(Message::text(
+            json!({ "question": "warp", "conversation_id": 9999 }).to_string(),
+        ))
+        .await
+        .unwrap();
+    let events = receive(&mut socket).await;
+    assert_eq!(events[0]["code"], "not_found");
+    socket.close(None).await.unwrap();
+
+    let conversations: Vec<Value> = app
+        .get("/conversations")
+        .header("Authorization", &token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(conversations.len(), 1);
+
+    let request = format!("{}/chat/ws", app.url.replacen("http", "ws", 1));
+    assert!(tokio_tungstenite::connect_async(request).await.is_err());
+}
+
+#[tokio::test]
+async fn disconnecting_cancels_the_answer() {
+    let Some(app) = TestApp::spawn().await else {
+        return;
+    };
+    let token = app.sign_up("cancel@email.com").await;
+    app.create_question(&token, SLOW_QUESTION).await;
+    let mut socket = connect(&app, &token).await;
+
+    socket
+        .send(Message::text(
+            json!({ "question": SLOW_QUESTION }).to_string(),
+        ))
+        .await
+        .unwrap();
+    // 모델을 부를 때까지 기다렸다가 끊는다
+    while app.llm.requests().is_empty() {
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+    drop(socket);
+    tokio::time::sleep(LLM_DELAY + std::time::Duration::from_millis(500)).await;
+
+    // 답을 저장하지 않았으니 새 대화도 만들지 않는다
+    let conversations: Vec<Value> = app
+        .get("/conversations")
+        .header("Authorization", &token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert!(conversations.is_empty());
+}
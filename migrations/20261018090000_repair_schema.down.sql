@@ -0,0 +1,24 @@
+-- Add down migration script here
+ALTER TABLE answers
+ALTER COLUMN created_on TYPE TIMESTAMP USING created_on AT TIME ZONE 'UTC';
+ALTER TABLE questions
+ALTER COLUMN created_on TYPE TIMESTAMP USING created_on AT TIME ZONE 'UTC';
+
+ALTER TABLE answers DROP CONSTRAINT answers_corresponding_question_fkey;
+ALTER TABLE answers
+ADD CONSTRAINT answers_corresponding_question_fkey
+FOREIGN KEY (corresponding_question) REFERENCES questions (id);
+
+ALTER TABLE answers DROP CONSTRAINT answers_account_id_fkey;
+CREATE SEQUENCE IF NOT EXISTS answers_account_id_seq OWNED BY answers.account_id;
+ALTER TABLE answers
+ALTER COLUMN account_id SET DEFAULT nextval('answers_account_id_seq');
+
+ALTER TABLE questions DROP CONSTRAINT questions_account_id_fkey;
+CREATE SEQUENCE IF NOT EXISTS questions_account_id_seq OWNED BY questions.account_id;
+ALTER TABLE questions
+ALTER COLUMN account_id SET DEFAULT nextval('questions_account_id_seq');
+
+ALTER TABLE accounts DROP CONSTRAINT accounts_email_key;
+ALTER TABLE accounts DROP CONSTRAINT accounts_pkey;
+ALTER TABLE accounts ADD PRIMARY KEY (email);
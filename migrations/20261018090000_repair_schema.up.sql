@@ -0,0 +1,35 @@
+-- Add up migration script here
+ALTER TABLE accounts DROP CONSTRAINT accounts_pkey;
+ALTER TABLE accounts ADD PRIMARY KEY (id);
+ALTER TABLE accounts ADD CONSTRAINT accounts_email_key UNIQUE (email);
+
+-- 예전에는 account_id 가 시퀀스 기본값이라 없는 계정을 가리키는 행이 있을 수 있다
+-- 외래 키를 걸기 전에 주인 없는 답변과 질문(그 답변 포함)을 지운다
+DELETE FROM answers
+WHERE account_id NOT IN (SELECT id FROM accounts)
+   OR corresponding_question IN (
+       SELECT id FROM questions WHERE account_id NOT IN (SELECT id FROM accounts)
+   );
+DELETE FROM questions WHERE account_id NOT IN (SELECT id FROM accounts);
+
+ALTER TABLE questions ALTER COLUMN account_id DROP DEFAULT;
+DROP SEQUENCE IF EXISTS questions_account_id_seq;
+ALTER TABLE questions
+ADD CONSTRAINT questions_account_id_fkey
+FOREIGN KEY (account_id) REFERENCES accounts (id);
+
+ALTER TABLE answers ALTER COLUMN account_id DROP DEFAULT;
+DROP SEQUENCE IF EXISTS answers_account_id_seq;
+ALTER TABLE answers
+ADD CONSTRAINT answers_account_id_fkey
+FOREIGN KEY (account_id) REFERENCES accounts (id);
+
+ALTER TABLE answers DROP CONSTRAINT answers_corresponding_question_fkey;
+ALTER TABLE answers
+ADD CONSTRAINT answers_corresponding_question_fkey
+FOREIGN KEY (corresponding_question) REFERENCES questions (id) ON DELETE CASCADE;
+
+ALTER TABLE questions
+ALTER COLUMN created_on TYPE TIMESTAMPTZ USING created_on AT TIME ZONE 'UTC';
+ALTER TABLE answers
+ALTER COLUMN created_on TYPE TIMESTAMPTZ USING created_on AT TIME ZONE 'UTC';
@@ -0,0 +1,10 @@
+-- Add down migration script here
+DROP TABLE IF EXISTS question_revisions;
+
+ALTER TABLE accounts
+DROP COLUMN is_moderator;
+
+ALTER TABLE answers
+DROP COLUMN deleted_at;
+ALTER TABLE questions
+DROP COLUMN deleted_at;
@@ -0,0 +1,24 @@
+-- Add up migration script here
+ALTER TABLE questions
+ADD COLUMN deleted_at TIMESTAMPTZ;
+ALTER TABLE answers
+ADD COLUMN deleted_at TIMESTAMPTZ;
+
+ALTER TABLE accounts
+ADD COLUMN is_moderator BOOLEAN NOT NULL DEFAULT FALSE;
+
+CREATE TABLE IF NOT EXISTS question_revisions (
+    id serial PRIMARY KEY,
+    question_id integer NOT NULL REFERENCES questions ON DELETE CASCADE,
+    title VARCHAR (255) NOT NULL,
+    content TEXT NOT NULL,
+    tags TEXT [],
+    account_id integer NOT NULL REFERENCES accounts (id),
+    created_on TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS question_revisions_question_id_idx
+ON question_revisions (question_id);
+
+INSERT INTO question_revisions (question_id, title, content, tags, account_id, created_on)
+SELECT id, title, content, tags, account_id, created_on FROM questions;
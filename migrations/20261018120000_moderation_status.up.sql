@@ -0,0 +1,8 @@
+-- Add up migration script here
+ALTER TABLE questions
+ADD COLUMN status VARCHAR(16) NOT NULL DEFAULT 'published',
+ADD CONSTRAINT questions_status_check CHECK (status IN ('published', 'pending'));
+
+ALTER TABLE answers
+ADD COLUMN status VARCHAR(16) NOT NULL DEFAULT 'published',
+ADD CONSTRAINT answers_status_check CHECK (status IN ('published', 'pending'));
@@ -0,0 +1,14 @@
+-- Add down migration script here
+DROP TABLE IF EXISTS notifications;
+DROP TABLE IF EXISTS moderation_queue;
+
+UPDATE answers SET status = 'pending' WHERE status = 'rejected';
+UPDATE questions SET status = 'pending' WHERE status = 'rejected';
+
+ALTER TABLE answers
+DROP CONSTRAINT answers_status_check,
+ADD CONSTRAINT answers_status_check CHECK (status IN ('published', 'pending'));
+
+ALTER TABLE questions
+DROP CONSTRAINT questions_status_check,
+ADD CONSTRAINT questions_status_check CHECK (status IN ('published', 'pending'));
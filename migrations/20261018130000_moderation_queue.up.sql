@@ -0,0 +1,47 @@
+-- Add up migration script here
+ALTER TABLE questions
+DROP CONSTRAINT questions_status_check,
+ADD CONSTRAINT questions_status_check CHECK (status IN ('published', 'pending', 'rejected'));
+
+ALTER TABLE answers
+DROP CONSTRAINT answers_status_check,
+ADD CONSTRAINT answers_status_check CHECK (status IN ('published', 'pending', 'rejected'));
+
+CREATE TABLE IF NOT EXISTS moderation_queue (
+    id serial PRIMARY KEY,
+    question_id integer REFERENCES questions(id) ON DELETE CASCADE,
+    answer_id integer REFERENCES answers(id) ON DELETE CASCADE,
+    reason VARCHAR(16) NOT NULL CHECK (reason IN ('profanity', 'unchecked')),
+    matched_words TEXT[] NOT NULL DEFAULT '{}',
+    status VARCHAR(16) NOT NULL DEFAULT 'pending'
+        CHECK (status IN ('pending', 'approved', 'edited', 'rejected')),
+    moderator_id integer REFERENCES accounts(id),
+    note TEXT,
+    created_on TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    decided_on TIMESTAMPTZ,
+    CHECK ((question_id IS NULL) <> (answer_id IS NULL))
+);
+
+-- 글마다 대기 중인 항목은 하나만 둔다
+CREATE UNIQUE INDEX moderation_queue_pending_question_idx
+    ON moderation_queue (question_id) WHERE status = 'pending';
+CREATE UNIQUE INDEX moderation_queue_pending_answer_idx
+    ON moderation_queue (answer_id) WHERE status = 'pending';
+
+CREATE TABLE IF NOT EXISTS notifications (
+    id serial PRIMARY KEY,
+    account_id integer NOT NULL REFERENCES accounts(id) ON DELETE CASCADE,
+    question_id integer REFERENCES questions(id) ON DELETE CASCADE,
+    answer_id integer REFERENCES answers(id) ON DELETE CASCADE,
+    message TEXT NOT NULL,
+    created_on TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX notifications_account_id_idx ON notifications (account_id);
+
+-- 비속어 검사를 하지 못해 pending 으로 남아 있던 글을 대기열에 넣는다
+INSERT INTO moderation_queue (question_id, reason)
+SELECT id, 'unchecked' FROM questions WHERE status = 'pending';
+
+INSERT INTO moderation_queue (answer_id, reason)
+SELECT id, 'unchecked' FROM answers WHERE status = 'pending';
@@ -0,0 +1,9 @@
+-- Add up migration script here
+CREATE TABLE IF NOT EXISTS profanity_cache (
+    content_hash TEXT PRIMARY KEY,
+    censored_content TEXT NOT NULL,
+    bad_words TEXT[] NOT NULL DEFAULT '{}',
+    created_on TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS profanity_cache_created_on_idx ON profanity_cache (created_on);
@@ -0,0 +1,3 @@
+-- Add down migration script here
+DROP INDEX IF EXISTS answers_search_idx;
+DROP INDEX IF EXISTS questions_search_idx;
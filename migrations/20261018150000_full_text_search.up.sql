@@ -0,0 +1,6 @@
+-- Add up migration script here
+CREATE INDEX IF NOT EXISTS questions_search_idx
+    ON questions USING GIN (to_tsvector('simple', title || ' ' || content));
+
+CREATE INDEX IF NOT EXISTS answers_search_idx
+    ON answers USING GIN (to_tsvector('simple', content));
@@ -0,0 +1,7 @@
+-- Add down migration script here
+DROP TRIGGER IF EXISTS answers_touch_updated_at ON answers;
+DROP TRIGGER IF EXISTS questions_touch_updated_at ON questions;
+DROP FUNCTION IF EXISTS touch_updated_at();
+
+ALTER TABLE answers DROP COLUMN IF EXISTS updated_at;
+ALTER TABLE questions DROP COLUMN IF EXISTS updated_at;
@@ -0,0 +1,30 @@
+-- Add up migration script here
+ALTER TABLE questions
+ADD COLUMN updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW();
+ALTER TABLE answers
+ADD COLUMN updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW();
+
+UPDATE questions SET updated_at = created_on;
+UPDATE answers SET updated_at = created_on;
+
+-- 삭제/복구나 상태 변경이 아니라 글이 바뀔 때만 갱신한다
+CREATE OR REPLACE FUNCTION touch_updated_at() RETURNS trigger AS $$
+BEGIN
+    NEW.updated_at = NOW();
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER questions_touch_updated_at
+BEFORE UPDATE ON questions
+FOR EACH ROW
+WHEN (OLD.title IS DISTINCT FROM NEW.title
+      OR OLD.content IS DISTINCT FROM NEW.content
+      OR OLD.tags IS DISTINCT FROM NEW.tags)
+EXECUTE FUNCTION touch_updated_at();
+
+CREATE TRIGGER answers_touch_updated_at
+BEFORE UPDATE ON answers
+FOR EACH ROW
+WHEN (OLD.content IS DISTINCT FROM NEW.content)
+EXECUTE FUNCTION touch_updated_at();
@@ -0,0 +1,10 @@
+-- Add up migration script here
+CREATE TABLE IF NOT EXISTS embeddings (
+    source VARCHAR(16) NOT NULL CHECK (source IN ('question', 'answer')),
+    entity_id integer NOT NULL,
+    model TEXT NOT NULL,
+    embedding REAL[] NOT NULL,
+    source_updated_at TIMESTAMPTZ NOT NULL,
+    created_on TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (source, entity_id, model)
+);
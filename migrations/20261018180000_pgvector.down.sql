@@ -0,0 +1,4 @@
+-- Add down migration script here
+-- 확장은 다른 곳에서 쓸 수 있으므로 지우지 않는다
+DROP INDEX IF EXISTS embeddings_vector_384_idx;
+ALTER TABLE embeddings DROP COLUMN IF EXISTS vector;
@@ -0,0 +1,27 @@
+-- Add up migration script here
+-- pgvector 가 있을 때만 벡터 열과 인덱스를 만든다 (없으면 VECTOR_STORE=pgvector 로 시작할 수 없다)
+-- 확장은 public 에 만든다 (테스트마다 따로 쓰는 스키마에 만들면 다른 스키마에서 쓸 수 없다)
+-- vector 는 embedding 에서 만드는 생성 열이라 쓰는 쪽은 확장이 있는지 몰라도 된다
+-- HNSW 인덱스는 기본 차원(384)에만 만든다. 다른 차원은 인덱스 없이 찾는다
+DO $$
+BEGIN
+    IF NOT EXISTS (SELECT 1 FROM pg_available_extensions WHERE name = 'vector') THEN
+        RAISE NOTICE 'pgvector is not available, skipping vector column';
+        RETURN;
+    END IF;
+
+    BEGIN
+        CREATE EXTENSION IF NOT EXISTS vector SCHEMA public;
+    EXCEPTION WHEN insufficient_privilege THEN
+        RAISE NOTICE 'not allowed to create extension vector, skipping vector column';
+        RETURN;
+    END;
+
+    EXECUTE 'ALTER TABLE embeddings
+             ADD COLUMN IF NOT EXISTS vector vector
+             GENERATED ALWAYS AS (embedding::vector) STORED';
+    EXECUTE 'CREATE INDEX IF NOT EXISTS embeddings_vector_384_idx ON embeddings
+             USING hnsw ((vector::vector(384)) vector_cosine_ops)
+             WHERE vector_dims(vector) = 384';
+END
+$$;
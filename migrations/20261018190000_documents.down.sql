@@ -0,0 +1,8 @@
+-- Add down migration script here
+DELETE FROM embeddings WHERE source NOT IN ('question', 'answer');
+ALTER TABLE embeddings DROP CONSTRAINT IF EXISTS embeddings_source_check;
+ALTER TABLE embeddings ADD CONSTRAINT embeddings_source_check
+    CHECK (source IN ('question', 'answer'));
+
+DROP TABLE IF EXISTS document_chunks;
+DROP TABLE IF EXISTS documents;
@@ -0,0 +1,29 @@
+-- Add up migration script here
+CREATE TABLE IF NOT EXISTS documents (
+    id serial PRIMARY KEY,
+    title VARCHAR(255) NOT NULL,
+    source_type VARCHAR(16) NOT NULL CHECK (source_type IN ('markdown', 'text', 'pdf')),
+    filename TEXT NOT NULL,
+    -- CLI 로 가져온 문서는 올린 계정이 없다
+    account_id integer REFERENCES accounts,
+    created_on TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    deleted_at TIMESTAMPTZ
+);
+
+CREATE TABLE IF NOT EXISTS document_chunks (
+    id serial PRIMARY KEY,
+    document_id integer NOT NULL REFERENCES documents ON DELETE CASCADE,
+    position integer NOT NULL,
+    heading TEXT,
+    content TEXT NOT NULL,
+    UNIQUE (document_id, position)
+);
+
+CREATE INDEX IF NOT EXISTS document_chunks_search_idx
+    ON document_chunks USING GIN (to_tsvector('simple', COALESCE(heading, '') || ' ' || content));
+
+-- 문서 조각의 벡터는 source 에 문서 형식, entity_id 에 조각 id 를 넣는다
+ALTER TABLE embeddings DROP CONSTRAINT IF EXISTS embeddings_source_check;
+ALTER TABLE embeddings ADD CONSTRAINT embeddings_source_check
+    CHECK (source IN ('question', 'answer', 'markdown', 'text', 'pdf'));
@@ -0,0 +1,3 @@
+-- Add down migration script here
+DROP TABLE IF EXISTS messages;
+DROP TABLE IF EXISTS conversations;
@@ -0,0 +1,26 @@
+-- Add up migration script here
+CREATE TABLE IF NOT EXISTS conversations (
+    id serial PRIMARY KEY,
+    account_id integer NOT NULL REFERENCES accounts ON DELETE CASCADE,
+    title VARCHAR(255) NOT NULL,
+    summary TEXT,
+    -- summary 에 들어간 마지막 메시지 id
+    summarized_until integer,
+    created_on TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS conversations_account_idx
+    ON conversations (account_id, updated_at DESC);
+
+CREATE TABLE IF NOT EXISTS messages (
+    id serial PRIMARY KEY,
+    conversation_id integer NOT NULL REFERENCES conversations ON DELETE CASCADE,
+    role VARCHAR(16) NOT NULL CHECK (role IN ('user', 'assistant')),
+    content TEXT NOT NULL,
+    citations JSONB NOT NULL DEFAULT '[]',
+    created_on TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS messages_conversation_idx
+    ON messages (conversation_id, id);
@@ -0,0 +1,13 @@
+-- Add down migration script here
+ALTER TABLE questions
+DROP COLUMN ai_drafted_at;
+
+DELETE FROM answers WHERE is_ai;
+
+ALTER TABLE answers
+DROP COLUMN is_ai;
+
+DELETE FROM accounts WHERE is_ai;
+
+ALTER TABLE accounts
+DROP COLUMN is_ai;
@@ -0,0 +1,16 @@
+-- Add up migration script here
+-- 모델이 쓴 답변의 작성자 (로그인할 수 없다)
+ALTER TABLE accounts
+ADD COLUMN is_ai BOOLEAN NOT NULL DEFAULT FALSE;
+
+INSERT INTO accounts (email, password, is_ai)
+VALUES ('assistant@ai.invalid', '', TRUE)
+ON CONFLICT (email) DO NOTHING;
+
+-- 모델이 쓴 답변은 질문 작성자가 받아들이기 전까지 pending, 거절하면 rejected 다
+ALTER TABLE answers
+ADD COLUMN is_ai BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- 답변 초안을 만들어 본 질문 (근거가 없어 만들지 못했어도 다시 시도하지 않는다)
+ALTER TABLE questions
+ADD COLUMN ai_drafted_at TIMESTAMPTZ;
@@ -0,0 +1,11 @@
+-- Add up migration script here
+-- 임베딩 API 가 거절한 글 (같은 내용으로는 다시 보내지 않고 글이 바뀌면 다시 시도한다)
+CREATE TABLE IF NOT EXISTS embedding_failures (
+    source VARCHAR(16) NOT NULL,
+    entity_id integer NOT NULL,
+    model TEXT NOT NULL,
+    source_updated_at TIMESTAMPTZ NOT NULL,
+    error TEXT NOT NULL,
+    created_on TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (source, entity_id, model)
+);
@@ -0,0 +1,12 @@
+-- Add down migration script here
+DROP TRIGGER IF EXISTS document_chunks_forget_embeddings ON document_chunks;
+DROP TRIGGER IF EXISTS documents_forget_embeddings ON documents;
+DROP TRIGGER IF EXISTS answers_forget_embeddings_on_delete ON answers;
+DROP TRIGGER IF EXISTS answers_forget_embeddings ON answers;
+DROP TRIGGER IF EXISTS questions_forget_embeddings_on_delete ON questions;
+DROP TRIGGER IF EXISTS questions_forget_embeddings ON questions;
+DROP FUNCTION IF EXISTS forget_chunk_embeddings();
+DROP FUNCTION IF EXISTS forget_document_embeddings();
+DROP FUNCTION IF EXISTS forget_answer_embeddings();
+DROP FUNCTION IF EXISTS forget_question_embeddings();
+DROP FUNCTION IF EXISTS forget_embeddings(TEXT[], integer[]);
@@ -0,0 +1,89 @@
+-- Add up migration script here
+-- 글이 지워지거나 공개되지 않게 되면 그 글의 벡터와 임베딩 실패 기록을 지운다
+-- (다시 공개되면 임베딩 워커가 새로 만든다)
+CREATE OR REPLACE FUNCTION forget_embeddings(sources TEXT[], ids integer[]) RETURNS void AS $$
+BEGIN
+    DELETE FROM embeddings WHERE source = ANY(sources) AND entity_id = ANY(ids);
+    DELETE FROM embedding_failures WHERE source = ANY(sources) AND entity_id = ANY(ids);
+END;
+$$ LANGUAGE plpgsql;
+
+-- 질문이 내려가면 답변도 검색에서 빠지므로 답변의 벡터도 지운다
+CREATE OR REPLACE FUNCTION forget_question_embeddings() RETURNS trigger AS $$
+BEGIN
+    PERFORM forget_embeddings(ARRAY['question'], ARRAY[OLD.id]);
+    PERFORM forget_embeddings(
+        ARRAY['answer'],
+        ARRAY(SELECT id FROM answers WHERE corresponding_question = OLD.id)
+    );
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION forget_answer_embeddings() RETURNS trigger AS $$
+BEGIN
+    PERFORM forget_embeddings(ARRAY['answer'], ARRAY[OLD.id]);
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION forget_document_embeddings() RETURNS trigger AS $$
+BEGIN
+    PERFORM forget_embeddings(
+        ARRAY['markdown', 'text', 'pdf'],
+        ARRAY(SELECT id FROM document_chunks WHERE document_id = OLD.id)
+    );
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION forget_chunk_embeddings() RETURNS trigger AS $$
+BEGIN
+    PERFORM forget_embeddings(ARRAY['markdown', 'text', 'pdf'], ARRAY[OLD.id]);
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER questions_forget_embeddings
+AFTER UPDATE OF deleted_at, status ON questions
+FOR EACH ROW
+WHEN (NEW.deleted_at IS NOT NULL OR NEW.status <> 'published')
+EXECUTE FUNCTION forget_question_embeddings();
+
+CREATE TRIGGER questions_forget_embeddings_on_delete
+AFTER DELETE ON questions
+FOR EACH ROW EXECUTE FUNCTION forget_question_embeddings();
+
+CREATE TRIGGER answers_forget_embeddings
+AFTER UPDATE OF deleted_at, status ON answers
+FOR EACH ROW
+WHEN (NEW.deleted_at IS NOT NULL OR NEW.status <> 'published')
+EXECUTE FUNCTION forget_answer_embeddings();
+
+CREATE TRIGGER answers_forget_embeddings_on_delete
+AFTER DELETE ON answers
+FOR EACH ROW EXECUTE FUNCTION forget_answer_embeddings();
+
+CREATE TRIGGER documents_forget_embeddings
+AFTER UPDATE OF deleted_at ON documents
+FOR EACH ROW
+WHEN (NEW.deleted_at IS NOT NULL)
+EXECUTE FUNCTION forget_document_embeddings();
+
+CREATE TRIGGER document_chunks_forget_embeddings
+AFTER DELETE ON document_chunks
+FOR EACH ROW EXECUTE FUNCTION forget_chunk_embeddings();
+
+-- 이미 지워졌거나 숨겨진 글의 벡터를 정리한다
+DELETE FROM embeddings e
+WHERE (e.source = 'question' AND NOT EXISTS (
+        SELECT 1 FROM questions q
+        WHERE q.id = e.entity_id AND q.deleted_at IS NULL AND q.status = 'published'))
+   OR (e.source = 'answer' AND NOT EXISTS (
+        SELECT 1 FROM answers a JOIN questions q ON q.id = a.corresponding_question
+        WHERE a.id = e.entity_id
+          AND a.deleted_at IS NULL AND a.status = 'published'
+          AND q.deleted_at IS NULL AND q.status = 'published'))
+   OR (e.source IN ('markdown', 'text', 'pdf') AND NOT EXISTS (
+        SELECT 1 FROM document_chunks c JOIN documents d ON d.id = c.document_id
+        WHERE c.id = e.entity_id AND d.deleted_at IS NULL));
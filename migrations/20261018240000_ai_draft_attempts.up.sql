@@ -0,0 +1,4 @@
+-- Add up migration script here
+-- 답변 초안을 만들다 실패한 횟수 (정해진 횟수만큼 실패하면 ai_drafted_at 을 남기고 포기한다)
+ALTER TABLE questions
+ADD COLUMN ai_draft_attempts integer NOT NULL DEFAULT 0;
@@ -0,0 +1,10 @@
+-- Add down migration script here
+DELETE FROM moderation_queue WHERE revision_id IS NOT NULL;
+
+ALTER TABLE moderation_queue
+DROP COLUMN revision_id;
+
+DELETE FROM question_revisions WHERE status <> 'published';
+
+ALTER TABLE question_revisions
+DROP COLUMN status;
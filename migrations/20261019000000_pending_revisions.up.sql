@@ -0,0 +1,9 @@
+-- Add up migration script here
+-- 검토 대기열에 간 수정은 공개한 질문을 바꾸지 않고 pending 이력으로만 남긴다
+-- 모더레이터가 공개하면 질문에 반영하고(published), 거절하면 rejected 로 남긴다
+ALTER TABLE question_revisions
+ADD COLUMN status VARCHAR(16) NOT NULL DEFAULT 'published'
+    CHECK (status IN ('published', 'pending', 'rejected'));
+
+ALTER TABLE moderation_queue
+ADD COLUMN revision_id integer REFERENCES question_revisions(id) ON DELETE CASCADE;
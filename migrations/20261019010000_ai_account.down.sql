@@ -0,0 +1,5 @@
+-- Add down migration script here
+-- 이미 초안을 쓴 계정은 답변과 함께 ai_answers 의 down 에서 지운다
+DELETE FROM accounts
+WHERE email = 'assistant' AND is_ai
+AND NOT EXISTS (SELECT 1 FROM answers WHERE answers.account_id = accounts.id);
@@ -0,0 +1,7 @@
+-- Add up migration script here
+-- 처음 마이그레이션에서 같은 이메일로 가입한 사람이 있으면 모델 계정이 만들어지지 않았다
+-- 'assistant' 는 이메일 형식이 아니라 가입으로는 쓸 수 없는 주소다
+INSERT INTO accounts (email, password, is_ai)
+SELECT 'assistant', '', TRUE
+WHERE NOT EXISTS (SELECT 1 FROM accounts WHERE is_ai)
+ON CONFLICT (email) DO UPDATE SET password = '', is_ai = TRUE;
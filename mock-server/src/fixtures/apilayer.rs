@@ -0,0 +1,63 @@
+use serde_json::{json, Value};
+
+use crate::{Mock, MockResponse, RecordedRequest};
+
+/// APILayer `POST /bad_words`
+/// `apikey` 헤더가 다르면 401, 맞으면 목록의 단어를 대소문자 구별 없이 찾아 `*` 로 가린다
+pub fn bad_words(api_key: &str, words: &[&str]) -> Mock {
+    let api_key = api_key.to_string();
+    let words: Vec<String> = words.iter().map(|word| word.to_ascii_lowercase()).collect();
+
+    Mock::post("/bad_words").respond_with_fn(move |request| {
+        if request.header("apikey") != Some(api_key.as_str()) {
+            return MockResponse::json(
+                401,
+                &json!({ "message": "Invalid authentication credentials" }),
+            );
+        }
+        MockResponse::json(200, &censor(request, &words))
+    })
+}
+
+fn censor(request: &RecordedRequest, words: &[String]) -> Value {
+    let content = request.body_text();
+    // ASCII 만 소문자로 바꾸므로 바이트 위치가 원문과 같다
+    let lowercase = content.to_ascii_lowercase();
+    let mut masked = Vec::new();
+    let mut bad_words_list = Vec::new();
+
+    for word in words.iter().filter(|word| !word.is_empty()) {
+        for (start, _) in lowercase.match_indices(word.as_str()) {
+            let end = start + word.len();
+            let original = &content[start..end];
+            let replaced_len = original.chars().count();
+            masked.push(start..end);
+            bad_words_list.push(json!({
+                "original": original,
+                "word": word,
+                "deviations": 0,
+                "info": 2,
+                "start": start,
+                "end": end,
+                "replacedLen": replaced_len,
+            }));
+        }
+    }
+
+    let censored: String = content
+        .char_indices()
+        .map(
+            |(i, c)| match masked.iter().any(|range| range.contains(&i)) {
+                true => '*',
+                false => c,
+            },
+        )
+        .collect();
+
+    json!({
+        "content": content,
+        "bad_words_total": bad_words_list.len(),
+        "bad_words_list": bad_words_list,
+        "censored_content": censored,
+    })
+}
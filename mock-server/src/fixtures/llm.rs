@@ -0,0 +1,220 @@
+use serde_json::{json, Value};
+
+use crate::{Mock, MockResponse};
+
+/// OpenAI 호환 `POST /v1/chat/completions`, 언제나 같은 답을 한다
+pub fn chat(reply: &str) -> Mock {
+    let reply = reply.to_string();
+    chat_fn(move |_| reply.clone())
+}
+
+/// 요청의 messages 를 보고 답을 만든다
+/// `"stream": true` 이면 단어마다 chunk 를 SSE 로 보내고 `[DONE]` 으로 끝낸다
+/// stream_options.include_usage 를 보내면 `[DONE]` 앞에 usage 만 있는 chunk 를 보낸다
+/// 토큰 수는 단어 수로 센다
+pub fn chat_fn<F>(reply: F) -> Mock
+where
+    F: Fn(&[Value]) -> String + Send + Sync + 'static,
+{
+    Mock::post("/v1/chat/completions").respond_with_fn(move |request| {
+        let body = request.json().unwrap_or(Value::Null);
+        let messages = body["messages"].as_array().cloned().unwrap_or_default();
+        let model = body["model"].as_str().unwrap_or("mock-model").to_string();
+        let reply = reply(&messages);
+        let prompt_tokens: usize = messages
+            .iter()
+            .filter_map(|message| message["content"].as_str())
+            .map(|content| content.split_whitespace().count())
+            .sum();
+        let completion_tokens = reply.split_whitespace().count();
+        let usage = json!({
+            "prompt_tokens": prompt_tokens,
+            "completion_tokens": completion_tokens,
+            "total_tokens": prompt_tokens + completion_tokens,
+        });
+
+        if body["stream"].as_bool() == Some(true) {
+            let mut events: Vec<String> = reply
+                .split_inclusive(' ')
+                .map(|word| {
+                    json!({
+                        "id": "chatcmpl-mock",
+                        "object": "chat.completion.chunk",
+                        "model": model,
+                        "choices": [{"index": 0, "delta": {"content": word}, "finish_reason": null}],
+                    })
+                    .to_string()
+                })
+                .collect();
+            events.push(
+                json!({
+                    "id": "chatcmpl-mock",
+                    "object": "chat.completion.chunk",
+                    "model": model,
+                    "choices": [{"index": 0, "delta": {}, "finish_reason": "stop"}],
+                })
+                .to_string(),
+            );
+            if body["stream_options"]["include_usage"].as_bool() == Some(true) {
+                events.push(
+                    json!({
+                        "id": "chatcmpl-mock",
+                        "object": "chat.completion.chunk",
+                        "model": model,
+                        "choices": [],
+                        "usage": usage,
+                    })
+                    .to_string(),
+                );
+            }
+            events.push("[DONE]".to_string());
+            return MockResponse::sse(events);
+        }
+
+        MockResponse::json(
+            200,
+            &json!({
+                "id": "chatcmpl-mock",
+                "object": "chat.completion",
+                "model": model,
+                "choices": [{
+                    "index": 0,
+                    "message": {"role": "assistant", "content": reply},
+                    "finish_reason": "stop",
+                }],
+                "usage": usage,
+            }),
+        )
+    })
+}
+
+/// Anthropic `POST /v1/messages`, 언제나 같은 답을 한다
+pub fn messages(reply: &str) -> Mock {
+    let reply = reply.to_string();
+    messages_fn(move |_| reply.clone())
+}
+
+/// 요청의 messages 를 보고 답을 만든다 (system 은 messages 에 없다)
+/// `"stream": true` 이면 단어마다 content_block_delta 를 SSE 로 보내고 message_stop 으로 끝낸다
+/// 입력 토큰 수는 message_start, 출력 토큰 수는 message_delta 로 보낸다 (단어 수로 센다)
+pub fn messages_fn<F>(reply: F) -> Mock
+where
+    F: Fn(&[Value]) -> String + Send + Sync + 'static,
+{
+    Mock::post("/v1/messages").respond_with_fn(move |request| {
+        let body = request.json().unwrap_or(Value::Null);
+        let messages = body["messages"].as_array().cloned().unwrap_or_default();
+        let model = body["model"].as_str().unwrap_or("mock-model").to_string();
+        let reply = reply(&messages);
+        let input_tokens: usize = std::iter::once(&body["system"])
+            .chain(messages.iter().map(|message| &message["content"]))
+            .filter_map(Value::as_str)
+            .map(|content| content.split_whitespace().count())
+            .sum();
+        let output_tokens = reply.split_whitespace().count();
+
+        if body["stream"].as_bool() == Some(true) {
+            let mut events = vec![json!({
+                "type": "message_start",
+                "message": {
+                    "id": "msg_mock",
+                    "role": "assistant",
+                    "model": model,
+                    "content": [],
+                    "usage": {"input_tokens": input_tokens, "output_tokens": 1},
+                },
+            })];
+            events.extend(reply.split_inclusive(' ').map(|word| {
+                json!({
+                    "type": "content_block_delta",
+                    "index": 0,
+                    "delta": {"type": "text_delta", "text": word},
+                })
+            }));
+            events.push(json!({
+                "type": "message_delta",
+                "delta": {"stop_reason": "end_turn"},
+                "usage": {"output_tokens": output_tokens},
+            }));
+            events.push(json!({"type": "message_stop"}));
+            return MockResponse::sse(events.iter().map(Value::to_string));
+        }
+
+        MockResponse::json(
+            200,
+            &json!({
+                "id": "msg_mock",
+                "type": "message",
+                "role": "assistant",
+                "model": model,
+                "content": [{"type": "text", "text": reply}],
+                "stop_reason": "end_turn",
+                "usage": {
+                    "input_tokens": input_tokens,
+                    "output_tokens": output_tokens,
+                },
+            }),
+        )
+    })
+}
+
+/// OpenAI 호환 `POST /v1/embeddings`
+/// 같은 글에는 언제나 같은 단위 벡터를 돌려준다
+pub fn embeddings(dimensions: usize) -> Mock {
+    Mock::post("/v1/embeddings").respond_with_fn(move |request| {
+        let body = request.json().unwrap_or(Value::Null);
+        let inputs: Vec<String> = match &body["input"] {
+            Value::String(input) => vec![input.clone()],
+            Value::Array(inputs) => inputs
+                .iter()
+                .map(|input| input.as_str().unwrap_or_default().to_string())
+                .collect(),
+            _ => {
+                return MockResponse::json(
+                    400,
+                    &json!({"error": {"message": "input is required", "type": "invalid_request_error"}}),
+                )
+            }
+        };
+
+        let data: Vec<Value> = inputs
+            .iter()
+            .enumerate()
+            .map(|(index, input)| {
+                json!({
+                    "object": "embedding",
+                    "index": index,
+                    "embedding": embed(input, dimensions),
+                })
+            })
+            .collect();
+        MockResponse::json(
+            200,
+            &json!({
+                "object": "list",
+                "model": body["model"].as_str().unwrap_or("mock-embedding"),
+                "data": data,
+            }),
+        )
+    })
+}
+
+/// 차원마다 다른 씨앗으로 FNV-1a 해시를 구해 [-1, 1] 로 옮긴 뒤 길이를 1 로 맞춘다
+pub fn embed(text: &str, dimensions: usize) -> Vec<f32> {
+    let vector: Vec<f32> = (0..dimensions)
+        .map(|dimension| {
+            let mut hash: u64 = 0xcbf2_9ce4_8422_2325 ^ dimension as u64;
+            for byte in text.bytes() {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(0x0100_0000_01b3);
+            }
+            (hash % 2001) as f32 / 1000.0 - 1.0
+        })
+        .collect();
+
+    let norm = vector.iter().map(|v| v * v).sum::<f32>().sqrt();
+    if norm == 0.0 {
+        return vector;
+    }
+    vector.into_iter().map(|v| v / norm).collect()
+}
@@ -0,0 +1,7 @@
+//! 자주 쓰는 외부 서비스 흉내
+//!
+//! 돌려주는 목은 보통의 `Mock` 이라서 `expect` 를 붙이거나 같은 경로의 목을 나중에 등록해서 덮어쓸 수 있다
+
+pub mod apilayer;
+pub mod llm;
+pub mod oidc;
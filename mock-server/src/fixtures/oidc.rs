@@ -0,0 +1,141 @@
+use serde_json::{json, Value};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::{Mock, MockResponse, RecordedRequest};
+
+/// `/authorize` 가 발급하고 `/token` 이 받아 주는 코드
+pub const AUTHORIZATION_CODE: &str = "mock-authorization-code";
+pub const ACCESS_TOKEN: &str = "mock-access-token";
+pub const REFRESH_TOKEN: &str = "mock-refresh-token";
+pub const SUBJECT: &str = "mock-user";
+pub const EMAIL: &str = "mock-user@example.com";
+
+/// discovery, authorize, token, userinfo, jwks 엔드포인트를 가진 OIDC 공급자
+/// issuer 는 요청의 Host 헤더로 정하므로 어느 포트에 띄워도 맞는다
+/// id_token 은 서명하지 않은(`alg: none`) JWT 다
+pub fn provider(client_id: &str, client_secret: &str) -> Vec<Mock> {
+    let id = client_id.to_string();
+    let secret = client_secret.to_string();
+
+    vec![
+        Mock::get("/.well-known/openid-configuration").respond_with_fn(|request| {
+            let issuer = issuer(request);
+            MockResponse::json(
+                200,
+                &json!({
+                    "issuer": issuer,
+                    "authorization_endpoint": format!("{}/authorize", issuer),
+                    "token_endpoint": format!("{}/token", issuer),
+                    "userinfo_endpoint": format!("{}/userinfo", issuer),
+                    "jwks_uri": format!("{}/jwks", issuer),
+                    "response_types_supported": ["code"],
+                    "subject_types_supported": ["public"],
+                    "grant_types_supported": ["authorization_code", "refresh_token", "client_credentials"],
+                    "id_token_signing_alg_values_supported": ["none"],
+                }),
+            )
+        }),
+        // 로그인 화면 없이 바로 redirect_uri 로 돌려보낸다
+        Mock::get("/authorize").respond_with_fn(|request| {
+            let redirect_uri = match request.query.get("redirect_uri") {
+                Some(uri) => uri,
+                None => return error(400, "invalid_request"),
+            };
+            let mut location = format!("{}?code={}", redirect_uri, AUTHORIZATION_CODE);
+            if let Some(state) = request.query.get("state") {
+                location.push_str(&format!("&state={}", state));
+            }
+            MockResponse::new(302).header("location", &location)
+        }),
+        Mock::post("/token").respond_with_fn(move |request| token(request, &id, &secret)),
+        Mock::get("/userinfo").respond_with_fn(|request| {
+            if request.header("authorization") != Some(&format!("Bearer {}", ACCESS_TOKEN)) {
+                return error(401, "invalid_token");
+            }
+            MockResponse::json(200, &user())
+        }),
+        Mock::get("/jwks").respond_with(MockResponse::json(200, &json!({ "keys": [] }))),
+    ]
+}
+
+fn token(request: &RecordedRequest, client_id: &str, client_secret: &str) -> MockResponse {
+    let form = request.form();
+    let credentials = basic_auth(request).or_else(|| {
+        Some((
+            form.get("client_id")?.clone(),
+            form.get("client_secret")?.clone(),
+        ))
+    });
+    if credentials != Some((client_id.to_string(), client_secret.to_string())) {
+        return error(401, "invalid_client");
+    }
+
+    let valid = match form.get("grant_type").map(String::as_str) {
+        Some("authorization_code") => {
+            form.get("code").map(String::as_str) == Some(AUTHORIZATION_CODE)
+        }
+        Some("refresh_token") => {
+            form.get("refresh_token").map(String::as_str) == Some(REFRESH_TOKEN)
+        }
+        Some("client_credentials") => true,
+        _ => return error(400, "unsupported_grant_type"),
+    };
+    if !valid {
+        return error(400, "invalid_grant");
+    }
+
+    MockResponse::json(
+        200,
+        &json!({
+            "access_token": ACCESS_TOKEN,
+            "token_type": "Bearer",
+            "expires_in": 3600,
+            "refresh_token": REFRESH_TOKEN,
+            "id_token": id_token(&issuer(request), client_id),
+        }),
+    )
+}
+
+fn issuer(request: &RecordedRequest) -> String {
+    format!("http://{}", request.header("host").unwrap_or("127.0.0.1"))
+}
+
+fn user() -> Value {
+    json!({
+        "sub": SUBJECT,
+        "email": EMAIL,
+        "email_verified": true,
+        "name": "Mock User",
+    })
+}
+
+fn id_token(issuer: &str, client_id: &str) -> String {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let mut claims = user();
+    claims["iss"] = json!(issuer);
+    claims["aud"] = json!(client_id);
+    claims["iat"] = json!(now);
+    claims["exp"] = json!(now + 3600);
+
+    let encode = |value: Value| base64::encode_config(value.to_string(), base64::URL_SAFE_NO_PAD);
+    format!(
+        "{}.{}.",
+        encode(json!({ "alg": "none", "typ": "JWT" })),
+        encode(claims)
+    )
+}
+
+/// `Authorization: Basic` 의 (client_id, client_secret)
+fn basic_auth(request: &RecordedRequest) -> Option<(String, String)> {
+    let encoded = request.header("authorization")?.strip_prefix("Basic ")?;
+    let decoded = String::from_utf8(base64::decode(encoded).ok()?).ok()?;
+    let (id, secret) = decoded.split_once(':')?;
+    Some((id.to_string(), secret.to_string()))
+}
+
+fn error(status: u16, error: &str) -> MockResponse {
+    MockResponse::json(status, &json!({ "error": error }))
+}
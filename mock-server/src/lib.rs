@@ -1,81 +1,446 @@
+//! 외부 의존성(APILayer, OIDC, LLM)을 흉내 내는 테스트용 HTTP 서버
+//!
+//! ```ignore
+//! let server = MockServer::builder()
+//!     .mock(fixtures::apilayer::bad_words("KEY", &["shitty"]))
+//!     .mock(Mock::post("/retry").respond_with_sequence([
+//!         MockResponse::new(503),
+//!         MockResponse::new(200),
+//!     ]).expect(2))
+//!     .start();
+//! // server.url() 로 요청을 보낸 뒤
+//! server.verify();
+//! ```
+
 use bytes::Bytes;
 use serde_json::json;
 use std::collections::HashMap;
+use std::convert::Infallible;
 use std::net::SocketAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
 use tokio::sync::{oneshot, oneshot::Sender};
-use warp::{http, Filter, Reply};
+use warp::http::{HeaderMap, Method, Response};
+use warp::hyper;
+use warp::path::FullPath;
+use warp::Filter;
 
-#[derive(Clone, Debug)]
-pub struct MockServer {
-    socket: SocketAddr,
+pub mod fixtures;
+mod mock;
+
+pub use mock::{Mock, MockResponse, RecordedRequest};
+
+use mock::{parse_pairs, Body};
+
+struct State {
+    /// 나중에 등록한 목이 먼저 맞춰진다 (픽스처의 기본 응답을 덮어쓸 수 있도록)
+    mocks: Vec<(Mock, AtomicUsize)>,
+    requests: Mutex<Vec<RecordedRequest>>,
 }
 
-pub struct OneshotHandler {
-    pub sender: Sender<i32>,
+#[derive(Default)]
+pub struct MockServerBuilder {
+    mocks: Vec<Mock>,
+    addr: Option<SocketAddr>,
+}
+
+impl MockServerBuilder {
+    pub fn mock(mut self, mock: Mock) -> Self {
+        self.mocks.push(mock);
+        self
+    }
+
+    pub fn mocks(mut self, mocks: impl IntoIterator<Item = Mock>) -> Self {
+        self.mocks.extend(mocks);
+        self
+    }
+
+    /// 정해진 주소에 띄운다 (기본은 127.0.0.1 의 빈 포트)
+    pub fn bind(mut self, addr: SocketAddr) -> Self {
+        self.addr = Some(addr);
+        self
+    }
+
+    /// 백그라운드 태스크로 띄운다 (tokio 런타임 안에서 불러야 한다)
+    pub fn start(self) -> MockServer {
+        let state = Arc::new(State {
+            mocks: self
+                .mocks
+                .into_iter()
+                .map(|mock| (mock, AtomicUsize::new(0)))
+                .collect(),
+            requests: Mutex::new(Vec::new()),
+        });
+
+        let handler_state = state.clone();
+        let routes = warp::method()
+            .and(warp::path::full())
+            .and(warp::query::raw().or(warp::any().map(String::new)).unify())
+            .and(warp::header::headers_cloned())
+            .and(warp::body::bytes())
+            .and_then(move |method, path, query, headers, body| {
+                handle(handler_state.clone(), method, path, query, headers, body)
+            });
+
+        let (tx, rx) = oneshot::channel::<()>();
+        let addr = self.addr.unwrap_or_else(|| ([127, 0, 0, 1], 0).into());
+        let (addr, server) = warp::serve(routes).bind_with_graceful_shutdown(addr, async {
+            rx.await.ok();
+        });
+        tokio::task::spawn(server);
+
+        MockServer {
+            addr,
+            state,
+            shutdown: Some(tx),
+        }
+    }
+}
+
+/// 떠 있는 목 서버, drop 하면 내려간다
+pub struct MockServer {
+    addr: SocketAddr,
+    state: Arc<State>,
+    shutdown: Option<Sender<()>>,
 }
 
 impl MockServer {
-    pub fn new(bind_addr: SocketAddr) -> MockServer {
-        MockServer { socket: bind_addr }
+    pub fn builder() -> MockServerBuilder {
+        MockServerBuilder::default()
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// `http://127.0.0.1:PORT`
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// 지금까지 받은 요청 (맞는 목이 없던 요청도 포함)
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.state
+            .requests
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// 이름이 같은 목이 불린 횟수
+    pub fn received(&self, name: &str) -> usize {
+        self.state
+            .mocks
+            .iter()
+            .filter(|(mock, _)| mock.name == name)
+            .map(|(_, calls)| calls.load(Ordering::SeqCst))
+            .sum()
+    }
+
+    /// `expect` 로 정한 횟수만큼 불리지 않은 목이 있으면 패닉한다
+    pub fn verify(&self) {
+        let failures: Vec<String> = self
+            .state
+            .mocks
+            .iter()
+            .filter_map(|(mock, calls)| {
+                let calls = calls.load(Ordering::SeqCst);
+                match mock.expected {
+                    Some(expected) if expected != calls => Some(format!(
+                        "{}: expected {} calls, received {}",
+                        mock.name, expected, calls
+                    )),
+                    _ => None,
+                }
+            })
+            .collect();
+
+        if !failures.is_empty() {
+            panic!("mock expectations not met:\n{}", failures.join("\n"));
+        }
     }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        if let Some(tx) = self.shutdown.take() {
+            let _ = tx.send(());
+        }
+    }
+}
 
-    async fn check_profanity(_: (), content: Bytes) -> 
-        Result<impl warp::Reply, warp::Rejection> 
+async fn handle(
+    state: Arc<State>,
+    method: Method,
+    path: FullPath,
+    query: String,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Response<hyper::Body>, Infallible> {
+    let request = RecordedRequest {
+        method,
+        path: path.as_str().to_string(),
+        query: parse_pairs(&query),
+        headers: headers
+            .iter()
+            .map(|(name, value)| {
+                (
+                    name.as_str().to_string(),
+                    String::from_utf8_lossy(value.as_bytes()).to_string(),
+                )
+            })
+            .collect::<HashMap<_, _>>(),
+        body,
+    };
+    state
+        .requests
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .push(request.clone());
+
+    let response = match state
+        .mocks
+        .iter()
+        .rev()
+        .find(|(mock, _)| mock.matches(&request))
     {
-        let content = 
-            String::from_utf8(content.to_vec()).expect("Invalid UTF-8");
-        if content.contains("shitty") {
-            Ok(warp::reply::with_status(
-                warp::reply::json(&json!({
-                    "bad_words_list": [{
-                    "deviations": 0,
-                    "end": 16,
-                    "info": 2,
-                    "original": "shitty",
-                    "replacedLen": 6,
-                    "start": 10,
-                    "word": "shitty"
-                    }],
-                    "bad_words_total": 1,
-                    "censored_content": "this is a ****** sentence",
-                    "content": "this is a shitty sentence"
-                })),
-                http::StatusCode::OK,
-            ))
-        } else {
-            Ok(warp::reply::with_status(
-                warp::reply::json(&json!({
-                    "bad_words_list": [],
-                    "bad_words_total": 0,
-                    "censored_content": "",
-                    "content": "this is a sentence"
-                })),
-                http::StatusCode::OK,
-            ))
+        Some((mock, calls)) => mock.response(calls.fetch_add(1, Ordering::SeqCst), &request),
+        None => MockResponse::json(
+            404,
+            &json!({ "message": format!("no mock for {} {}", request.method, request.path) }),
+        ),
+    };
+
+    if !response.delay.is_zero() {
+        tokio::time::sleep(response.delay).await;
+    }
+
+    let mut builder = Response::builder().status(response.status);
+    for (name, value) in &response.headers {
+        builder = builder.header(name.as_str(), value.as_str());
+    }
+    let body = match response.body {
+        Body::Bytes(bytes) => hyper::Body::from(bytes),
+        Body::Abort => {
+            let (sender, body) = hyper::Body::channel();
+            sender.abort();
+            body
         }
+    };
+
+    Ok(builder
+        .body(body)
+        .unwrap_or_else(|_| Response::new(hyper::Body::empty())))
+}
+
+#[cfg(test)]
+mod mock_server_tests {
+    use super::{fixtures, Mock, MockResponse, MockServer};
+    use serde_json::{json, Value};
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn sequence_records_and_verifies() {
+        let server = MockServer::builder()
+            .mock(
+                Mock::post("/flaky")
+                    .respond_with_sequence([MockResponse::new(503), MockResponse::new(200)])
+                    .expect(3),
+            )
+            .start();
+        let client = reqwest::Client::new();
+
+        let mut statuses = Vec::new();
+        for _ in 0..3 {
+            let res = client
+                .post(format!("{}/flaky?a=b+c", server.url()))
+                .header("x-test", "1")
+                .body("hello")
+                .send()
+                .await
+                .unwrap();
+            statuses.push(res.status().as_u16());
+        }
+        assert_eq!(statuses, vec![503, 200, 200]);
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 3);
+        assert_eq!(requests[0].header("X-Test"), Some("1"));
+        assert_eq!(requests[0].query["a"], "b c");
+        assert_eq!(requests[0].body_text(), "hello");
+        server.verify();
     }
 
-    fn build_routes(&self) -> impl Filter<Extract = impl Reply> + Clone {
-        warp::post()
-            .and(warp::path("bad_words"))
-            .and(warp::query())
-            .map(|_: HashMap<String, String>| ())
-            .and(warp::path::end())
-            .and(warp::body::bytes())
-            .and_then(Self::check_profanity)
+    #[tokio::test]
+    async fn later_mocks_win_and_unmatched_is_404() {
+        let server = MockServer::builder()
+            .mock(Mock::get("/x").respond_with(MockResponse::text(200, "first")))
+            .mock(Mock::get("/x").respond_with(MockResponse::text(200, "second")))
+            .start();
+
+        let body = reqwest::get(format!("{}/x", server.url()))
+            .await
+            .unwrap()
+            .text()
+            .await
+            .unwrap();
+        assert_eq!(body, "second");
+
+        let res = reqwest::get(format!("{}/missing", server.url()))
+            .await
+            .unwrap();
+        assert_eq!(res.status().as_u16(), 404);
+        assert_eq!(server.requests().len(), 2);
     }
 
-    pub fn oneshot(&self) -> OneshotHandler {
-        let (tx, rx) = oneshot::channel::<i32>();
-        let routes = Self::build_routes(&self);
+    #[tokio::test]
+    #[should_panic(expected = "expected 1 calls, received 0")]
+    async fn verify_reports_missing_calls() {
+        let server = MockServer::builder()
+            .mock(Mock::get("/never").expect(1))
+            .start();
+        server.verify();
+    }
 
-        let (_, server) = warp::serve(routes)
-            .bind_with_graceful_shutdown(self.socket, async {
-                rx.await.ok();
-        });
+    #[tokio::test]
+    async fn delay_and_abort() {
+        let server = MockServer::builder()
+            .mock(
+                Mock::get("/slow")
+                    .respond_with(MockResponse::new(200).delay(Duration::from_millis(500))),
+            )
+            .mock(Mock::get("/abort").respond_with(MockResponse::abort()))
+            .start();
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_millis(100))
+            .build()
+            .unwrap();
 
-        tokio::task::spawn(server);
+        let slow = client.get(format!("{}/slow", server.url())).send().await;
+        assert!(slow.unwrap_err().is_timeout());
+
+        let aborted = match client.get(format!("{}/abort", server.url())).send().await {
+            Ok(res) => res.bytes().await.is_err(),
+            Err(_) => true,
+        };
+        assert!(aborted);
+    }
+
+    #[tokio::test]
+    async fn apilayer_fixture_checks_key_and_censors() {
+        let server = MockServer::builder()
+            .mock(fixtures::apilayer::bad_words("KEY", &["shitty"]))
+            .start();
+        let client = reqwest::Client::new();
+        let url = format!("{}/bad_words?censor_character=*", server.url());
+
+        let res = client.post(&url).body("x").send().await.unwrap();
+        assert_eq!(res.status().as_u16(), 401);
+
+        let body: Value = client
+            .post(&url)
+            .header("apikey", "KEY")
+            .body("This is a Shitty sentence")
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(body["censored_content"], "This is a ****** sentence");
+        assert_eq!(body["bad_words_list"][0]["original"], "Shitty");
+        assert_eq!(body["bad_words_total"], 1);
+    }
+
+    #[tokio::test]
+    async fn oidc_fixture_issues_tokens() {
+        let server = MockServer::builder()
+            .mocks(fixtures::oidc::provider("client", "secret"))
+            .start();
+        let client = reqwest::Client::new();
+
+        let discovery: Value =
+            reqwest::get(format!("{}/.well-known/openid-configuration", server.url()))
+                .await
+                .unwrap()
+                .json()
+                .await
+                .unwrap();
+        assert_eq!(discovery["issuer"], server.url());
+
+        let token: Value = client
+            .post(discovery["token_endpoint"].as_str().unwrap())
+            .form(&[
+                ("grant_type", "authorization_code"),
+                ("code", fixtures::oidc::AUTHORIZATION_CODE),
+                ("client_id", "client"),
+                ("client_secret", "secret"),
+            ])
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(token["access_token"], fixtures::oidc::ACCESS_TOKEN);
+
+        let userinfo: Value = client
+            .get(discovery["userinfo_endpoint"].as_str().unwrap())
+            .bearer_auth(fixtures::oidc::ACCESS_TOKEN)
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(userinfo["sub"], fixtures::oidc::SUBJECT);
+    }
+
+    #[tokio::test]
+    async fn llm_fixture_answers_and_streams() {
+        let server = MockServer::builder()
+            .mock(fixtures::llm::chat("hello there"))
+            .mock(fixtures::llm::embeddings(4))
+            .start();
+        let client = reqwest::Client::new();
+        let url = format!("{}/v1/chat/completions", server.url());
+
+        let reply: Value = client
+            .post(&url)
+            .json(&json!({"model": "m", "messages": [{"role": "user", "content": "hi"}]}))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(reply["choices"][0]["message"]["content"], "hello there");
+
+        let stream = client
+            .post(&url)
+            .json(&json!({"model": "m", "stream": true, "messages": []}))
+            .send()
+            .await
+            .unwrap()
+            .text()
+            .await
+            .unwrap();
+        assert!(stream.contains("\"hello \""));
+        assert!(stream.ends_with("data: [DONE]\n\n"));
 
-        OneshotHandler { sender: tx }
+        let embeddings: Value = client
+            .post(format!("{}/v1/embeddings", server.url()))
+            .json(&json!({"model": "m", "input": ["a", "b"]}))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(embeddings["data"].as_array().unwrap().len(), 2);
+        assert_eq!(
+            embeddings["data"][0]["embedding"].as_array().unwrap().len(),
+            4
+        );
     }
 }
@@ -0,0 +1,286 @@
+use bytes::Bytes;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use warp::http::Method;
+
+/// 서버가 받은 요청
+#[derive(Debug, Clone)]
+pub struct RecordedRequest {
+    pub method: Method,
+    pub path: String,
+    pub query: HashMap<String, String>,
+    /// 헤더 이름은 소문자다
+    pub headers: HashMap<String, String>,
+    pub body: Bytes,
+}
+
+impl RecordedRequest {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .get(&name.to_ascii_lowercase())
+            .map(String::as_str)
+    }
+
+    pub fn body_text(&self) -> String {
+        String::from_utf8_lossy(&self.body).to_string()
+    }
+
+    pub fn json(&self) -> Option<Value> {
+        serde_json::from_slice(&self.body).ok()
+    }
+
+    /// `application/x-www-form-urlencoded` 본문
+    pub fn form(&self) -> HashMap<String, String> {
+        parse_pairs(&self.body_text())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) enum Body {
+    Bytes(Bytes),
+    /// 헤더를 보낸 뒤 연결을 끊는다
+    Abort,
+}
+
+/// 미리 정한 응답
+#[derive(Debug, Clone)]
+pub struct MockResponse {
+    pub(crate) status: u16,
+    pub(crate) headers: Vec<(String, String)>,
+    pub(crate) body: Body,
+    pub(crate) delay: Duration,
+}
+
+impl MockResponse {
+    /// 본문이 없는 응답
+    pub fn new(status: u16) -> Self {
+        MockResponse {
+            status,
+            headers: Vec::new(),
+            body: Body::Bytes(Bytes::new()),
+            delay: Duration::ZERO,
+        }
+    }
+
+    pub fn json(status: u16, body: &Value) -> Self {
+        MockResponse::new(status)
+            .header("content-type", "application/json")
+            .body(body.to_string())
+    }
+
+    pub fn text(status: u16, body: &str) -> Self {
+        MockResponse::new(status)
+            .header("content-type", "text/plain")
+            .body(body.to_string())
+    }
+
+    /// `text/event-stream` 응답 (이벤트마다 `data:` 한 줄)
+    pub fn sse<I, S>(events: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let body: String = events
+            .into_iter()
+            .map(|event| format!("data: {}\n\n", event.as_ref()))
+            .collect();
+        MockResponse::new(200)
+            .header("content-type", "text/event-stream")
+            .body(body)
+    }
+
+    /// 200 헤더를 보낸 뒤 본문 도중에 연결을 끊는다
+    pub fn abort() -> Self {
+        MockResponse {
+            body: Body::Abort,
+            ..MockResponse::new(200)
+        }
+    }
+
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn body(mut self, body: impl Into<Bytes>) -> Self {
+        self.body = Body::Bytes(body.into());
+        self
+    }
+
+    /// 응답하기 전에 기다린다 (타임아웃 시험용)
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+}
+
+type ResponseFn = dyn Fn(&RecordedRequest) -> MockResponse + Send + Sync;
+
+#[derive(Clone)]
+pub(crate) enum Responder {
+    Fixed(MockResponse),
+    /// 호출마다 다음 응답, 다 쓰면 마지막 응답을 되풀이한다
+    Sequence(Vec<MockResponse>),
+    Dynamic(Arc<ResponseFn>),
+}
+
+/// 기대하는 요청과 그 응답
+#[derive(Clone)]
+pub struct Mock {
+    pub(crate) name: String,
+    method: Option<Method>,
+    path: String,
+    headers: Vec<(String, String)>,
+    query: Vec<(String, String)>,
+    body_contains: Option<String>,
+    pub(crate) responder: Responder,
+    pub(crate) expected: Option<usize>,
+}
+
+impl Mock {
+    /// 메서드와 상관없이 경로가 같으면 맞는다
+    pub fn any(path: &str) -> Self {
+        Mock {
+            name: path.to_string(),
+            method: None,
+            path: path.to_string(),
+            headers: Vec::new(),
+            query: Vec::new(),
+            body_contains: None,
+            responder: Responder::Fixed(MockResponse::new(200)),
+            expected: None,
+        }
+    }
+
+    pub fn get(path: &str) -> Self {
+        Mock::any(path).method(Method::GET)
+    }
+
+    pub fn post(path: &str) -> Self {
+        Mock::any(path).method(Method::POST)
+    }
+
+    pub fn method(mut self, method: Method) -> Self {
+        self.name = format!("{} {}", method, self.path);
+        self.method = Some(method);
+        self
+    }
+
+    /// 검증과 호출 수 조회에 쓰는 이름 (기본은 `METHOD path`)
+    pub fn named(mut self, name: &str) -> Self {
+        self.name = name.to_string();
+        self
+    }
+
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers
+            .push((name.to_ascii_lowercase(), value.to_string()));
+        self
+    }
+
+    pub fn query(mut self, name: &str, value: &str) -> Self {
+        self.query.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn body_contains(mut self, text: &str) -> Self {
+        self.body_contains = Some(text.to_string());
+        self
+    }
+
+    pub fn respond_with(mut self, response: MockResponse) -> Self {
+        self.responder = Responder::Fixed(response);
+        self
+    }
+
+    /// 재시도 시험용: 호출 순서대로 응답한다
+    pub fn respond_with_sequence(
+        mut self,
+        responses: impl IntoIterator<Item = MockResponse>,
+    ) -> Self {
+        self.responder = Responder::Sequence(responses.into_iter().collect());
+        self
+    }
+
+    /// 요청을 보고 응답을 만든다
+    pub fn respond_with_fn<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&RecordedRequest) -> MockResponse + Send + Sync + 'static,
+    {
+        self.responder = Responder::Dynamic(Arc::new(f));
+        self
+    }
+
+    /// `MockServer::verify` 에서 정확히 이만큼 불렸는지 확인한다
+    pub fn expect(mut self, times: usize) -> Self {
+        self.expected = Some(times);
+        self
+    }
+
+    pub(crate) fn matches(&self, request: &RecordedRequest) -> bool {
+        self.method.as_ref().is_none_or(|m| *m == request.method)
+            && self.path == request.path
+            && self
+                .headers
+                .iter()
+                .all(|(name, value)| request.header(name) == Some(value.as_str()))
+            && self
+                .query
+                .iter()
+                .all(|(name, value)| request.query.get(name) == Some(value))
+            && self
+                .body_contains
+                .as_ref()
+                .is_none_or(|text| request.body_text().contains(text.as_str()))
+    }
+
+    /// `call` 번째(0 부터) 호출의 응답
+    pub(crate) fn response(&self, call: usize, request: &RecordedRequest) -> MockResponse {
+        match &self.responder {
+            Responder::Fixed(response) => response.clone(),
+            Responder::Sequence(responses) => responses
+                .get(call)
+                .or_else(|| responses.last())
+                .cloned()
+                .unwrap_or_else(|| MockResponse::new(200)),
+            Responder::Dynamic(f) => f(request),
+        }
+    }
+}
+
+/// `a=1&b=2` 를 읽는다 (`+` 는 공백, `%XX` 는 그 바이트)
+pub(crate) fn parse_pairs(raw: &str) -> HashMap<String, String> {
+    raw.split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| match pair.split_once('=') {
+            Some((name, value)) => (decode(name), decode(value)),
+            None => (decode(pair), String::new()),
+        })
+        .collect()
+}
+
+fn decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => decoded.push(b' '),
+            b'%' if i + 2 < bytes.len() => {
+                let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).unwrap_or("");
+                match u8::from_str_radix(hex, 16) {
+                    Ok(byte) => {
+                        decoded.push(byte);
+                        i += 2;
+                    }
+                    Err(_) => decoded.push(b'%'),
+                }
+            }
+            byte => decoded.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).to_string()
+}
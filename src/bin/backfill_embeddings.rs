@@ -0,0 +1,15 @@
+use ::warp_chatbot::{backfill_embeddings, config, setup_store};
+
+/// 벡터가 없거나 글이 바뀐 질문, 답변, 문서 조각의 임베딩을 모두 만든다
+#[tokio::main]
+async fn main() -> Result<(), handle_errors::Error> {
+    dotenv::dotenv().ok();
+
+    let config = config::Config::new()?;
+    let store = setup_store(&config).await?;
+
+    let embedded = backfill_embeddings(&config, store).await?;
+    println!("Embedded {} passages", embedded);
+
+    Ok(())
+}
@@ -0,0 +1,28 @@
+use ::warp_chatbot::{config, import_documents, setup_store};
+use clap::Parser;
+use std::path::PathBuf;
+
+/// Markdown, 텍스트, PDF 파일을 문서로 가져온다 (임베딩은 서버의 워커가 만든다)
+#[derive(Parser, Debug)]
+struct Args {
+    /// 가져올 파일 (.md, .txt, .pdf)
+    #[clap(required = true)]
+    paths: Vec<PathBuf>,
+    #[clap(flatten)]
+    config: config::Config,
+}
+
+#[tokio::main]
+async fn main() -> Result<(), handle_errors::Error> {
+    dotenv::dotenv().ok();
+
+    let args = Args::parse();
+    let config = config::Config::with_env(args.config)?;
+    let store = setup_store(&config).await?;
+
+    for (filename, chunks) in import_documents(&config, store, &args.paths).await? {
+        println!("Imported {} ({} chunks)", filename, chunks);
+    }
+
+    Ok(())
+}
@@ -4,12 +4,10 @@ use::warp_chatbot::{config, run, setup_store};
 async fn main() -> Result<(), handle_errors::Error> {
     dotenv::dotenv().ok();
 
-    let config = config::Config::new().expect("Config can't be set");
+    let config = config::Config::new()?;
     let store = setup_store(&config).await?;
 
     tracing::info!("Q&A service build ID {}", env!("RUST_WEB_DEV_VERSION"));
 
-    run(config, store).await;
-
-    Ok(())
+    run(config, store).await
 }
@@ -0,0 +1,206 @@
+//! 크기와 수명이 제한된 메모리 캐시
+//!
+//! 가득 차면 오래 전에 넣은 항목부터 버린다. 시간은 `tokio::time` 을 쓰므로
+//! 테스트에서 멈춘 시계로 만료를 확인할 수 있다.
+
+use std::collections::{HashMap, VecDeque};
+use std::hash::Hash;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// 지금까지의 카운터 (모니터링용 스냅숏)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CacheMetrics {
+    pub hits: u64,
+    pub misses: u64,
+    /// 용량이 차서 버린 항목 수 (만료는 세지 않는다)
+    pub evictions: u64,
+    pub len: usize,
+}
+
+#[derive(Debug)]
+struct Entry<V> {
+    value: V,
+    expires_at: Instant,
+    /// 같은 키를 다시 넣었을 때 순서 큐의 이전 자리를 구별한다
+    seq: u64,
+}
+
+#[derive(Debug)]
+struct Entries<K, V> {
+    map: HashMap<K, Entry<V>>,
+    order: VecDeque<(K, u64)>,
+    next_seq: u64,
+}
+
+#[derive(Debug)]
+struct Inner<K, V> {
+    capacity: usize,
+    ttl: Duration,
+    entries: Mutex<Entries<K, V>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    evictions: AtomicU64,
+}
+
+/// 복제하면 같은 캐시를 공유한다
+#[derive(Debug)]
+pub struct TtlCache<K, V> {
+    inner: Arc<Inner<K, V>>,
+}
+
+impl<K, V> Clone for TtlCache<K, V> {
+    fn clone(&self) -> Self {
+        TtlCache {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<K, V> TtlCache<K, V>
+where
+    K: Eq + Hash + Clone,
+    V: Clone,
+{
+    pub fn new(capacity: usize, ttl: Duration) -> Self {
+        TtlCache {
+            inner: Arc::new(Inner {
+                capacity,
+                ttl,
+                entries: Mutex::new(Entries {
+                    map: HashMap::new(),
+                    order: VecDeque::new(),
+                    next_seq: 0,
+                }),
+                hits: AtomicU64::new(0),
+                misses: AtomicU64::new(0),
+                evictions: AtomicU64::new(0),
+            }),
+        }
+    }
+
+    pub fn get(&self, key: &K) -> Option<V> {
+        let mut entries = self.lock();
+        let value = match entries.map.get(key) {
+            Some(entry) if entry.expires_at > Instant::now() => Some(entry.value.clone()),
+            Some(_) => {
+                entries.map.remove(key);
+                None
+            }
+            None => None,
+        };
+
+        match value {
+            Some(_) => self.inner.hits.fetch_add(1, Ordering::Relaxed),
+            None => self.inner.misses.fetch_add(1, Ordering::Relaxed),
+        };
+        value
+    }
+
+    pub fn insert(&self, key: K, value: V) {
+        if self.inner.capacity == 0 {
+            return;
+        }
+
+        let mut entries = self.lock();
+        let seq = entries.next_seq;
+        entries.next_seq += 1;
+
+        let replaced = entries.map.insert(
+            key.clone(),
+            Entry {
+                value,
+                expires_at: Instant::now() + self.inner.ttl,
+                seq,
+            },
+        );
+        entries.order.push_back((key, seq));
+        if replaced.is_some() {
+            return;
+        }
+
+        while entries.map.len() > self.inner.capacity {
+            let (key, seq) = match entries.order.pop_front() {
+                Some(oldest) => oldest,
+                None => break,
+            };
+            // 다시 넣었거나 이미 만료로 지운 키의 이전 자리는 건너뛴다
+            if entries.map.get(&key).map(|entry| entry.seq) == Some(seq) {
+                entries.map.remove(&key);
+                self.inner.evictions.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        // 순서 큐에 지난 자리가 너무 쌓이지 않게 한다
+        if entries.order.len() > self.inner.capacity * 2 {
+            let Entries { map, order, .. } = &mut *entries;
+            order.retain(|(key, seq)| map.get(key).map(|entry| entry.seq) == Some(*seq));
+        }
+    }
+
+    pub fn metrics(&self) -> CacheMetrics {
+        CacheMetrics {
+            hits: self.inner.hits.load(Ordering::Relaxed),
+            misses: self.inner.misses.load(Ordering::Relaxed),
+            evictions: self.inner.evictions.load(Ordering::Relaxed),
+            len: self.lock().map.len(),
+        }
+    }
+
+    /// 락을 await 너머로 들고 있지 않으므로 std Mutex 로 충분하다
+    fn lock(&self) -> MutexGuard<'_, Entries<K, V>> {
+        self.inner
+            .entries
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[cfg(test)]
+mod cache_tests {
+    use super::{CacheMetrics, TtlCache};
+    use std::time::Duration;
+
+    #[tokio::test(start_paused = true)]
+    async fn entries_expire() {
+        let cache = TtlCache::new(10, Duration::from_secs(60));
+        cache.insert("a", 1);
+        assert_eq!(cache.get(&"a"), Some(1));
+
+        tokio::time::advance(Duration::from_secs(60)).await;
+        assert_eq!(cache.get(&"a"), None);
+        assert_eq!(
+            cache.metrics(),
+            CacheMetrics {
+                hits: 1,
+                misses: 1,
+                evictions: 0,
+                len: 0,
+            }
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn evicts_oldest_when_full() {
+        let cache = TtlCache::new(2, Duration::from_secs(60));
+        cache.insert("a", 1);
+        cache.insert("b", 2);
+        // 다시 넣으면 가장 최근 항목이 된다
+        cache.insert("a", 3);
+        cache.insert("c", 4);
+
+        assert_eq!(cache.get(&"a"), Some(3));
+        assert_eq!(cache.get(&"b"), None);
+        assert_eq!(cache.get(&"c"), Some(4));
+        assert_eq!(cache.metrics().evictions, 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn zero_capacity_disables_cache() {
+        let cache = TtlCache::new(0, Duration::from_secs(60));
+        cache.insert("a", 1);
+        assert_eq!(cache.get(&"a"), None);
+    }
+}
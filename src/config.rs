@@ -1,5 +1,149 @@
-use clap::Parser;
+use clap::{ArgEnum, Parser};
 use std::env;
+use std::str::FromStr;
+
+/// 비속어 검사 API 를 쓸 수 없을 때 글을 어떻게 처리할지
+#[derive(ArgEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProfanityFallback {
+    /// 503 으로 요청을 거절한다
+    Reject,
+    /// 검사하지 않은 원문을 그대로 저장한다
+    Accept,
+    /// 원문을 저장하되 검토 대기(pending) 상태로 숨긴다
+    Queue,
+}
+
+impl FromStr for ProfanityFallback {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        <Self as ArgEnum>::from_str(s, true)
+    }
+}
+
+/// 비속어를 찾았을 때 할 일
+#[derive(ArgEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProfanityAction {
+    /// `*` 로 가려서 바로 공개한다
+    Censor,
+    /// 원문을 검토 대기열에 보내고 모더레이터가 결정할 때까지 숨긴다
+    Queue,
+}
+
+impl FromStr for ProfanityAction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        <Self as ArgEnum>::from_str(s, true)
+    }
+}
+
+/// 비속어 검사 방식
+#[derive(ArgEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProfanityEngine {
+    /// APILayer bad_words API
+    ApiLayer,
+    /// 내장 단어 목록 (외부 호출 없음)
+    Local,
+}
+
+impl FromStr for ProfanityEngine {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        <Self as ArgEnum>::from_str(s, true)
+    }
+}
+
+/// 챗봇이 참고할 글을 찾는 방식
+#[derive(ArgEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RetrieverKind {
+    /// Postgres 전문 검색
+    Keyword,
+    /// 메모리 벡터 인덱스
+    Vector,
+    /// 두 점수의 가중합
+    Hybrid,
+}
+
+impl FromStr for RetrieverKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        <Self as ArgEnum>::from_str(s, true)
+    }
+}
+
+/// 지난 대화가 창을 넘으면 밀려난 메시지를 어떻게 할지
+#[derive(ArgEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HistoryStrategy {
+    /// 버린다
+    Truncate,
+    /// 모델로 요약해서 대화에 남긴다
+    Summarize,
+}
+
+impl FromStr for HistoryStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        <Self as ArgEnum>::from_str(s, true)
+    }
+}
+
+/// 챗봇이 부를 LLM API 형식
+#[derive(ArgEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LlmProvider {
+    /// OpenAI 호환 chat completions API (llama.cpp, vLLM 같은 로컬 서버 포함)
+    #[clap(name = "openai")]
+    OpenAi,
+    /// Anthropic messages API
+    Anthropic,
+    /// 정해 둔 답을 돌려준다 (외부 호출 없음)
+    Fake,
+}
+
+impl FromStr for LlmProvider {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        <Self as ArgEnum>::from_str(s, true)
+    }
+}
+
+/// 질문과 답변을 벡터로 바꾸는 방식
+#[derive(ArgEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EmbedderKind {
+    /// OpenAI 호환 embeddings API
+    Http,
+    /// 단어 해싱 (외부 호출 없음)
+    Hashing,
+}
+
+impl FromStr for EmbedderKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        <Self as ArgEnum>::from_str(s, true)
+    }
+}
+
+/// 벡터를 저장하고 찾는 곳
+#[derive(ArgEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VectorStoreKind {
+    /// 메모리에서 전부 비교한다 (DB 의 embeddings 테이블에서 읽는다)
+    Memory,
+    /// Postgres pgvector 확장 (없으면 시작하지 않는다)
+    Pgvector,
+}
+
+impl FromStr for VectorStoreKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        <Self as ArgEnum>::from_str(s, true)
+    }
+}
 
 /// Q&A 웹 서비스 API
 #[derive(Parser, Debug, PartialEq)]
@@ -25,53 +169,334 @@ pub struct Config {
     /// 데이터베이스 이름
     #[clap(long, default_value = "rustwebdev")]
     pub db_name: String,
+    /// 테이블을 만들 스키마 (없으면 search_path 기본값)
+    #[clap(long)]
+    pub db_schema: Option<String>,
+    /// 토큰을 암호화하는 PASETO 키 (PASETO_KEY 환경 변수로 설정한다)
+    #[clap(skip)]
+    pub paseto_key: String,
+    /// 비속어 검사 방식(api-layer, local)
+    #[clap(long, arg_enum, default_value = "api-layer")]
+    pub profanity_engine: ProfanityEngine,
+    /// 비속어를 찾았을 때 할 일(censor, queue)
+    #[clap(long, arg_enum, default_value = "censor")]
+    pub profanity_action: ProfanityAction,
+    /// local 검사에 더할 단어 목록 파일 (한 줄에 한 단어)
+    #[clap(long)]
+    pub profanity_words: Option<String>,
+    /// APILayer 기본 URL
+    #[clap(long, default_value = "https://api.apilayer.com")]
+    pub api_layer_url: String,
+    /// BadWords API 키 (BAD_WORDS_API_KEY 환경 변수로 설정한다)
+    #[clap(skip)]
+    pub bad_words_api_key: String,
+    /// 비속어 검사 API 장애 시 처리 방식(reject, accept, queue)
+    #[clap(long, arg_enum, default_value = "reject")]
+    pub profanity_fallback: ProfanityFallback,
+    /// 요청 한 번의 타임아웃(ms)
+    #[clap(long, default_value = "5000")]
+    pub profanity_timeout_ms: u64,
+    /// 비속어 검사 API 에 동시에 보낼 수 있는 요청 수
+    #[clap(long, default_value = "16")]
+    pub profanity_max_concurrent: usize,
+    /// 일시적인 에러에서 다시 시도할 횟수
+    #[clap(long, default_value = "2")]
+    pub profanity_max_retries: u32,
+    /// 연속으로 이만큼 실패하면 서킷을 연다
+    #[clap(long, default_value = "5")]
+    pub profanity_failure_threshold: u32,
+    /// 서킷을 연 뒤 다시 시도해 보기까지의 시간(ms)
+    #[clap(long, default_value = "30000")]
+    pub profanity_reset_timeout_ms: u64,
+    /// 메모리에 둘 비속어 검사 결과 수 (0 이면 캐시하지 않는다)
+    #[clap(long, default_value = "10000")]
+    pub profanity_cache_capacity: usize,
+    /// 검사 결과를 다시 쓸 수 있는 시간(초)
+    #[clap(long, default_value = "86400")]
+    pub profanity_cache_ttl_secs: u64,
+    /// 검사 결과를 DB 에도 저장해서 재시작과 인스턴스 사이에 공유한다
+    #[clap(long)]
+    pub profanity_cache_persistent: bool,
+    /// LLM API 형식(openai, anthropic, fake)
+    #[clap(long, arg_enum, default_value = "openai")]
+    pub llm_provider: LlmProvider,
+    /// LLM API 기본 URL
+    #[clap(long, default_value = "https://api.openai.com")]
+    pub llm_url: String,
+    /// LLM API 키 (LLM_API_KEY 환경 변수로 설정한다, 없으면 인증 헤더를 보내지 않는다)
+    #[clap(skip)]
+    pub llm_api_key: String,
+    /// 챗봇이 쓸 모델
+    #[clap(long, default_value = "gpt-4o-mini")]
+    pub llm_model: String,
+    /// LLM 요청 한 번의 타임아웃(ms)
+    #[clap(long, default_value = "30000")]
+    pub llm_timeout_ms: u64,
+    /// 답 하나의 최대 토큰 수
+    #[clap(long, default_value = "1024")]
+    pub llm_max_tokens: u32,
+    /// fake 모델이 차례로 돌려줄 답 (여러 번 줄 수 있다, 없으면 고정된 답)
+    #[clap(long = "llm-fake-reply")]
+    pub llm_fake_replies: Vec<String>,
+    /// 첫 번째 LLM 을 쓸 수 없을 때 대신 부를 API 형식 (없으면 대신하지 않는다)
+    #[clap(long, arg_enum)]
+    pub llm_fallback_provider: Option<LlmProvider>,
+    /// 대신 부를 LLM API 기본 URL (없으면 llm_url)
+    #[clap(long)]
+    pub llm_fallback_url: Option<String>,
+    /// 대신 부를 LLM API 키 (LLM_FALLBACK_API_KEY 환경 변수로 설정한다)
+    #[clap(skip)]
+    pub llm_fallback_api_key: String,
+    /// 대신 부를 모델 (없으면 llm_model)
+    #[clap(long)]
+    pub llm_fallback_model: Option<String>,
+    /// 대신 부를 LLM 요청 한 번의 타임아웃(ms)
+    #[clap(long, default_value = "30000")]
+    pub llm_fallback_timeout_ms: u64,
+    /// LLM 마다 연속으로 이만큼 실패하면 서킷을 열고 다음 LLM 으로 넘어간다
+    #[clap(long, default_value = "3")]
+    pub llm_failure_threshold: u32,
+    /// 서킷을 연 뒤 다시 시도해 보기까지의 시간(ms)
+    #[clap(long, default_value = "30000")]
+    pub llm_reset_timeout_ms: u64,
+    /// 챗봇이 답할 때 참고할 글 수
+    #[clap(long, default_value = "5")]
+    pub chat_top_k: usize,
+    /// POST /questions/similar 가 돌려주는 질문 수 기본값
+    #[clap(long, default_value = "5")]
+    pub similar_questions_limit: usize,
+    /// 새 질문과 비슷한 질문이 있으면 중복일 수 있다고 409 로 막는다 (force=true 면 올린다)
+    #[clap(long)]
+    pub reject_duplicates: bool,
+    /// reject_duplicates 일 때 이 유사도 이상이면 중복일 수 있다고 본다
+    #[clap(long, default_value = "0.9")]
+    pub duplicate_min_score: f32,
+    /// 답변이 없는 질문에 모델이 답변 초안을 달게 한다 (질문 작성자가 받아들이거나 거절한다)
+    #[clap(long)]
+    pub ai_drafts: bool,
+    /// 질문이 올라온 뒤 이만큼(분) 답변이 없으면 초안을 만든다
+    #[clap(long, default_value = "30")]
+    pub ai_draft_delay_mins: u32,
+    /// 초안을 만들 질문을 찾는 간격(초)
+    #[clap(long, default_value = "60")]
+    pub ai_draft_interval_secs: u64,
+    /// 한 번에 초안을 만들 최대 질문 수
+    #[clap(long, default_value = "10")]
+    pub ai_draft_batch_size: usize,
+    /// 초안을 만들다 이만큼 실패한 질문은 더 시도하지 않는다
+    #[clap(long, default_value = "3")]
+    pub ai_draft_max_attempts: u32,
+    /// 챗봇 검색 방식(keyword, vector, hybrid)
+    #[clap(long, arg_enum, default_value = "hybrid")]
+    pub retriever: RetrieverKind,
+    /// 후속 질문의 프롬프트에 넣을 지난 대화의 최대 글자 수
+    #[clap(long, default_value = "4000")]
+    pub chat_history_chars: usize,
+    /// 창을 넘은 지난 대화 처리(truncate, summarize)
+    #[clap(long, arg_enum, default_value = "truncate")]
+    pub chat_history_strategy: HistoryStrategy,
+    /// hybrid 검색에서 의미(벡터) 점수의 비중
+    #[clap(long, default_value = "0.7")]
+    pub hybrid_semantic_weight: f32,
+    /// hybrid 검색에서 키워드 점수의 비중
+    #[clap(long, default_value = "0.3")]
+    pub hybrid_keyword_weight: f32,
+    /// 벡터 저장소(memory, pgvector)
+    #[clap(long, arg_enum, default_value = "memory")]
+    pub vector_store: VectorStoreKind,
+    /// memory 벡터 저장소를 DB 에서 다시 읽는 간격(초)
+    #[clap(long, default_value = "60")]
+    pub vector_refresh_secs: u64,
+    /// 벡터 검색에서 이 유사도 이하인 글은 버린다
+    #[clap(long, default_value = "0.1")]
+    pub vector_min_score: f32,
+    /// 임베딩 방식(http, hashing)
+    #[clap(long, arg_enum, default_value = "hashing")]
+    pub embedder: EmbedderKind,
+    /// OpenAI 호환 embeddings API 기본 URL
+    #[clap(long, default_value = "https://api.openai.com")]
+    pub embedding_url: String,
+    /// embeddings API 키 (EMBEDDING_API_KEY 환경 변수로 설정한다)
+    #[clap(skip)]
+    pub embedding_api_key: String,
+    /// 임베딩 모델
+    #[clap(long, default_value = "text-embedding-3-small")]
+    pub embedding_model: String,
+    /// 벡터 차원 수 (바꾸면 모든 글의 벡터를 다시 만든다)
+    #[clap(long, default_value = "384")]
+    pub embedding_dimensions: usize,
+    /// embeddings 요청 한 번의 타임아웃(ms)
+    #[clap(long, default_value = "30000")]
+    pub embedding_timeout_ms: u64,
+    /// 요청 한 번에 보낼 글 수
+    #[clap(long, default_value = "32")]
+    pub embedding_batch_size: usize,
+    /// 임베딩 워커가 새 글을 찾는 간격(초)
+    #[clap(long, default_value = "10")]
+    pub embedding_interval_secs: u64,
+    /// 문서를 나눌 조각 하나의 최대 글자 수
+    #[clap(long, default_value = "1000")]
+    pub chunk_size: usize,
+    /// 이어지는 조각끼리 겹치는 글자 수 (chunk_size 보다 작아야 한다)
+    #[clap(long, default_value = "200")]
+    pub chunk_overlap: usize,
+    /// POST /documents 로 올릴 수 있는 최대 크기(바이트)
+    #[clap(long, default_value = "10485760")]
+    pub document_max_bytes: u64,
 }
 
 impl Config {
     pub fn new() -> Result<Config, handle_errors::Error> {
-        // let config = Config::parse();
-        //
-        // if let Err(_) = env::var("BAD_WORDS_API_KEY") {
-        //     panic!("BadWords API key not set");
-        // }
-        //
-        // if let Err(_) = env::var("PASETO_KEY") {
-        //     panic!("PASETO key not set");
-        // }
-        //
-        // let port = std::env::var("PORT")
-        //     .ok()
-        //     .map(|val| val.parse::<u16>())
-        //     .unwrap_or(Ok(8080))
-        //     .map_err(|e| handle_errors::Error::ParseError(e))?;
-        //
-        // let db_user = env::var("POSTGRES_USER").unwrap_or(config.db_user.to_owned());
-        // let db_password = env::var("POSTGRES_PASSWORD").unwrap();
-        // let db_host = env::var("POSTGRES_HOST").unwrap_or(config.db_host.to_owned());
-        // let db_port = env::var("POSTGRES_PORT").unwrap_or(config.db_port.to_string());
-        // let db_name = env::var("POSTGRES_DB").unwrap_or(config.db_name.to_owned());
-        //
-        // Ok(Config {
-        //     log_level: config.log_level,
-        //     port,
-        //     db_user,
-        //     db_password,
-        //     db_host,
-        //     db_port: db_port
-        //         .parse::<u16>()
-        //         .map_err(|e| handle_errors::Error::ParseError(e))?,
-        //     db_name,
-        // })
-        
-        Ok(Config{
-            log_level: "warn".to_string(),
-            port: 8080,
-            db_user: "dev".to_string(),
-            db_password: "dev".to_string(),
-            db_host: "localhost".to_string(),
-            db_port: 5432,
-            db_name: "rustwebdev".to_string(),})
+        Config::with_env(Config::parse())
     }
+
+    /// 명령줄로 읽은 설정을 환경 변수로 덮어쓴다 (다른 인자와 함께 파싱한 CLI 에서 쓴다)
+    pub fn with_env(config: Config) -> Result<Config, handle_errors::Error> {
+        if config.chunk_overlap >= config.chunk_size {
+            return Err(handle_errors::Error::InvalidConfig(
+                "chunk_overlap must be smaller than chunk_size".to_string(),
+            ));
+        }
+
+        let profanity_engine =
+            parse_env("PROFANITY_ENGINE", "api-layer, local")?.unwrap_or(config.profanity_engine);
+
+        let profanity_action =
+            parse_env("PROFANITY_ACTION", "censor, queue")?.unwrap_or(config.profanity_action);
+
+        // local 검사는 APILayer 를 부르지 않으므로 키가 없어도 된다
+        let bad_words_api_key = match env::var("BAD_WORDS_API_KEY") {
+            Ok(key) => key,
+            Err(_) if profanity_engine == ProfanityEngine::Local => String::new(),
+            Err(_) => {
+                return Err(handle_errors::Error::InvalidConfig(
+                    "BadWords API key not set".to_string(),
+                ))
+            }
+        };
+
+        let paseto_key = env::var("PASETO_KEY")
+            .map_err(|_| handle_errors::Error::InvalidConfig("PASETO key not set".to_string()))?;
+
+        let port = std::env::var("PORT")
+            .ok()
+            .map(|val| val.parse::<u16>())
+            .unwrap_or(Ok(config.port))
+            .map_err(handle_errors::Error::ParseError)?;
+
+        let db_user = env::var("POSTGRES_USER").unwrap_or(config.db_user.to_owned());
+        let db_password = env::var("POSTGRES_PASSWORD").map_err(|_| {
+            handle_errors::Error::InvalidConfig("POSTGRES_PASSWORD not set".to_string())
+        })?;
+        let db_host = env::var("POSTGRES_HOST").unwrap_or(config.db_host.to_owned());
+        let db_port = env::var("POSTGRES_PORT").unwrap_or(config.db_port.to_string());
+        let db_name = env::var("POSTGRES_DB").unwrap_or(config.db_name.to_owned());
+        let api_layer_url = env::var("API_LAYER_URL").unwrap_or(config.api_layer_url);
+        let profanity_fallback = parse_env("PROFANITY_FALLBACK", "reject, accept, queue")?
+            .unwrap_or(config.profanity_fallback);
+
+        let llm_provider =
+            parse_env("LLM_PROVIDER", "openai, anthropic, fake")?.unwrap_or(config.llm_provider);
+        let llm_fallback_provider = parse_env("LLM_FALLBACK_PROVIDER", "openai, anthropic, fake")?
+            .or(config.llm_fallback_provider);
+
+        let retriever =
+            parse_env("RETRIEVER", "keyword, vector, hybrid")?.unwrap_or(config.retriever);
+
+        let chat_history_strategy = parse_env("CHAT_HISTORY_STRATEGY", "truncate, summarize")?
+            .unwrap_or(config.chat_history_strategy);
+
+        let vector_store =
+            parse_env("VECTOR_STORE", "memory, pgvector")?.unwrap_or(config.vector_store);
+
+        let embedder = parse_env("EMBEDDER", "http, hashing")?.unwrap_or(config.embedder);
+
+        Ok(Config {
+            log_level: config.log_level,
+            port,
+            db_user,
+            db_password,
+            db_host,
+            db_port: db_port
+                .parse::<u16>()
+                .map_err(handle_errors::Error::ParseError)?,
+            db_name,
+            db_schema: env::var("POSTGRES_SCHEMA").ok().or(config.db_schema),
+            paseto_key,
+            profanity_engine,
+            profanity_action,
+            profanity_words: env::var("PROFANITY_WORDS").ok().or(config.profanity_words),
+            api_layer_url,
+            bad_words_api_key,
+            profanity_fallback,
+            profanity_timeout_ms: config.profanity_timeout_ms,
+            profanity_max_concurrent: config.profanity_max_concurrent,
+            profanity_max_retries: config.profanity_max_retries,
+            profanity_failure_threshold: config.profanity_failure_threshold,
+            profanity_reset_timeout_ms: config.profanity_reset_timeout_ms,
+            profanity_cache_capacity: config.profanity_cache_capacity,
+            profanity_cache_ttl_secs: config.profanity_cache_ttl_secs,
+            profanity_cache_persistent: config.profanity_cache_persistent,
+            llm_provider,
+            llm_url: env::var("LLM_URL").unwrap_or(config.llm_url),
+            llm_api_key: env::var("LLM_API_KEY").unwrap_or_default(),
+            llm_model: env::var("LLM_MODEL").unwrap_or(config.llm_model),
+            llm_timeout_ms: config.llm_timeout_ms,
+            llm_max_tokens: config.llm_max_tokens,
+            llm_fake_replies: config.llm_fake_replies,
+            llm_fallback_provider,
+            llm_fallback_url: env::var("LLM_FALLBACK_URL")
+                .ok()
+                .or(config.llm_fallback_url),
+            llm_fallback_api_key: env::var("LLM_FALLBACK_API_KEY").unwrap_or_default(),
+            llm_fallback_model: env::var("LLM_FALLBACK_MODEL")
+                .ok()
+                .or(config.llm_fallback_model),
+            llm_fallback_timeout_ms: config.llm_fallback_timeout_ms,
+            llm_failure_threshold: config.llm_failure_threshold,
+            llm_reset_timeout_ms: config.llm_reset_timeout_ms,
+            chat_top_k: config.chat_top_k,
+            similar_questions_limit: config.similar_questions_limit,
+            reject_duplicates: config.reject_duplicates,
+            duplicate_min_score: config.duplicate_min_score,
+            ai_drafts: config.ai_drafts,
+            ai_draft_delay_mins: config.ai_draft_delay_mins,
+            ai_draft_interval_secs: config.ai_draft_interval_secs,
+            ai_draft_batch_size: config.ai_draft_batch_size,
+            ai_draft_max_attempts: config.ai_draft_max_attempts,
+            retriever,
+            chat_history_chars: config.chat_history_chars,
+            chat_history_strategy,
+            hybrid_semantic_weight: config.hybrid_semantic_weight,
+            hybrid_keyword_weight: config.hybrid_keyword_weight,
+            vector_store,
+            vector_refresh_secs: config.vector_refresh_secs,
+            vector_min_score: config.vector_min_score,
+            embedder,
+            embedding_url: env::var("EMBEDDING_URL").unwrap_or(config.embedding_url),
+            embedding_api_key: env::var("EMBEDDING_API_KEY").unwrap_or_default(),
+            embedding_model: env::var("EMBEDDING_MODEL").unwrap_or(config.embedding_model),
+            embedding_dimensions: config.embedding_dimensions,
+            embedding_timeout_ms: config.embedding_timeout_ms,
+            embedding_batch_size: config.embedding_batch_size,
+            embedding_interval_secs: config.embedding_interval_secs,
+            chunk_size: config.chunk_size,
+            chunk_overlap: config.chunk_overlap,
+            document_max_bytes: config.document_max_bytes,
+        })
+    }
+}
+
+/// 환경 변수가 있으면 값으로 바꾼다 (없으면 None)
+fn parse_env<T: FromStr>(name: &str, expected: &str) -> Result<Option<T>, handle_errors::Error> {
+    env::var(name)
+        .ok()
+        .map(|val| {
+            val.parse::<T>().map_err(|_| {
+                handle_errors::Error::InvalidConfig(format!("{} must be one of {}", name, expected))
+            })
+        })
+        .transpose()
 }
 
 #[cfg(test)]
@@ -90,8 +515,11 @@ mod config_tests {
 
     #[test]
     fn unset_and_set_api_key() {
-        let result = std::panic::catch_unwind(|| Config::new());
-        assert!(result.is_err());
+        let result = Config::new();
+        assert!(matches!(
+            result,
+            Err(handle_errors::Error::InvalidConfig(_))
+        ));
 
         set_env();
 
@@ -103,10 +531,103 @@ mod config_tests {
             db_host: "localhost".to_string(),
             db_port: 5432,
             db_name: "rustwebdev".to_string(),
+            db_schema: None,
+            paseto_key: "RANDOM WORDS WINTER MACINTOSH PC".to_string(),
+            profanity_engine: ProfanityEngine::ApiLayer,
+            profanity_action: ProfanityAction::Censor,
+            profanity_words: None,
+            api_layer_url: "https://api.apilayer.com".to_string(),
+            bad_words_api_key: "yes".to_string(),
+            profanity_fallback: ProfanityFallback::Reject,
+            profanity_timeout_ms: 5000,
+            profanity_max_concurrent: 16,
+            profanity_max_retries: 2,
+            profanity_failure_threshold: 5,
+            profanity_reset_timeout_ms: 30000,
+            profanity_cache_capacity: 10000,
+            profanity_cache_ttl_secs: 86400,
+            profanity_cache_persistent: false,
+            llm_provider: LlmProvider::OpenAi,
+            llm_url: "https://api.openai.com".to_string(),
+            llm_api_key: String::new(),
+            llm_model: "gpt-4o-mini".to_string(),
+            llm_timeout_ms: 30000,
+            llm_max_tokens: 1024,
+            llm_fake_replies: Vec::new(),
+            llm_fallback_provider: None,
+            llm_fallback_url: None,
+            llm_fallback_api_key: String::new(),
+            llm_fallback_model: None,
+            llm_fallback_timeout_ms: 30000,
+            llm_failure_threshold: 3,
+            llm_reset_timeout_ms: 30000,
+            chat_top_k: 5,
+            similar_questions_limit: 5,
+            reject_duplicates: false,
+            duplicate_min_score: 0.9,
+            ai_drafts: false,
+            ai_draft_delay_mins: 30,
+            ai_draft_interval_secs: 60,
+            ai_draft_batch_size: 10,
+            ai_draft_max_attempts: 3,
+            retriever: RetrieverKind::Hybrid,
+            chat_history_chars: 4000,
+            chat_history_strategy: HistoryStrategy::Truncate,
+            hybrid_semantic_weight: 0.7,
+            hybrid_keyword_weight: 0.3,
+            vector_store: VectorStoreKind::Memory,
+            vector_refresh_secs: 60,
+            vector_min_score: 0.1,
+            embedder: EmbedderKind::Hashing,
+            embedding_url: "https://api.openai.com".to_string(),
+            embedding_api_key: String::new(),
+            embedding_model: "text-embedding-3-small".to_string(),
+            embedding_dimensions: 384,
+            embedding_timeout_ms: 30000,
+            embedding_batch_size: 32,
+            embedding_interval_secs: 10,
+            chunk_size: 1000,
+            chunk_overlap: 200,
+            document_max_bytes: 10485760,
         };
 
         let config = Config::new().unwrap();
 
         assert_eq!(config, expected);
+
+        // 잘못된 값은 패닉 대신 설정 에러가 된다
+        assert_invalid("PROFANITY_ENGINE", "regex");
+        assert_invalid("PROFANITY_ACTION", "delete");
+        assert_invalid("PROFANITY_FALLBACK", "retry");
+        assert_invalid("LLM_PROVIDER", "gemini");
+        assert_invalid("LLM_FALLBACK_PROVIDER", "gemini");
+        assert_invalid("RETRIEVER", "semantic");
+        assert_invalid("CHAT_HISTORY_STRATEGY", "forget");
+        assert_invalid("VECTOR_STORE", "faiss");
+        assert_invalid("EMBEDDER", "word2vec");
+    }
+
+    #[test]
+    fn chunk_overlap_must_be_smaller_than_chunk_size() {
+        let config = Config::parse_from(["test", "--chunk-size", "100", "--chunk-overlap", "100"]);
+        match Config::with_env(config) {
+            Err(handle_errors::Error::InvalidConfig(message)) => {
+                assert!(message.starts_with("chunk_overlap"), "{}", message)
+            }
+            other => panic!("expected invalid chunk_overlap, got {:?}", other),
+        }
+    }
+
+    /// 환경 변수 하나만 잘못 넣고 설정 에러인지 확인한다
+    fn assert_invalid(name: &str, value: &str) {
+        env::set_var(name, value);
+        let result = Config::new();
+        env::remove_var(name);
+        match result {
+            Err(handle_errors::Error::InvalidConfig(message)) => {
+                assert!(message.starts_with(name), "{}", message)
+            }
+            other => panic!("expected invalid {}, got {:?}", name, other),
+        }
     }
 }
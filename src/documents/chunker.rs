@@ -0,0 +1,104 @@
+/// 글을 size 글자 이하의 조각으로 나눈다
+/// 문단, 줄, 문장, 공백 순서로 자연스러운 경계를 찾고, 이어지는 조각은 overlap 글자쯤 겹친다
+pub(super) fn split(text: &str, size: usize, overlap: usize) -> Vec<String> {
+    let chars: Vec<char> = text.trim().chars().collect();
+    let mut chunks = Vec::new();
+    let mut start = 0;
+
+    while start < chars.len() {
+        let mut end = (start + size).min(chars.len());
+        if end < chars.len() {
+            // 너무 짧은 조각이 생기지 않도록 뒤쪽 절반에서만 경계를 찾는다
+            end = boundary(&chars, start + size / 2, end);
+        }
+
+        let chunk: String = chars[start..end].iter().collect();
+        let chunk = chunk.trim();
+        if !chunk.is_empty() {
+            chunks.push(chunk.to_string());
+        }
+        if end == chars.len() {
+            break;
+        }
+
+        // 겹치는 부분이 단어 중간에서 시작하지 않게 한다
+        let mut next = end.saturating_sub(overlap).max(start + 1);
+        while next < end && !chars[next - 1].is_whitespace() {
+            next += 1;
+        }
+        start = next;
+    }
+
+    chunks
+}
+
+/// [min, max) 에서 가장 나은 경계 바로 뒤의 위치 (없으면 max 에서 자른다)
+fn boundary(chars: &[char], min: usize, max: usize) -> usize {
+    let last = |is_boundary: &dyn Fn(usize) -> bool| {
+        (min..max).rev().find(|&i| is_boundary(i)).map(|i| i + 1)
+    };
+
+    last(&|i| chars[i] == '\n' && i > 0 && chars[i - 1] == '\n')
+        .or_else(|| last(&|i| chars[i] == '\n'))
+        .or_else(|| {
+            last(&|i| {
+                matches!(chars[i], '.' | '!' | '?' | '。')
+                    && chars.get(i + 1).is_none_or(|c| c.is_whitespace())
+            })
+        })
+        .or_else(|| last(&|i| chars[i].is_whitespace()))
+        .unwrap_or(max)
+}
+
+#[cfg(test)]
+mod chunker_tests {
+    use super::*;
+
+    #[test]
+    fn keeps_short_text_whole() {
+        assert_eq!(split("  one paragraph  ", 100, 20), vec!["one paragraph"]);
+        assert!(split(" \n ", 100, 20).is_empty());
+    }
+
+    #[test]
+    fn prefers_paragraphs_and_overlaps() {
+        let text = "First paragraph is here.\n\nSecond one follows. It has two sentences.";
+
+        assert_eq!(
+            split(text, 50, 0),
+            vec![
+                "First paragraph is here.",
+                "Second one follows. It has two sentences."
+            ]
+        );
+        assert_eq!(
+            split(text, 30, 0),
+            vec![
+                "First paragraph is here.",
+                "Second one follows.",
+                "It has two sentences."
+            ]
+        );
+
+        let words = "alpha beta gamma delta epsilon zeta eta theta iota kappa";
+        let chunks = split(words, 20, 8);
+        assert!(chunks.len() > 2);
+        // 다음 조각은 앞 조각의 끝 단어부터 다시 시작한다
+        for pair in chunks.windows(2) {
+            let first_word = pair[1].split(' ').next().unwrap();
+            assert!(
+                pair[0].split(' ').any(|word| word == first_word),
+                "{:?}",
+                pair
+            );
+            assert!(pair.iter().all(|chunk| chunk.len() <= 20));
+        }
+    }
+
+    #[test]
+    fn cuts_long_words() {
+        let chunks = split(&"x".repeat(25), 10, 3);
+        assert_eq!(chunks.len(), 3);
+        assert!(chunks.iter().all(|chunk| chunk.len() <= 10));
+    }
+}
@@ -0,0 +1,81 @@
+use super::Section;
+
+/// ATX 제목(`#` ~ `######`)마다 구역을 나눈다
+/// 제목은 상위 제목과 이어 `설치 > Linux` 처럼 경로로 남기고, 코드 블록 안의 `#` 은 무시한다
+pub(super) fn sections(text: &str) -> Vec<Section> {
+    let mut sections = Vec::new();
+    let mut headings: Vec<(usize, String)> = Vec::new();
+    let mut lines: Vec<&str> = Vec::new();
+    let mut fence: Option<&str> = None;
+
+    for line in text.lines() {
+        let trimmed = line.trim_start();
+        if let Some(marker) = fence {
+            if trimmed.starts_with(marker) {
+                fence = None;
+            }
+            lines.push(line);
+            continue;
+        }
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            fence = Some(&trimmed[..3]);
+            lines.push(line);
+            continue;
+        }
+
+        match heading(line) {
+            Some((level, title)) => {
+                push(&mut sections, &headings, &mut lines);
+                headings.retain(|(parent, _)| *parent < level);
+                headings.push((level, title));
+            }
+            None => lines.push(line),
+        }
+    }
+    push(&mut sections, &headings, &mut lines);
+
+    sections
+}
+
+/// 제목 줄이면 (단계, 제목)
+fn heading(line: &str) -> Option<(usize, String)> {
+    // 네 칸 이상 들여 쓰면 코드 블록이다
+    if line.starts_with("    ") || line.starts_with('\t') {
+        return None;
+    }
+    let line = line.trim_start();
+    let level = line.chars().take_while(|c| *c == '#').count();
+    let rest = &line[level..];
+    if !(1..=6).contains(&level) || !(rest.is_empty() || rest.starts_with([' ', '\t'])) {
+        return None;
+    }
+
+    // 닫는 `#` 도 제목에 넣지 않는다
+    let title = rest.trim().trim_end_matches('#').trim_end();
+    if title.is_empty() {
+        return None;
+    }
+    Some((level, title.to_string()))
+}
+
+/// 모아 둔 줄을 지금 제목 경로의 구역으로 넣는다 (빈 구역은 버린다)
+fn push(sections: &mut Vec<Section>, headings: &[(usize, String)], lines: &mut Vec<&str>) {
+    let text = lines.join("\n").trim().to_string();
+    lines.clear();
+    if text.is_empty() {
+        return;
+    }
+
+    let heading = if headings.is_empty() {
+        None
+    } else {
+        Some(
+            headings
+                .iter()
+                .map(|(_, title)| title.as_str())
+                .collect::<Vec<_>>()
+                .join(" > "),
+        )
+    };
+    sections.push(Section { heading, text });
+}
@@ -0,0 +1,263 @@
+use handle_errors::{Error, FieldError};
+
+use crate::config::Config;
+use crate::types::chat::SourceType;
+use crate::types::document::{Chunk, NewDocument};
+
+mod chunker;
+mod markdown;
+
+/// 올리거나 가져온 파일을 읽어 검색할 조각으로 나눈다
+/// 조각은 제목(Markdown)이나 쪽(PDF)을 넘지 않는다
+#[derive(Debug, Clone)]
+pub struct DocumentProcessor {
+    chunk_size: usize,
+    chunk_overlap: usize,
+    max_bytes: u64,
+}
+
+/// 같은 제목 아래의 글
+#[derive(Debug, Clone, PartialEq)]
+struct Section {
+    heading: Option<String>,
+    text: String,
+}
+
+impl DocumentProcessor {
+    pub fn new(config: &Config) -> Self {
+        DocumentProcessor {
+            chunk_size: config.chunk_size,
+            chunk_overlap: config.chunk_overlap,
+            max_bytes: config.document_max_bytes,
+        }
+    }
+
+    /// 올릴 수 있는 파일의 최대 크기(바이트)
+    pub fn max_bytes(&self) -> u64 {
+        self.max_bytes
+    }
+
+    /// 파일 이름의 확장자로 형식을 고른다
+    /// 제목이 없으면 확장자를 뺀 파일 이름을 쓴다
+    /// PDF 를 읽는 동안 런타임을 막지 않도록 블로킹 스레드에서 돌린다
+    pub async fn process(
+        &self,
+        filename: String,
+        title: Option<String>,
+        bytes: Vec<u8>,
+    ) -> Result<NewDocument, Error> {
+        let processor = self.clone();
+        // pdf-extract 는 깨진 파일에서 패닉할 수 있다
+        tokio::task::spawn_blocking(move || processor.parse(filename, title, &bytes))
+            .await
+            .map_err(|_| Error::InvalidBody("document can't be read".to_string()))?
+    }
+
+    fn parse(
+        &self,
+        filename: String,
+        title: Option<String>,
+        bytes: &[u8],
+    ) -> Result<NewDocument, Error> {
+        let source_type = SourceType::from_filename(&filename)
+            .ok_or_else(|| file_error("must be a .md, .txt or .pdf file"))?;
+
+        let sections = match source_type {
+            SourceType::Pdf => pdf_extract::extract_text_from_mem_by_pages(bytes)
+                .map_err(|error| Error::InvalidBody(format!("PDF can't be read: {}", error)))?
+                .into_iter()
+                .enumerate()
+                .map(|(page, text)| Section {
+                    heading: Some(format!("p. {}", page + 1)),
+                    text,
+                })
+                .collect(),
+            _ => {
+                let text = std::str::from_utf8(bytes)
+                    .map_err(|_| file_error("must be UTF-8 encoded"))?
+                    .replace("\r\n", "\n");
+                match source_type {
+                    SourceType::Markdown => markdown::sections(&text),
+                    _ => vec![Section {
+                        heading: None,
+                        text,
+                    }],
+                }
+            }
+        };
+
+        let chunks: Vec<Chunk> = sections
+            .into_iter()
+            .flat_map(|section| {
+                chunker::split(&section.text, self.chunk_size, self.chunk_overlap)
+                    .into_iter()
+                    .map(move |content| Chunk {
+                        heading: section.heading.clone(),
+                        content,
+                    })
+            })
+            .collect();
+        if chunks.is_empty() {
+            return Err(file_error("must contain text"));
+        }
+
+        let title = title
+            .map(|title| title.trim().to_string())
+            .filter(|title| !title.is_empty())
+            .unwrap_or_else(|| match filename.rsplit_once('.') {
+                Some((stem, _)) if !stem.is_empty() => stem.to_string(),
+                _ => filename.clone(),
+            });
+
+        Ok(NewDocument {
+            title,
+            source_type,
+            filename,
+            chunks,
+        })
+    }
+}
+
+fn file_error(message: &str) -> Error {
+    Error::ValidationError(vec![FieldError {
+        field: "file".to_string(),
+        message: message.to_string(),
+    }])
+}
+
+#[cfg(test)]
+mod documents_tests {
+    use super::*;
+    use clap::Parser;
+
+    fn processor(chunk_size: usize, chunk_overlap: usize) -> DocumentProcessor {
+        let mut config = Config::parse_from(["test"]);
+        config.chunk_size = chunk_size;
+        config.chunk_overlap = chunk_overlap;
+        DocumentProcessor::new(&config)
+    }
+
+    #[tokio::test]
+    async fn splits_markdown_under_headings() {
+        let markdown = "Intro line.\n\n# Install\n\nRun cargo.\n\n## Linux\n\napt install libpq-dev\n\n# Usage\n\n```sh\n# not a heading\n```\n";
+
+        let document = processor(1000, 200)
+            .process("guide.md".to_string(), None, markdown.as_bytes().to_vec())
+            .await
+            .unwrap();
+
+        assert_eq!(document.title, "guide");
+        assert_eq!(document.source_type, SourceType::Markdown);
+        let headings: Vec<_> = document
+            .chunks
+            .iter()
+            .map(|chunk| chunk.heading.as_deref())
+            .collect();
+        assert_eq!(
+            headings,
+            vec![
+                None,
+                Some("Install"),
+                Some("Install > Linux"),
+                Some("Usage")
+            ]
+        );
+        assert_eq!(document.chunks[3].content, "```sh\n# not a heading\n```");
+    }
+
+    /// 쪽마다 Helvetica 로 한 줄씩 쓴 PDF
+    fn pdf(pages: &[&str]) -> Vec<u8> {
+        let page_ids: Vec<usize> = (0..pages.len()).map(|i| 4 + i * 2).collect();
+        let mut objects = vec![
+            "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+            format!(
+                "<< /Type /Pages /Kids [{}] /Count {} >>",
+                page_ids
+                    .iter()
+                    .map(|id| format!("{} 0 R", id))
+                    .collect::<Vec<_>>()
+                    .join(" "),
+                pages.len()
+            ),
+            "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica >>".to_string(),
+        ];
+        for (i, text) in pages.iter().enumerate() {
+            let stream = format!("BT /F1 12 Tf 72 720 Td ({}) Tj ET", text);
+            objects.push(format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 612 792] \
+                 /Resources << /Font << /F1 3 0 R >> >> /Contents {} 0 R >>",
+                page_ids[i] + 1
+            ));
+            objects.push(format!(
+                "<< /Length {} >>\nstream\n{}\nendstream",
+                stream.len(),
+                stream
+            ));
+        }
+
+        let mut bytes = b"%PDF-1.4\n".to_vec();
+        let mut offsets = Vec::new();
+        for (i, object) in objects.iter().enumerate() {
+            offsets.push(bytes.len());
+            bytes.extend(format!("{} 0 obj\n{}\nendobj\n", i + 1, object).into_bytes());
+        }
+        let xref = bytes.len();
+        bytes.extend(format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).into_bytes());
+        for offset in offsets {
+            bytes.extend(format!("{:010} 00000 n \n", offset).into_bytes());
+        }
+        bytes.extend(
+            format!(
+                "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+                objects.len() + 1,
+                xref
+            )
+            .into_bytes(),
+        );
+        bytes
+    }
+
+    #[tokio::test]
+    async fn reads_pdf_pages() {
+        let document = processor(1000, 200)
+            .process(
+                "manual.PDF".to_string(),
+                Some(" Manual ".to_string()),
+                pdf(&["Install the server", "Restart the service"]),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(document.title, "Manual");
+        assert_eq!(document.source_type, SourceType::Pdf);
+        assert_eq!(document.chunks.len(), 2);
+        assert_eq!(document.chunks[1].heading.as_deref(), Some("p. 2"));
+        assert!(document.chunks[1].content.contains("Restart the service"));
+
+        let result = processor(1000, 200)
+            .process("broken.pdf".to_string(), None, b"%PDF-1.4 nope".to_vec())
+            .await;
+        assert!(matches!(result, Err(Error::InvalidBody(_))), "{:?}", result);
+    }
+
+    #[tokio::test]
+    async fn rejects_unknown_and_empty_files() {
+        let processor = processor(1000, 200);
+
+        for (filename, bytes) in [
+            ("photo.png", b"text".to_vec()),
+            ("notes.txt", b"  \n ".to_vec()),
+            ("latin1.txt", vec![0xff, 0xfe]),
+        ] {
+            let result = processor
+                .process(filename.to_string(), Some("Title".to_string()), bytes)
+                .await;
+            assert!(
+                matches!(result, Err(Error::ValidationError(ref errors)) if errors[0].field == "file"),
+                "{}: {:?}",
+                filename,
+                result
+            );
+        }
+    }
+}
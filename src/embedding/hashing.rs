@@ -0,0 +1,75 @@
+use async_trait::async_trait;
+use handle_errors::Error;
+
+use super::{normalize, Embedder};
+
+/// 단어마다 FNV-1a 해시로 차원과 부호를 정해 더한다
+/// 외부 모델 없이 같은 단어를 많이 공유하는 글끼리 가깝게 만든다 (테스트와 오프라인용)
+pub struct HashingEmbedder {
+    dimensions: usize,
+    model: String,
+}
+
+impl HashingEmbedder {
+    pub fn new(dimensions: usize) -> Self {
+        HashingEmbedder {
+            dimensions,
+            model: format!("hashing-v1-{}", dimensions),
+        }
+    }
+
+    pub fn vectorize(&self, text: &str) -> Vec<f32> {
+        let mut vector = vec![0.0; self.dimensions];
+        if self.dimensions == 0 {
+            return vector;
+        }
+        for token in text
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|token| !token.is_empty())
+        {
+            let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+            for byte in token.to_lowercase().bytes() {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(0x0100_0000_01b3);
+            }
+            let sign = if hash >> 63 == 0 { 1.0 } else { -1.0 };
+            vector[(hash % self.dimensions as u64) as usize] += sign;
+        }
+        normalize(vector)
+    }
+}
+
+#[async_trait]
+impl Embedder for HashingEmbedder {
+    async fn embed(&self, texts: Vec<String>) -> Result<Vec<Vec<f32>>, Error> {
+        Ok(texts.iter().map(|text| self.vectorize(text)).collect())
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+}
+
+#[cfg(test)]
+mod hashing_tests {
+    use super::*;
+    use crate::embedding::cosine;
+
+    #[tokio::test]
+    async fn same_words_same_vector() {
+        let embedder = HashingEmbedder::new(64);
+        let vectors = embedder
+            .embed(vec![
+                "Warp filters".to_string(),
+                "filters, WARP!".to_string(),
+                "cooking pasta".to_string(),
+            ])
+            .await
+            .unwrap();
+
+        assert_eq!(vectors[0].len(), 64);
+        assert!((cosine(&vectors[0], &vectors[1]) - 1.0).abs() < 1e-6);
+        assert!(cosine(&vectors[0], &vectors[2]) < 0.5);
+        assert_eq!(embedder.model(), "hashing-v1-64");
+    }
+}
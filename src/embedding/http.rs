@@ -0,0 +1,180 @@
+use async_trait::async_trait;
+use handle_errors::Error;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use super::{normalize, Embedder};
+use crate::config::Config;
+use crate::llm::http;
+
+#[derive(Serialize, Debug)]
+struct EmbeddingRequest<'a> {
+    model: &'a str,
+    input: &'a [String],
+    dimensions: usize,
+}
+
+#[derive(Deserialize, Debug)]
+struct EmbeddingResponse {
+    data: Vec<EmbeddingData>,
+}
+
+#[derive(Deserialize, Debug)]
+struct EmbeddingData {
+    index: usize,
+    embedding: Vec<f32>,
+}
+
+/// OpenAI 호환 `POST /v1/embeddings` 클라이언트
+pub struct HttpEmbedder {
+    client: reqwest::Client,
+    url: String,
+    api_key: String,
+    /// API 에 보내는 모델 이름
+    name: String,
+    dimensions: usize,
+    /// 저장한 벡터에 붙이는 모델 이름 (차원 수를 붙인다)
+    model: String,
+}
+
+impl HttpEmbedder {
+    pub fn new(config: &Config) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_millis(config.embedding_timeout_ms))
+            .build()
+            .unwrap_or_default();
+
+        HttpEmbedder {
+            client,
+            url: config.embedding_url.trim_end_matches('/').to_string(),
+            api_key: config.embedding_api_key.clone(),
+            name: config.embedding_model.clone(),
+            dimensions: config.embedding_dimensions,
+            model: format!("{}-{}", config.embedding_model, config.embedding_dimensions),
+        }
+    }
+}
+
+#[async_trait]
+impl Embedder for HttpEmbedder {
+    async fn embed(&self, texts: Vec<String>) -> Result<Vec<Vec<f32>>, Error> {
+        if texts.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut request = self
+            .client
+            .post(format!("{}/v1/embeddings", self.url))
+            .json(&EmbeddingRequest {
+                model: &self.name,
+                input: &texts,
+                dimensions: self.dimensions,
+            });
+        if !self.api_key.is_empty() {
+            request = request.bearer_auth(&self.api_key);
+        }
+
+        let res = http::send(request).await?;
+
+        let mut data = res
+            .json::<EmbeddingResponse>()
+            .await
+            .map_err(Error::ReqwestAPIError)?
+            .data;
+        if data.len() != texts.len() {
+            return Err(http::invalid(&format!(
+                "embedding API returned {} vectors for {} inputs",
+                data.len(),
+                texts.len()
+            )));
+        }
+        // 응답 순서는 보장되지 않으므로 index 로 맞춘다
+        data.sort_by_key(|d| d.index);
+
+        data.into_iter()
+            .map(|d| {
+                if d.embedding.len() == self.dimensions {
+                    Ok(normalize(d.embedding))
+                } else {
+                    Err(http::invalid(&format!(
+                        "embedding API returned {} dimensions, expected {}",
+                        d.embedding.len(),
+                        self.dimensions
+                    )))
+                }
+            })
+            .collect()
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+}
+
+#[cfg(test)]
+mod http_tests {
+    use super::*;
+    use crate::embedding::cosine;
+    use clap::Parser;
+    use mock_server::{fixtures, Mock, MockResponse, MockServer};
+
+    fn embedder(url: String, dimensions: usize) -> HttpEmbedder {
+        let mut config = Config::parse_from(["test"]);
+        config.embedding_url = url;
+        config.embedding_dimensions = dimensions;
+        HttpEmbedder::new(&config)
+    }
+
+    #[tokio::test]
+    async fn embeds_in_one_request() {
+        let server = MockServer::builder()
+            .mock(fixtures::llm::embeddings(8).expect(1))
+            .start();
+        let embedder = embedder(server.url(), 8);
+
+        let vectors = embedder
+            .embed(vec!["first".to_string(), "second".to_string()])
+            .await
+            .unwrap();
+
+        assert_eq!(vectors.len(), 2);
+        let expected = fixtures::llm::embed("first", 8);
+        assert!((cosine(&vectors[0], &expected) - 1.0).abs() < 1e-5);
+        assert_eq!(embedder.model(), "text-embedding-3-small-8");
+        let body = server.requests()[0].json().unwrap();
+        assert_eq!(body["dimensions"], 8);
+        server.verify();
+    }
+
+    #[tokio::test]
+    async fn rejects_wrong_dimensions() {
+        let server = MockServer::builder()
+            .mock(fixtures::llm::embeddings(4))
+            .start();
+
+        match embedder(server.url(), 8).embed(vec!["x".to_string()]).await {
+            Err(Error::ServerError(e)) => assert!(e.message.contains("4 dimensions")),
+            other => panic!("expected server error, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn reads_the_upstream_error_message() {
+        let server = MockServer::builder()
+            .mock(
+                Mock::post("/v1/embeddings").respond_with(MockResponse::json(
+                    400,
+                    &serde_json::json!({"error": {"message": "input is too long"}}),
+                )),
+            )
+            .start();
+
+        match embedder(server.url(), 8).embed(vec!["x".to_string()]).await {
+            Err(Error::ClientError(e)) => {
+                assert_eq!(e.status, 400);
+                assert_eq!(e.message, "input is too long");
+            }
+            other => panic!("expected client error, got {:?}", other),
+        }
+    }
+}
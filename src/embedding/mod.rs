@@ -0,0 +1,45 @@
+use async_trait::async_trait;
+use handle_errors::Error;
+use std::sync::Arc;
+
+use crate::config::{Config, EmbedderKind};
+
+mod hashing;
+mod http;
+mod worker;
+
+pub use hashing::HashingEmbedder;
+pub use http::HttpEmbedder;
+pub use worker::EmbeddingWorker;
+
+/// 글을 같은 공간의 단위 벡터로 바꾼다
+#[async_trait]
+pub trait Embedder: Send + Sync {
+    /// 결과는 입력 순서대로 글마다 하나씩이다
+    async fn embed(&self, texts: Vec<String>) -> Result<Vec<Vec<f32>>, Error>;
+
+    /// 저장한 벡터가 어느 모델로 만든 것인지 (모델이나 차원이 바뀌면 달라져야 한다)
+    fn model(&self) -> &str;
+}
+
+/// 설정에서 고른 임베더
+pub fn from_config(config: &Config) -> Arc<dyn Embedder> {
+    match config.embedder {
+        EmbedderKind::Http => Arc::new(HttpEmbedder::new(config)),
+        EmbedderKind::Hashing => Arc::new(HashingEmbedder::new(config.embedding_dimensions)),
+    }
+}
+
+/// 두 단위 벡터의 코사인 유사도
+pub fn cosine(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+/// 길이를 1 로 맞춘다 (영벡터는 그대로 둔다)
+pub(crate) fn normalize(mut vector: Vec<f32>) -> Vec<f32> {
+    let norm = vector.iter().map(|v| v * v).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        vector.iter_mut().for_each(|v| *v /= norm);
+    }
+    vector
+}
@@ -0,0 +1,140 @@
+use handle_errors::{APILayerError, Error};
+use std::sync::Arc;
+use tokio::task::JoinHandle;
+use tokio::time::Duration;
+
+use super::Embedder;
+use crate::config::Config;
+use crate::store::Store;
+use crate::types::embedding::EmbeddingJob;
+use crate::vector_store::VectorStore;
+
+/// 새로 쓰였거나 바뀐 질문과 답변의 벡터를 만든다
+/// 글이 바뀌면 updated_at 이 저장된 벡터보다 새로워지므로 다음 주기에 다시 만든다
+pub struct EmbeddingWorker {
+    store: Store,
+    embedder: Arc<dyn Embedder>,
+    vectors: Arc<dyn VectorStore>,
+    batch_size: usize,
+    interval: Duration,
+}
+
+impl EmbeddingWorker {
+    pub fn new(
+        config: &Config,
+        store: Store,
+        embedder: Arc<dyn Embedder>,
+        vectors: Arc<dyn VectorStore>,
+    ) -> Self {
+        EmbeddingWorker {
+            store,
+            embedder,
+            vectors,
+            batch_size: config.embedding_batch_size.max(1),
+            interval: Duration::from_secs(config.embedding_interval_secs),
+        }
+    }
+
+    /// 한 묶음을 처리하고 (만든 벡터 수, 거절당해 건너뛴 글 수) 를 돌려준다
+    pub async fn run_once(&self) -> Result<(usize, usize), Error> {
+        let model = self.embedder.model();
+        let jobs = self
+            .store
+            .get_embedding_jobs(model, self.batch_size as i64)
+            .await?;
+        if jobs.is_empty() {
+            return Ok((0, 0));
+        }
+
+        let texts = jobs.iter().map(|job| job.text.clone()).collect();
+        match self.embedder.embed(texts).await {
+            Ok(vectors) => {
+                let embedded: Vec<_> = jobs.into_iter().zip(vectors).collect();
+                self.vectors.upsert(model, &embedded).await?;
+                Ok((embedded.len(), 0))
+            }
+            // 4xx 는 다시 보내도 똑같으므로 하나씩 보내 어느 글 때문인지 찾는다
+            Err(Error::ClientError(_)) => self.one_by_one(jobs).await,
+            Err(error) => Err(error),
+        }
+    }
+
+    /// 글마다 따로 보내고 거절당한 글은 기록해 다음부터 건너뛴다
+    /// 5xx 나 타임아웃이면 그 앞까지 만든 벡터를 저장하고 멈춘다
+    async fn one_by_one(&self, jobs: Vec<EmbeddingJob>) -> Result<(usize, usize), Error> {
+        let model = self.embedder.model();
+        let mut embedded = Vec::new();
+        let mut failed = 0;
+        let mut stopped = None;
+
+        for job in jobs {
+            match self.embedder.embed(vec![job.text.clone()]).await {
+                Ok(vectors) => match vectors.into_iter().next() {
+                    Some(vector) => embedded.push((job, vector)),
+                    None => {
+                        stopped = Some(Error::ServerError(APILayerError {
+                            status: 502,
+                            message: "embedding API returned no vector".to_string(),
+                        }));
+                        break;
+                    }
+                },
+                Err(Error::ClientError(error)) => {
+                    tracing::event!(
+                        tracing::Level::WARN,
+                        "{} {} can't be embedded: {}",
+                        job.passage.source.as_str(),
+                        job.entity_id(),
+                        error.message
+                    );
+                    self.store
+                        .put_embedding_failure(model, &job, &error.message)
+                        .await?;
+                    failed += 1;
+                }
+                Err(error) => {
+                    stopped = Some(error);
+                    break;
+                }
+            }
+        }
+
+        if !embedded.is_empty() {
+            self.vectors.upsert(model, &embedded).await?;
+        }
+        match stopped {
+            Some(error) => Err(error),
+            None => Ok((embedded.len(), failed)),
+        }
+    }
+
+    /// 밀린 작업이 없을 때까지 처리하고 만든 벡터 수를 돌려준다
+    pub async fn backfill(&self) -> Result<usize, Error> {
+        let mut total = 0;
+        loop {
+            let (embedded, failed) = self.run_once().await?;
+            if embedded + failed == 0 {
+                return Ok(total);
+            }
+            total += embedded;
+            tracing::event!(
+                tracing::Level::DEBUG,
+                "embedded {} passages with {}",
+                total,
+                self.embedder.model()
+            );
+        }
+    }
+
+    /// interval 마다 밀린 작업을 처리한다 (실패하면 다음 주기에 다시 시도한다)
+    pub fn spawn(self) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            loop {
+                if let Err(error) = self.backfill().await {
+                    tracing::event!(tracing::Level::WARN, "embedding worker failed: {}", error);
+                }
+                tokio::time::sleep(self.interval).await;
+            }
+        })
+    }
+}
@@ -1,28 +1,134 @@
 use warp::http::StatusCode;
 
-use crate::profanity::check_profanity;
+use crate::profanity::ProfanityClient;
 use crate::store::Store;
 use crate::types::account::Session;
-use crate::types::answer::NewAnswer;
+use crate::types::answer::{Answer, NewAnswer};
+use crate::validation::Validate;
 
 pub async fn add_answer(
     session: Session,
     store: Store,
+    profanity: ProfanityClient,
     new_answer: NewAnswer,
 ) -> Result<impl warp::Reply, warp::Rejection> {
     let account_id = session.account_id;
-    let content = match check_profanity(new_answer.content).await {
+    new_answer.validate()?;
+
+    let [content] = match profanity
+        .censor_fields([("content", new_answer.content)])
+        .await
+    {
         Ok(res) => res,
         Err(e) => return Err(warp::reject::custom(e)),
     };
 
     let answer = NewAnswer {
-        content,
+        content: content.content,
         question_id: new_answer.question_id,
     };
 
-    match store.add_answer(answer, account_id).await {
+    match store.add_answer(answer, account_id, content.flag).await {
         Ok(_) => Ok(warp::reply::with_status("Answer added", StatusCode::OK)),
         Err(e) => Err(warp::reject::custom(e)),
     }
 }
+
+pub async fn delete_answer(
+    id: i32,
+    session: Session,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let account_id = session.account_id;
+    match store.delete_answer(id, account_id).await {
+        Ok(true) => Ok(warp::reply::with_status(
+            format!("Answer {} deleted", id),
+            StatusCode::OK,
+        )),
+        Ok(false) => {
+            if store.answer_exists(id).await? {
+                Err(warp::reject::custom(handle_errors::Error::Unauthorized))
+            } else {
+                Err(warp::reject::custom(handle_errors::Error::NotFound))
+            }
+        }
+        Err(e) => Err(warp::reject::custom(e)),
+    }
+}
+
+pub async fn restore_answer(
+    id: i32,
+    session: Session,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let account_id = session.account_id;
+    if store.is_answer_owner(id, &account_id).await? || store.is_moderator(&account_id).await? {
+        match store.restore_answer(id).await {
+            Ok(true) => Ok(warp::reply::with_status(
+                format!("Answer {} restored", id),
+                StatusCode::OK,
+            )),
+            Ok(false) => Err(warp::reject::custom(handle_errors::Error::NotFound)),
+            Err(e) => Err(warp::reject::custom(e)),
+        }
+    } else {
+        Err(warp::reject::custom(handle_errors::Error::Unauthorized))
+    }
+}
+
+/// 내 질문에 달린 답변 초안 (받아들이거나 거절하기 전의 것)
+pub async fn get_drafts(
+    session: Session,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match store.get_ai_drafts(&session.account_id).await {
+        Ok(drafts) => Ok(warp::reply::json(&drafts)),
+        Err(e) => Err(warp::reject::custom(e)),
+    }
+}
+
+/// 답변 초안을 공개한다 (질문 작성자만)
+/// 사람이 쓴 답변처럼 비속어 검사를 거치고, 검토 대기 사유가 있으면 검토 대기열로 보낸다
+pub async fn accept_draft(
+    id: i32,
+    session: Session,
+    store: Store,
+    profanity: ProfanityClient,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let draft = owned_draft(id, &session, &store).await?;
+
+    let [content] = match profanity.censor_fields([("content", draft.content)]).await {
+        Ok(res) => res,
+        Err(e) => return Err(warp::reject::custom(e)),
+    };
+
+    match store
+        .accept_ai_draft(&draft.id, content.content, content.flag)
+        .await
+    {
+        Ok(answer) => Ok(warp::reply::json(&answer)),
+        Err(e) => Err(warp::reject::custom(e)),
+    }
+}
+
+/// 답변 초안을 거절한다 (질문 작성자만, 거절한 초안은 다시 만들지 않는다)
+pub async fn dismiss_draft(
+    id: i32,
+    session: Session,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let draft = owned_draft(id, &session, &store).await?;
+
+    match store.dismiss_ai_draft(&draft.id).await {
+        Ok(answer) => Ok(warp::reply::json(&answer)),
+        Err(e) => Err(warp::reject::custom(e)),
+    }
+}
+
+async fn owned_draft(id: i32, session: &Session, store: &Store) -> Result<Answer, warp::Rejection> {
+    let (draft, owner) = store.get_ai_draft(id).await?;
+    if owner != session.account_id {
+        return Err(warp::reject::custom(handle_errors::Error::Unauthorized));
+    }
+    Ok(draft)
+}
@@ -1,16 +1,18 @@
-use std::{env, future};
+use std::future;
 
 use chrono::prelude::*;
 
 use argon2::{self, Config};
 use rand::Rng;
-use warp::http::StatusCode;
 use warp::Filter;
 
 use crate::store::Store;
 use crate::types::account::{Account, AccountId, Session};
+use crate::validation::Validate;
 
 pub async fn register(store: Store, account: Account) -> Result<impl warp::Reply, warp::Rejection> {
+    account.validate()?;
+
     let hashed_password = hash_password(account.password.as_bytes());
 
     let account = Account {
@@ -31,12 +33,27 @@ pub fn hash_password(password: &[u8]) -> String {
     argon2::hash_encoded(password, &salt, &config).unwrap()
 }
 
-pub async fn login(store: Store, login: Account) -> Result<impl warp::Reply, warp::Rejection> {
+/// 토큰을 만들고 검증하는 PASETO 키
+#[derive(Clone)]
+pub struct TokenKey(String);
+
+impl TokenKey {
+    pub fn new(key: &str) -> Self {
+        TokenKey(key.to_string())
+    }
+}
+
+pub async fn login(
+    store: Store,
+    key: TokenKey,
+    login: Account,
+) -> Result<impl warp::Reply, warp::Rejection> {
     match store.get_account(login.email).await {
         Ok(account) => match verify_password(&account.password, login.password.as_bytes()) {
             Ok(verified) => {
                 if verified {
                     Ok(warp::reply::json(&issue_token(
+                        &key,
                         account.id.expect("id not found"),
                     )))
                 } else {
@@ -51,15 +68,17 @@ pub async fn login(store: Store, login: Account) -> Result<impl warp::Reply, war
     }
 }
 
-pub fn verify_token(token: String) -> Result<Session, handle_errors::Error> {
-    let key = env::var("PASETO_KEY").unwrap();
+pub fn verify_token(key: &TokenKey, token: String) -> Result<Session, handle_errors::Error> {
     let token = paseto::tokens::validate_local_token(
         &token,
         None,
-        key.as_bytes(),
+        key.0.as_bytes(),
         &paseto::tokens::TimeBackend::Chrono,
     )
-    .map_err(|_| handle_errors::Error::CannotDecryptToken)?;
+    .map_err(|e| match e.downcast_ref::<paseto::errors::GenericError>() {
+        Some(paseto::errors::GenericError::ExpiredToken {}) => handle_errors::Error::TokenExpired,
+        _ => handle_errors::Error::CannotDecryptToken,
+    })?;
 
     serde_json::from_value::<Session>(token).map_err(|_| handle_errors::Error::CannotDecryptToken)
 }
@@ -68,14 +87,12 @@ fn verify_password(hash: &str, password: &[u8]) -> Result<bool, argon2::Error> {
     argon2::verify_encoded(hash, password)
 }
 
-fn issue_token(account_id: AccountId) -> String {
-    let key = env::var("PASETO_KEY").unwrap();
-
+fn issue_token(key: &TokenKey, account_id: AccountId) -> String {
     let current_date_time = Utc::now();
     let dt = current_date_time + chrono::Duration::days(1);
 
     paseto::tokens::PasetoBuilder::new()
-        .set_encryption_key(&Vec::from(key.as_bytes()))
+        .set_encryption_key(&Vec::from(key.0.as_bytes()))
         .set_expiration(&dt)
         .set_not_before(&Utc::now())
         .set_claim("account_id", serde_json::json!(account_id))
@@ -83,31 +100,40 @@ fn issue_token(account_id: AccountId) -> String {
         .expect("Failed to construct paseto token w/ builder")
 }
 
-pub fn auth() -> impl Filter<Extract = (Session,), Error = warp::Rejection> + Clone {
-    warp::header::<String>("Authorization").and_then(|token: String| {
-        let token = match verify_token(token) {
+pub fn auth(key: TokenKey) -> impl Filter<Extract = (Session,), Error = warp::Rejection> + Clone {
+    warp::header::<String>("Authorization").and_then(move |token: String| {
+        let token = match verify_token(&key, token) {
             Ok(t) => t,
-            Err(_) => {
-                return future::ready(Err(warp::reject::custom(
-                    handle_errors::Error::Unauthorized,
-                )))
-            }
+            Err(e) => return future::ready(Err(warp::reject::custom(e))),
         };
 
         future::ready(Ok(token))
     })
 }
 
+/// 로그인하지 않아도 되는 경로에서 쓴다 (토큰이 있으면 검증한다)
+pub fn optional_auth(
+    key: TokenKey,
+) -> impl Filter<Extract = (Option<Session>,), Error = warp::Rejection> + Clone {
+    warp::header::optional::<String>("Authorization").and_then(move |token: Option<String>| {
+        let session = token.map(|token| verify_token(&key, token)).transpose();
+        future::ready(match session {
+            Ok(session) => Ok(session),
+            Err(e) => Err(warp::reject::custom(e)),
+        })
+    })
+}
+
 #[cfg(test)]
 mod authentication_tests {
-    use super::{auth, env, issue_token, AccountId};
+    use super::{auth, issue_token, AccountId, TokenKey};
 
     #[tokio::test]
     async fn post_questions_auth() {
-        env::set_var("PASETO_KEY", "RANDOM WORDS WINTER MACINTOSH PC");
-        let token = issue_token(AccountId(3));
+        let key = TokenKey::new("RANDOM WORDS WINTER MACINTOSH PC");
+        let token = issue_token(&key, AccountId(3));
 
-        let filter = auth();
+        let filter = auth(key);
 
         let res = warp::test::request()
             .header("Authorization", token)
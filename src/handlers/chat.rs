@@ -0,0 +1,276 @@
+use futures_util::{stream, SinkExt, StreamExt};
+use handle_errors::Error;
+use std::collections::VecDeque;
+use std::convert::Infallible;
+use tokio::sync::mpsc;
+use warp::reply::Reply;
+use warp::ws::{Message, WebSocket};
+
+use crate::rag::{Grounding, History, RagPipeline};
+use crate::store::Store;
+use crate::types::account::{AccountId, Session};
+use crate::types::chat::{ChatEvent, ChatRequest, ChatResponse};
+use crate::types::conversation::ConversationId;
+use crate::validation::Validate;
+
+/// 새 대화의 제목으로 쓸 첫 질문의 글자 수
+const TITLE_CHARS: usize = 80;
+
+/// 로그인한 사용자만 쓸 수 있다 (LLM 호출 비용 때문에)
+/// 질문과 답은 대화에 저장하고, conversation_id 가 없으면 답을 저장할 때 새 대화를 만든다
+/// `Accept: text/event-stream` 이면 답을 SSE 로 조각조각 보낸다
+pub async fn chat(
+    session: Session,
+    accept: Option<String>,
+    store: Store,
+    rag: RagPipeline,
+    request: ChatRequest,
+) -> Result<warp::reply::Response, warp::Rejection> {
+    let account_id = session.account_id;
+    tracing::event!(
+        tracing::Level::INFO,
+        "chat request from account {}",
+        account_id.0
+    );
+    let (conversation_id, history) = prepare(&account_id, &store, &rag, &request).await?;
+
+    if accept.is_some_and(|accept| accept.contains("text/event-stream")) {
+        let grounding = rag
+            .ground(&request.question, &history, request.top_k, &request.filter)
+            .await?;
+        let events = stream_answer(
+            store,
+            rag,
+            account_id,
+            conversation_id,
+            request.question,
+            grounding,
+        );
+        let events = stream::unfold(events, |mut events| async move {
+            let event = events.recv().await?;
+            let sse = warp::sse::Event::default()
+                .event(event.name())
+                .json_data(&event)
+                .unwrap_or_default();
+            Some((Ok::<_, Infallible>(sse), events))
+        });
+        return Ok(warp::sse::reply(warp::sse::keep_alive().stream(events)).into_response());
+    }
+
+    let mut res = rag
+        .answer(&request.question, &history, request.top_k, &request.filter)
+        .await?;
+    let conversation_id = store
+        .add_exchange(
+            conversation_id.as_ref(),
+            &account_id,
+            &title(&request.question),
+            &request.question,
+            &res,
+        )
+        .await?;
+    res.conversation_id = Some(conversation_id);
+
+    Ok(warp::reply::json(&res).into_response())
+}
+
+/// 한 연결에서 ChatRequest JSON 을 받고 ChatEvent JSON 으로 답한다
+/// conversation_id 를 빼면 이 연결에서 마지막으로 답한 대화를 이어 간다
+/// 답하는 동안 온 질문은 차례로 처리하고, 연결이 끊기면 만들던 답을 멈춘다
+pub async fn chat_socket(socket: WebSocket, session: Session, store: Store, rag: RagPipeline) {
+    let account_id = session.account_id;
+    let (mut sink, mut incoming) = socket.split();
+    let mut pending = VecDeque::new();
+    let mut conversation_id = None;
+
+    loop {
+        let message = match pending.pop_front() {
+            Some(message) => message,
+            None => match incoming.next().await {
+                Some(Ok(message)) => message,
+                _ => return,
+            },
+        };
+        if message.is_close() {
+            return;
+        }
+        let text = match message.to_str() {
+            Ok(text) => text,
+            // ping, pong, binary
+            Err(_) => continue,
+        };
+
+        let mut events = match start(text, &conversation_id, &account_id, &store, &rag).await {
+            Ok(events) => events,
+            Err(e) => {
+                if send(&mut sink, &ChatEvent::error(&e)).await.is_err() {
+                    return;
+                }
+                continue;
+            }
+        };
+
+        loop {
+            tokio::select! {
+                event = events.recv() => {
+                    let event = match event {
+                        Some(event) => event,
+                        None => break,
+                    };
+                    if let ChatEvent::Done(res) = &event {
+                        conversation_id = res.conversation_id.clone();
+                    }
+                    if send(&mut sink, &event).await.is_err() {
+                        return;
+                    }
+                    if !matches!(event, ChatEvent::Token { .. }) {
+                        break;
+                    }
+                }
+                message = incoming.next() => match message {
+                    Some(Ok(message)) if !message.is_close() => pending.push_back(message),
+                    // events 를 버리면 답을 만들던 태스크도 멈춘다
+                    _ => return,
+                },
+            }
+        }
+    }
+}
+
+/// 소켓으로 받은 질문 하나에 답하기 시작한다
+async fn start(
+    text: &str,
+    last: &Option<ConversationId>,
+    account_id: &AccountId,
+    store: &Store,
+    rag: &RagPipeline,
+) -> Result<mpsc::Receiver<ChatEvent>, Error> {
+    let mut request: ChatRequest =
+        serde_json::from_str(text).map_err(|error| Error::InvalidBody(error.to_string()))?;
+    if request.conversation_id.is_none() {
+        request.conversation_id = last.clone();
+    }
+
+    let (conversation_id, history) = prepare(account_id, store, rag, &request).await?;
+    let grounding = rag
+        .ground(&request.question, &history, request.top_k, &request.filter)
+        .await?;
+    Ok(stream_answer(
+        store.clone(),
+        rag.clone(),
+        account_id.clone(),
+        conversation_id,
+        request.question,
+        grounding,
+    ))
+}
+
+/// 질문을 검증하고 이어 갈 대화와 프롬프트에 넣을 지난 대화를 고른다
+/// 새 대화는 아직 만들지 않으므로 None 이다
+async fn prepare(
+    account_id: &AccountId,
+    store: &Store,
+    rag: &RagPipeline,
+    request: &ChatRequest,
+) -> Result<(Option<ConversationId>, History), Error> {
+    request.validate()?;
+
+    let conversation = match &request.conversation_id {
+        Some(id) => store.get_conversation(id.0, account_id).await?,
+        None => return Ok((None, History::default())),
+    };
+    let messages = store
+        .get_recent_messages(
+            &conversation.id,
+            conversation.summarized_until.as_ref(),
+            rag.history_chars() as i64,
+        )
+        .await?;
+    let (history, summary) = rag.history(&conversation, &messages).await?;
+    if let Some((summary, until)) = summary {
+        store
+            .update_summary(&conversation.id, &summary, &until)
+            .await?;
+    }
+
+    Ok((Some(conversation.id), history))
+}
+
+/// 새 대화의 제목 (첫 질문의 앞부분)
+fn title(question: &str) -> String {
+    question.trim().chars().take(TITLE_CHARS).collect()
+}
+
+/// 모델의 답을 조각마다 보내고, 다 받으면 대화에 저장한 뒤 Done 을 보낸다
+/// 받는 쪽을 버리면(클라이언트가 끊으면) 모델 호출을 멈추고 저장하지 않는다
+fn stream_answer(
+    store: Store,
+    rag: RagPipeline,
+    account_id: AccountId,
+    conversation_id: Option<ConversationId>,
+    question: String,
+    grounding: Grounding,
+) -> mpsc::Receiver<ChatEvent> {
+    let (tx, rx) = mpsc::channel(32);
+
+    tokio::spawn(async move {
+        let id = conversation_id.as_ref().map(|id| id.0);
+        tokio::select! {
+            _ = tx.closed() => {
+                tracing::event!(tracing::Level::INFO, conversation_id = id, "chat stream cancelled");
+            }
+            res = relay(&tx, &store, &rag, &account_id, conversation_id, &question, grounding) => {
+                let event = match res {
+                    Ok(res) => ChatEvent::Done(res),
+                    Err(e) => {
+                        tracing::event!(tracing::Level::ERROR, "{:?}", e);
+                        ChatEvent::error(&e)
+                    }
+                };
+                let _ = tx.send(event).await;
+            }
+        }
+    });
+
+    rx
+}
+
+async fn relay(
+    tx: &mpsc::Sender<ChatEvent>,
+    store: &Store,
+    rag: &RagPipeline,
+    account_id: &AccountId,
+    conversation_id: Option<ConversationId>,
+    question: &str,
+    grounding: Grounding,
+) -> Result<ChatResponse, Error> {
+    let mut tokens = rag.stream(&grounding).await?;
+    let mut answer = String::new();
+    while let Some(token) = tokens.next().await {
+        let token = token?;
+        answer.push_str(&token);
+        // 실패하면 받는 쪽이 끊긴 것이고 select 의 closed 가 멈춘다
+        let _ = tx.send(ChatEvent::Token { text: token }).await;
+    }
+
+    let mut res = grounding.respond(answer);
+    let conversation_id = store
+        .add_exchange(
+            conversation_id.as_ref(),
+            account_id,
+            &title(question),
+            question,
+            &res,
+        )
+        .await?;
+    res.conversation_id = Some(conversation_id);
+    Ok(res)
+}
+
+async fn send<S>(sink: &mut S, event: &ChatEvent) -> Result<(), warp::Error>
+where
+    S: SinkExt<Message, Error = warp::Error> + Unpin,
+{
+    let text = serde_json::to_string(event).unwrap_or_default();
+    sink.send(Message::text(text)).await
+}
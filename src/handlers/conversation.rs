@@ -0,0 +1,60 @@
+use std::collections::HashMap;
+use warp::http::StatusCode;
+
+use crate::store::Store;
+use crate::types::account::Session;
+use crate::types::conversation::ConversationDetail;
+use crate::types::pagination::{extract_pagination, Pagination};
+
+/// 내 대화 목록 (최근에 이어간 것부터)
+pub async fn get_conversations(
+    params: HashMap<String, String>,
+    session: Session,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let pagination = if params.is_empty() {
+        Pagination::default()
+    } else {
+        extract_pagination(params)?
+    };
+
+    match store
+        .get_conversations(&session.account_id, pagination.limit, pagination.offset)
+        .await
+    {
+        Ok(conversations) => Ok(warp::reply::json(&conversations)),
+        Err(e) => Err(warp::reject::custom(e)),
+    }
+}
+
+/// 다른 사람의 대화는 없는 대화처럼 404 다
+pub async fn get_conversation(
+    id: i32,
+    session: Session,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let conversation = store.get_conversation(id, &session.account_id).await?;
+
+    match store.get_messages(&conversation.id).await {
+        Ok(messages) => Ok(warp::reply::json(&ConversationDetail {
+            conversation,
+            messages,
+        })),
+        Err(e) => Err(warp::reject::custom(e)),
+    }
+}
+
+pub async fn delete_conversation(
+    id: i32,
+    session: Session,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match store.delete_conversation(id, &session.account_id).await {
+        Ok(true) => Ok(warp::reply::with_status(
+            format!("Conversation {} deleted", id),
+            StatusCode::OK,
+        )),
+        Ok(false) => Err(warp::reject::custom(handle_errors::Error::NotFound)),
+        Err(e) => Err(warp::reject::custom(e)),
+    }
+}
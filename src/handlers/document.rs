@@ -0,0 +1,92 @@
+use futures_util::TryStreamExt;
+use handle_errors::{Error, FieldError};
+use warp::http::StatusCode;
+use warp::hyper::body::Buf;
+use warp::multipart::{FormData, Part};
+
+use crate::documents::DocumentProcessor;
+use crate::store::Store;
+use crate::types::account::Session;
+use crate::validation::Validate;
+
+/// multipart 본문의 `file` 파트를 조각으로 나눠 저장한다 (`title` 파트는 골라 쓴다)
+/// 챗봇이 근거로 쓰는 글이므로 모더레이터만 올릴 수 있다
+pub async fn add_document(
+    session: Session,
+    store: Store,
+    processor: DocumentProcessor,
+    form: FormData,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let account_id = session.account_id;
+    if !store.is_moderator(&account_id).await? {
+        return Err(warp::reject::custom(Error::Unauthorized));
+    }
+
+    let mut file = None;
+    let mut title = None;
+    let mut parts = form;
+    while let Some(part) = parts
+        .try_next()
+        .await
+        .map_err(|error| Error::InvalidBody(error.to_string()))?
+    {
+        match part.name() {
+            "file" => {
+                let filename = part.filename().unwrap_or_default().to_string();
+                file = Some((filename, read(part).await?));
+            }
+            "title" => {
+                let bytes = read(part).await?;
+                title =
+                    Some(String::from_utf8(bytes).map_err(|_| {
+                        Error::InvalidBody("title must be UTF-8 encoded".to_string())
+                    })?);
+            }
+            _ => {}
+        }
+    }
+
+    let (filename, bytes) = file.ok_or_else(|| {
+        Error::ValidationError(vec![FieldError {
+            field: "file".to_string(),
+            message: "must not be empty".to_string(),
+        }])
+    })?;
+    let document = processor.process(filename, title, bytes).await?;
+    document.validate()?;
+
+    match store.add_document(document, Some(account_id)).await {
+        Ok(document) => Ok(warp::reply::json(&document)),
+        Err(e) => Err(warp::reject::custom(e)),
+    }
+}
+
+pub async fn delete_document(
+    id: i32,
+    session: Session,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    if !store.is_moderator(&session.account_id).await? {
+        return Err(warp::reject::custom(Error::Unauthorized));
+    }
+
+    match store.delete_document(id).await {
+        Ok(true) => Ok(warp::reply::with_status(
+            format!("Document {} deleted", id),
+            StatusCode::OK,
+        )),
+        Ok(false) => Err(warp::reject::custom(Error::NotFound)),
+        Err(e) => Err(warp::reject::custom(e)),
+    }
+}
+
+/// 파트 본문을 모두 읽는다 (전체 크기는 라우트의 max_length 가 막는다)
+async fn read(part: Part) -> Result<Vec<u8>, Error> {
+    part.stream()
+        .try_fold(Vec::new(), |mut bytes, buf| async move {
+            bytes.extend_from_slice(buf.chunk());
+            Ok(bytes)
+        })
+        .await
+        .map_err(|error| Error::InvalidBody(error.to_string()))
+}
@@ -1,3 +1,7 @@
 pub mod answer;
 pub mod authentication;
+pub mod chat;
+pub mod conversation;
+pub mod document;
+pub mod moderation;
 pub mod question;
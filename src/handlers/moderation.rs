@@ -0,0 +1,63 @@
+use warp::http::StatusCode;
+
+use crate::store::Store;
+use crate::types::account::Session;
+use crate::types::moderation::Decision;
+use crate::validation::Validate;
+
+pub async fn get_queue(
+    session: Session,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    if !store.is_moderator(&session.account_id).await? {
+        return Err(warp::reject::custom(handle_errors::Error::Unauthorized));
+    }
+
+    match store.get_moderation_queue().await {
+        Ok(items) => Ok(warp::reply::json(&items)),
+        Err(e) => Err(warp::reject::custom(e)),
+    }
+}
+
+pub async fn decide(
+    id: i32,
+    session: Session,
+    store: Store,
+    decision: Decision,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let account_id = session.account_id;
+    if !store.is_moderator(&account_id).await? {
+        return Err(warp::reject::custom(handle_errors::Error::Unauthorized));
+    }
+
+    let item = store.get_queue_item(id).await?;
+    decision.validate()?;
+    // 답변에는 제목이 없다
+    if let (None, Decision::Edit { title: Some(_), .. }) = (&item.question_id, &decision) {
+        return Err(warp::reject::custom(handle_errors::Error::ValidationError(
+            vec![handle_errors::FieldError {
+                field: "title".to_string(),
+                message: "answers have no title".to_string(),
+            }],
+        )));
+    }
+
+    let outcome = decision.outcome();
+    match store.decide(id, account_id, decision).await {
+        Ok(_) => Ok(warp::reply::with_status(
+            format!("Queue item {} {}", id, outcome),
+            StatusCode::OK,
+        )),
+        Err(e) => Err(warp::reject::custom(e)),
+    }
+}
+
+pub async fn get_notifications(
+    session: Session,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match store.get_notifications(&session.account_id).await {
+        Ok(notifications) => Ok(warp::reply::json(&notifications)),
+        Err(e) => Err(warp::reject::custom(e)),
+    }
+}
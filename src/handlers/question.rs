@@ -1,40 +1,26 @@
-use serde::{Deserialize, Serialize};
+use serde::Serialize;
 use std::collections::HashMap;
 use tracing::{event, info, instrument, Level};
 use warp::http::StatusCode;
+use warp::hyper::body::Bytes;
+use warp::{Filter, Reply};
 
-use crate::profanity::check_profanity;
+use crate::profanity::{Censored, ProfanityClient};
+use crate::rag::RagPipeline;
 use crate::store::Store;
 use crate::types::account::Session;
+use crate::types::etag;
+use crate::types::moderation::{Flag, ModerationStatus};
 use crate::types::pagination::{extract_pagination, Pagination};
-use crate::types::question::{NewQuestion, Question};
-
-#[derive(Deserialize, Serialize, Debug, Clone)]
-pub struct APIResponse {
-    message: String,
-}
-
-#[derive(Deserialize, Serialize, Debug, Clone)]
-struct BadWord {
-    original: String,
-    word: String,
-    deviations: i64,
-    info: i64,
-    #[serde(rename = "replacedLen")]
-    replaced_len: i64,
-}
-
-#[derive(Deserialize, Serialize, Debug, Clone)]
-struct BadWordsResponse {
-    content: String,
-    bad_words_total: i64,
-    bad_words_list: Vec<BadWord>,
-    censored_content: String,
-}
+use crate::types::question::{
+    AddQuestionParams, NewQuestion, Question, QuestionDraft, QuestionPatch,
+};
+use crate::validation::Validate;
 
 #[instrument]
 pub async fn get_questions(
     params: HashMap<String, String>,
+    if_none_match: Option<String>,
     store: Store,
 ) -> Result<impl warp::Reply, warp::Rejection> {
     event!(target: "practical_rust_book", Level::INFO, "querying questions");
@@ -49,41 +35,139 @@ pub async fn get_questions(
         .get_questions(pagination.limit, pagination.offset)
         .await
     {
-        Ok(res) => Ok(warp::reply::json(&res)),
+        Ok(res) => Ok(conditional_reply(
+            &res,
+            etag::from_questions(&res),
+            &if_none_match,
+        )),
         Err(e) => Err(warp::reject::custom(e)),
     }
 }
 
+/// 검토 대기 중이거나 거절된 질문은 목록과 마찬가지로 작성자와 모더레이터에게만 보인다
+pub async fn get_question(
+    id: i32,
+    if_none_match: Option<String>,
+    session: Option<Session>,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let question = store.get_question(id).await?;
+    if question.status != ModerationStatus::Published
+        && !can_see_hidden(id, &session, &store).await?
+    {
+        return Err(warp::reject::custom(handle_errors::Error::NotFound));
+    }
+
+    Ok(conditional_reply(
+        &question,
+        etag::from_version(question.version),
+        &if_none_match,
+    ))
+}
+
+/// 공개하지 않은 질문(삭제, 검토 대기, 거절)을 볼 수 있는 작성자나 모더레이터인가
+async fn can_see_hidden(
+    id: i32,
+    session: &Option<Session>,
+    store: &Store,
+) -> Result<bool, handle_errors::Error> {
+    match session {
+        Some(session) => Ok(store.is_question_owner(id, &session.account_id).await?
+            || store.is_moderator(&session.account_id).await?),
+        None => Ok(false),
+    }
+}
+
+/// If-None-Match 가 현재 ETag 와 같으면 본문 없이 304 를 돌려준다
+fn conditional_reply<T: Serialize>(
+    body: &T,
+    etag: String,
+    if_none_match: &Option<String>,
+) -> warp::reply::Response {
+    if etag::matches_none_match(if_none_match, &etag) {
+        warp::reply::with_header(
+            warp::reply::with_status(warp::reply(), StatusCode::NOT_MODIFIED),
+            "ETag",
+            etag,
+        )
+        .into_response()
+    } else {
+        warp::reply::with_header(warp::reply::json(body), "ETag", etag).into_response()
+    }
+}
+
 pub async fn add_question(
+    params: AddQuestionParams,
     session: Session,
     store: Store,
+    profanity: ProfanityClient,
+    rag: RagPipeline,
     new_question: NewQuestion,
 ) -> Result<impl warp::Reply, warp::Rejection> {
     let account_id = session.account_id;
-    let title = match check_profanity(new_question.title).await {
-        Ok(res) => res,
-        Err(e) => return Err(warp::reject::custom(e)),
-    };
+    new_question.validate()?;
 
-    let content = match check_profanity(new_question.content).await {
+    if !params.force {
+        // 중복 확인은 도움말일 뿐이라 검색이 실패해도 질문은 올린다
+        match rag
+            .possible_duplicates(&new_question.title, &new_question.content)
+            .await
+        {
+            Ok(duplicates) if !duplicates.is_empty() => {
+                return Err(warp::reject::custom(
+                    handle_errors::Error::PossibleDuplicate(
+                        duplicates.into_iter().map(Into::into).collect(),
+                    ),
+                ));
+            }
+            Ok(_) => {}
+            Err(e) => event!(Level::WARN, "duplicate check failed: {}", e),
+        }
+    }
+
+    let [title, content] = match profanity
+        .censor_fields([
+            ("title", new_question.title),
+            ("content", new_question.content),
+        ])
+        .await
+    {
         Ok(res) => res,
         Err(e) => return Err(warp::reject::custom(e)),
     };
 
+    let flag = Flag::merge([title.flag, content.flag]);
     let question = NewQuestion {
-        title: title,
-        content,
+        title: title.content,
+        content: content.content,
         tags: new_question.tags,
     };
 
-    match store.add_question(question, account_id).await {
+    match store.add_question(question, account_id, flag).await {
         Ok(question) => Ok(warp::reply::json(&question)),
         Err(e) => Err(warp::reject::custom(e)),
     }
 }
 
+/// 쓰고 있는 질문과 비슷한 질문 (올리기 전에 이미 있는 질문을 찾아보게 한다)
+pub async fn similar_questions(
+    _session: Session,
+    rag: RagPipeline,
+    draft: QuestionDraft,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    draft.validate()?;
+
+    match rag
+        .similar_questions(&draft.title, &draft.content, draft.limit)
+        .await
+    {
+        Ok(similar) => Ok(warp::reply::json(&similar)),
+        Err(e) => Err(warp::reject::custom(e)),
+    }
+}
+
 // tokio spawn 버전
-/**
+/*
 pub async fn update_question(
     id: i32,
     store: Store,
@@ -113,55 +197,213 @@ pub async fn update_question(
         Err(e) => Err(warp::reject::custom(e)),
     }
 }
-**/
+*/
 
-// tokio::join 버전
 pub async fn update_question(
     id: i32,
     session: Session,
     store: Store,
+    profanity: ProfanityClient,
+    if_match: Option<String>,
     question: Question,
 ) -> Result<impl warp::Reply, warp::Rejection> {
     let account_id = session.account_id;
+    let expected_version = etag::extract_if_match(if_match)?;
+    question.validate()?;
     if store.is_question_owner(id, &account_id).await? {
-        let title = check_profanity(question.title);
-        let content = check_profanity(question.content);
-        let (title, content) = tokio::join!(title, content);
-
-        if title.is_ok() && content.is_ok() {
-            let question = Question {
-                id: question.id,
-                title: title.unwrap(),
-                content: content.unwrap(),
-                tags: question.tags,
-            };
-
-            match store.update_question(question, id, account_id).await {
-                Ok(res) => Ok(warp::reply::json(&res)),
-                Err(e) => Err(warp::reject::custom(e)),
+        // 어차피 거절될 수정이라면 비속어 검사 API 를 부르지 않는다
+        if let Some(version) = expected_version {
+            if store.get_question(id).await?.version != version {
+                return Err(warp::reject::custom(
+                    handle_errors::Error::PreconditionFailed,
+                ));
+            }
+        }
+
+        let censored = profanity
+            .censor_fields([("title", question.title), ("content", question.content)])
+            .await;
+
+        match censored {
+            Ok([title, content]) => {
+                let flag = Flag::merge([title.flag, content.flag]);
+                let question = Question {
+                    id: question.id,
+                    title: title.content,
+                    content: content.content,
+                    tags: question.tags,
+                    version: question.version,
+                    status: ModerationStatus::from_flag(&flag),
+                };
+
+                match store
+                    .update_question(question, id, account_id, expected_version, flag)
+                    .await
+                {
+                    Ok(res) => Ok(warp::reply::with_header(
+                        warp::reply::json(&res),
+                        "ETag",
+                        etag::from_version(res.version),
+                    )),
+                    Err(e) => Err(warp::reject::custom(e)),
+                }
             }
-        } else {
-            Err(warp::reject::custom(
-                title.expect_err("Expected API call to have failed here"),
-            ))
+            Err(e) => Err(warp::reject::custom(e)),
         }
     } else {
         Err(warp::reject::custom(handle_errors::Error::Unauthorized))
     }
 }
 
+pub async fn patch_question(
+    id: i32,
+    session: Session,
+    store: Store,
+    profanity: ProfanityClient,
+    if_match: Option<String>,
+    patch: QuestionPatch,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let account_id = session.account_id;
+    let expected_version = match if_match {
+        Some(_) => etag::extract_if_match(if_match)?,
+        None => None,
+    };
+    patch.validate()?;
+
+    if !store.is_question_owner(id, &account_id).await? {
+        return Err(warp::reject::custom(handle_errors::Error::Unauthorized));
+    }
+
+    let current = store.get_question(id).await?;
+    if matches!(expected_version, Some(version) if version != current.version) {
+        return Err(warp::reject::custom(
+            handle_errors::Error::PreconditionFailed,
+        ));
+    }
+    // If-Match 가 없더라도 읽은 시점의 버전으로 수정해서 그 사이의 변경을 덮어쓰지 않는다
+    let expected_version = expected_version.or(Some(current.version));
+
+    // 바뀐 텍스트만 비속어 검사를 한다
+    let new_title = patch.title.filter(|title| *title != current.title);
+    let new_content = patch.content.filter(|content| *content != current.content);
+    let (title, content) = match (new_title, new_content) {
+        (Some(title), Some(content)) => {
+            let [title, content] = profanity
+                .censor_fields([("title", title), ("content", content)])
+                .await?;
+            (title, content)
+        }
+        (Some(title), None) => {
+            let [title] = profanity.censor_fields([("title", title)]).await?;
+            (title, unchanged(current.content))
+        }
+        (None, Some(content)) => {
+            let [content] = profanity.censor_fields([("content", content)]).await?;
+            (unchanged(current.title), content)
+        }
+        (None, None) => (unchanged(current.title), unchanged(current.content)),
+    };
+
+    let flag = Flag::merge([title.flag, content.flag]);
+    let question = Question {
+        id: current.id,
+        title: title.content,
+        content: content.content,
+        tags: patch.tags.unwrap_or(current.tags),
+        version: current.version,
+        status: ModerationStatus::from_flag(&flag),
+    };
+
+    match store
+        .update_question(question, id, account_id, expected_version, flag)
+        .await
+    {
+        Ok(res) => Ok(warp::reply::with_header(
+            warp::reply::json(&res),
+            "ETag",
+            etag::from_version(res.version),
+        )),
+        Err(e) => Err(warp::reject::custom(e)),
+    }
+}
+
+/// `application/merge-patch+json` 본문을 읽는다
+/// warp::body::json() 은 `application/json` 만 받으므로 직접 역직렬화한다
+pub fn merge_patch() -> impl Filter<Extract = (QuestionPatch,), Error = warp::Rejection> + Clone {
+    warp::body::bytes().and_then(|body: Bytes| async move {
+        serde_json::from_slice::<QuestionPatch>(&body)
+            .map_err(|e| warp::reject::custom(handle_errors::Error::InvalidBody(e.to_string())))
+    })
+}
+
+/// 검사하지 않은 그대로의 텍스트
+fn unchanged(content: String) -> Censored {
+    Censored {
+        content,
+        bad_words: Vec::new(),
+        flag: None,
+    }
+}
+
 pub async fn delete_question(
     id: i32,
     session: Session,
     store: Store,
 ) -> Result<impl warp::Reply, warp::Rejection> {
     let account_id = session.account_id;
-    if store.is_question_owner(id, &account_id).await? {
-        match store.delete_question(id, account_id).await {
-            Ok(_) => Ok(warp::reply::with_status(
-                format!("Question {} deleted", id),
+    match store.delete_question(id, account_id).await {
+        Ok(true) => Ok(warp::reply::with_status(
+            format!("Question {} deleted", id),
+            StatusCode::OK,
+        )),
+        // 아무것도 삭제되지 않았다면 질문이 없거나 다른 사람의 질문이다
+        Ok(false) => {
+            if store.question_exists(id).await? {
+                Err(warp::reject::custom(handle_errors::Error::Unauthorized))
+            } else {
+                Err(warp::reject::custom(handle_errors::Error::NotFound))
+            }
+        }
+        Err(e) => Err(warp::reject::custom(e)),
+    }
+}
+
+/// 삭제했거나 공개하지 않은 질문의 이력은 작성자와 모더레이터만 볼 수 있다
+/// 검토를 기다리거나 거절된 수정도 작성자와 모더레이터에게만 보인다
+pub async fn get_question_revisions(
+    id: i32,
+    session: Option<Session>,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let privileged = can_see_hidden(id, &session, &store).await?;
+    let visible = match store.get_question_state(id).await? {
+        None => false,
+        Some((false, ModerationStatus::Published)) => true,
+        Some(_) => privileged,
+    };
+    if !visible {
+        return Err(warp::reject::custom(handle_errors::Error::NotFound));
+    }
+
+    match store.get_question_revisions(id, privileged).await {
+        Ok(res) => Ok(warp::reply::json(&res)),
+        Err(e) => Err(warp::reject::custom(e)),
+    }
+}
+
+pub async fn restore_question(
+    id: i32,
+    session: Session,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let account_id = session.account_id;
+    if store.is_question_owner(id, &account_id).await? || store.is_moderator(&account_id).await? {
+        match store.restore_question(id).await {
+            Ok(true) => Ok(warp::reply::with_status(
+                format!("Question {} restored", id),
                 StatusCode::OK,
             )),
+            Ok(false) => Err(warp::reject::custom(handle_errors::Error::NotFound)),
             Err(e) => Err(warp::reject::custom(e)),
         }
     } else {
@@ -9,36 +9,67 @@ use tracing_subscriber::fmt::format::FmtSpan;
 
 pub use handle_errors;
 
+pub mod cache;
 pub mod config;
 mod profanity;
 mod handlers;
+mod documents;
+mod embedding;
+mod llm;
+mod rag;
 mod store;
-mod types;
-mod retry;
+pub mod types;
+mod validation;
+mod vector_store;
+pub mod resilience;
 
 pub struct OneshotHandler {
     pub sender: Sender<i32>,
+    /// 실제로 바인드한 주소 (포트는 OS 가 고른다)
+    pub addr: std::net::SocketAddr,
 }
 
-async fn build_routes(store: store::Store) -> impl Filter<Extract = impl Reply> + Clone {
+async fn build_routes(
+    store: store::Store,
+    profanity: profanity::ProfanityClient,
+    rag: rag::RagPipeline,
+    documents: documents::DocumentProcessor,
+    token_key: handlers::authentication::TokenKey,
+) -> impl Filter<Extract = impl Reply> + Clone {
+    let max_document_bytes = documents.max_bytes();
+    let auth = handlers::authentication::auth(token_key.clone());
+    let optional_auth = handlers::authentication::optional_auth(token_key.clone());
+    let token_key_filter = warp::any().map(move || token_key.clone());
     let store_filter = warp::any().map(move || store.clone());
+    let profanity_filter = warp::any().map(move || profanity.clone());
+    let rag_filter = warp::any().map(move || rag.clone());
+    let documents_filter = warp::any().map(move || documents.clone());
 
     let cors = warp::cors()
         .allow_any_origin()
-        .allow_header("Content-Type")
-        .allow_methods(&[Method::PUT, Method::DELETE, Method::GET, Method::POST]);
+        .allow_headers(vec!["Content-Type", "If-Match", "If-None-Match"])
+        .expose_headers(vec!["ETag", "X-Request-Id"])
+        .allow_methods(&[
+            Method::PUT,
+            Method::PATCH,
+            Method::DELETE,
+            Method::GET,
+            Method::POST,
+        ]);
 
-    let login = warp::post()
-        .and(warp::path("login"))
+    let login = warp::path("login")
         .and(warp::path::end())
+        .and(warp::post())
         .and(store_filter.clone())
+        .and(token_key_filter)
         .and(warp::body::json())
         .and_then(handlers::authentication::login);
 
-    let get_questions = warp::get()
-        .and(warp::path("questions"))
+    let get_questions = warp::path("questions")
         .and(warp::path::end())
+        .and(warp::get())
         .and(warp::query())
+        .and(warp::header::optional::<String>("if-none-match"))
         .and(store_filter.clone())
         //.and(id_filter)
         .and_then(handlers::question::get_questions)
@@ -51,75 +82,302 @@ async fn build_routes(store: store::Store) -> impl Filter<Extract = impl Reply>
             )
         }));
 
-    let add_question = warp::post()
-        .and(warp::path("questions"))
+    let get_question = warp::path("questions")
+        .and(warp::path::param::<i32>())
         .and(warp::path::end())
-        .and(handlers::authentication::auth())
+        .and(warp::get())
+        .and(warp::header::optional::<String>("if-none-match"))
+        .and(optional_auth.clone())
         .and(store_filter.clone())
+        .and_then(handlers::question::get_question);
+
+    let add_question = warp::path("questions")
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(warp::query())
+        .and(auth.clone())
+        .and(store_filter.clone())
+        .and(profanity_filter.clone())
+        .and(rag_filter.clone())
         .and(warp::body::json())
         .and_then(handlers::question::add_question);
 
-    let update_question = warp::put()
-        .and(warp::path("questions"))
+    let similar_questions = warp::path("questions")
+        .and(warp::path("similar"))
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(auth.clone())
+        .and(rag_filter.clone())
+        .and(warp::body::json())
+        .and_then(handlers::question::similar_questions);
+
+    let update_question = warp::path("questions")
         .and(warp::path::param::<i32>())
         .and(warp::path::end())
-        .and(handlers::authentication::auth())
+        .and(warp::put())
+        .and(auth.clone())
         .and(store_filter.clone())
+        .and(profanity_filter.clone())
+        .and(warp::header::optional::<String>("if-match"))
         .and(warp::body::json())
         .and_then(handlers::question::update_question);
 
-    let delete_question = warp::delete()
-        .and(warp::path("questions"))
+    let patch_question = warp::path("questions")
+        .and(warp::path::param::<i32>())
+        .and(warp::path::end())
+        .and(warp::patch())
+        .and(auth.clone())
+        .and(store_filter.clone())
+        .and(profanity_filter.clone())
+        .and(warp::header::optional::<String>("if-match"))
+        .and(handlers::question::merge_patch())
+        .and_then(handlers::question::patch_question);
+
+    let delete_question = warp::path("questions")
         .and(warp::path::param::<i32>())
         .and(warp::path::end())
-        .and(handlers::authentication::auth())
+        .and(warp::delete())
+        .and(auth.clone())
         .and(store_filter.clone())
         .and_then(handlers::question::delete_question);
 
-    let add_answer = warp::post()
-        .and(warp::path("answers"))
+    let get_question_revisions = warp::path("questions")
+        .and(warp::path::param::<i32>())
+        .and(warp::path("revisions"))
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(optional_auth.clone())
+        .and(store_filter.clone())
+        .and_then(handlers::question::get_question_revisions);
+
+    let restore_question = warp::path("questions")
+        .and(warp::path::param::<i32>())
+        .and(warp::path("restore"))
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(auth.clone())
+        .and(store_filter.clone())
+        .and_then(handlers::question::restore_question);
+
+    let add_answer = warp::path("answers")
         .and(warp::path::end())
-        .and(handlers::authentication::auth())
+        .and(warp::post())
+        .and(auth.clone())
         .and(store_filter.clone())
+        .and(profanity_filter.clone())
         .and(warp::body::form())
         .and_then(handlers::answer::add_answer);
 
-    let registration = warp::post()
-        .and(warp::path("registration"))
+    let delete_answer = warp::path("answers")
+        .and(warp::path::param::<i32>())
+        .and(warp::path::end())
+        .and(warp::delete())
+        .and(auth.clone())
+        .and(store_filter.clone())
+        .and_then(handlers::answer::delete_answer);
+
+    let get_answer_drafts = warp::path("answers")
+        .and(warp::path("drafts"))
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(auth.clone())
+        .and(store_filter.clone())
+        .and_then(handlers::answer::get_drafts);
+
+    let accept_answer_draft = warp::path("answers")
+        .and(warp::path::param::<i32>())
+        .and(warp::path("accept"))
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(auth.clone())
+        .and(store_filter.clone())
+        .and(profanity_filter.clone())
+        .and_then(handlers::answer::accept_draft);
+
+    let dismiss_answer_draft = warp::path("answers")
+        .and(warp::path::param::<i32>())
+        .and(warp::path("dismiss"))
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(auth.clone())
+        .and(store_filter.clone())
+        .and_then(handlers::answer::dismiss_draft);
+
+    let restore_answer = warp::path("answers")
+        .and(warp::path::param::<i32>())
+        .and(warp::path("restore"))
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(auth.clone())
+        .and(store_filter.clone())
+        .and_then(handlers::answer::restore_answer);
+
+    let get_moderation_queue = warp::path("moderation")
+        .and(warp::path("queue"))
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(auth.clone())
+        .and(store_filter.clone())
+        .and_then(handlers::moderation::get_queue);
+
+    let decide_moderation = warp::path("moderation")
+        .and(warp::path("queue"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(auth.clone())
+        .and(store_filter.clone())
+        .and(warp::body::json())
+        .and_then(handlers::moderation::decide);
+
+    let get_notifications = warp::path("notifications")
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(auth.clone())
+        .and(store_filter.clone())
+        .and_then(handlers::moderation::get_notifications);
+
+    let chat = warp::path("chat")
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(auth.clone())
+        .and(warp::header::optional::<String>("accept"))
+        .and(store_filter.clone())
+        .and(rag_filter.clone())
+        .and(warp::body::json())
+        .and_then(handlers::chat::chat);
+
+    let chat_socket = warp::path("chat")
+        .and(warp::path("ws"))
+        .and(warp::path::end())
+        .and(warp::ws())
+        .and(auth.clone())
+        .and(store_filter.clone())
+        .and(rag_filter.clone())
+        .map(|ws: warp::ws::Ws, session, store, rag| {
+            ws.on_upgrade(move |socket| handlers::chat::chat_socket(socket, session, store, rag))
+        });
+
+    let get_conversations = warp::path("conversations")
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(warp::query())
+        .and(auth.clone())
+        .and(store_filter.clone())
+        .and_then(handlers::conversation::get_conversations);
+
+    let get_conversation = warp::path("conversations")
+        .and(warp::path::param::<i32>())
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(auth.clone())
+        .and(store_filter.clone())
+        .and_then(handlers::conversation::get_conversation);
+
+    let delete_conversation = warp::path("conversations")
+        .and(warp::path::param::<i32>())
+        .and(warp::path::end())
+        .and(warp::delete())
+        .and(auth.clone())
+        .and(store_filter.clone())
+        .and_then(handlers::conversation::delete_conversation);
+
+    let add_document = warp::path("documents")
         .and(warp::path::end())
+        .and(warp::post())
+        .and(auth.clone())
+        .and(store_filter.clone())
+        .and(documents_filter.clone())
+        .and(warp::multipart::form().max_length(max_document_bytes))
+        .and_then(handlers::document::add_document);
+
+    let delete_document = warp::path("documents")
+        .and(warp::path::param::<i32>())
+        .and(warp::path::end())
+        .and(warp::delete())
+        .and(auth.clone())
+        .and(store_filter.clone())
+        .and_then(handlers::document::delete_document);
+
+    let registration = warp::path("registration")
+        .and(warp::path::end())
+        .and(warp::post())
         .and(store_filter.clone())
         .and(warp::body::json())
         .and_then(handlers::authentication::register);
 
-    get_questions
+    let routes = get_questions
+        .or(get_question)
         .or(add_question)
+        .or(similar_questions)
         .or(update_question)
+        .or(patch_question)
         .or(delete_question)
+        .or(get_question_revisions)
+        .or(restore_question)
         .or(add_answer)
+        .or(delete_answer)
+        .or(restore_answer)
+        .or(get_answer_drafts)
+        .or(accept_answer_draft)
+        .or(dismiss_answer_draft)
+        .or(get_moderation_queue)
+        .or(decide_moderation)
+        .or(get_notifications)
+        .or(chat)
+        .or(chat_socket)
+        .or(get_conversations)
+        .or(get_conversation)
+        .or(delete_conversation)
+        .or(add_document)
+        .or(delete_document)
         .or(registration)
         .or(login)
         .with(cors)
         //.with(log)
         .with(warp::trace::request())
-        .recover(return_error)
+        .map(|reply| Ok(Reply::into_response(reply)))
+        .or_else(|r| async move { Ok::<_, warp::Rejection>((Err(r),)) });
+
+    request_id().and(routes).and_then(reply_with_request_id)
 }
 
-pub async fn setup_store(config: &config::Config) -> Result<store::Store, handle_errors::Error> {
-    let store = store::Store::new(&format!(
-        "postgres://{}:{}@{}:{}/{}",
-        config.db_user, config.db_password, config.db_host, config.db_port, config.db_name
-    ))
-    .await
-    .map_err(|e| handle_errors::Error::DatabaseQueryError(e))?;
+/// 거절된 요청은 problem+json 응답으로 바꾸고, 모든 응답에 X-Request-Id 를 붙인다
+async fn reply_with_request_id(
+    request_id: String,
+    outcome: Result<warp::reply::Response, warp::Rejection>,
+) -> Result<impl Reply, warp::Rejection> {
+    let reply = match outcome {
+        Ok(reply) => reply,
+        Err(r) => return_error(r, request_id.clone()).await?.into_response(),
+    };
 
-    sqlx::migrate!()
-        .run(&store.clone().connection)
-        .await
-        .map_err(|e| handle_errors::Error::MigrationError(e))?;
+    Ok(warp::reply::with_header(reply, "x-request-id", request_id))
+}
+
+/// 요청마다 X-Request-Id 를 정한다
+/// 클라이언트가 보낸 값이 알맞으면 그대로 쓰고, 아니면 새로 만든다
+fn request_id() -> impl Filter<Extract = (String,), Error = warp::Rejection> + Clone {
+    warp::header::optional::<String>("x-request-id").map(|id: Option<String>| match id {
+        Some(id)
+            if !id.is_empty()
+                && id.len() <= 64
+                && id
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')) =>
+        {
+            id
+        }
+        _ => uuid::Uuid::new_v4().to_string(),
+    })
+}
+
+pub async fn setup_store(config: &config::Config) -> Result<store::Store, handle_errors::Error> {
+    let store = connect_store(config).await?;
 
     let log_filter = std::env::var("RUST_LOG").unwrap_or_else(|_| {
         format!(
-            "handle_errors={},rust_web_dev={},warp={}",
+            "handle_errors={},warp_chatbot={},warp={}",
             config.log_level, config.log_level, config.log_level
         )
     });
@@ -135,25 +393,111 @@ pub async fn setup_store(config: &config::Config) -> Result<store::Store, handle
     Ok(store)
 }
 
-pub async fn run(config: config::Config, store: store::Store) {
-    let routes = build_routes(store).await;
+/// DB 에 연결하고 마이그레이션을 적용한다 (로깅은 설정하지 않는다)
+pub async fn connect_store(config: &config::Config) -> Result<store::Store, handle_errors::Error> {
+    let store = store::Store::new(
+        &format!(
+            "postgres://{}:{}@{}:{}/{}",
+            config.db_user, config.db_password, config.db_host, config.db_port, config.db_name
+        ),
+        config.db_schema.clone(),
+    )
+    .await
+    .map_err(handle_errors::Error::DatabaseQueryError)?;
+
+    sqlx::migrate!()
+        .run(&store.clone().connection)
+        .await
+        .map_err(handle_errors::Error::MigrationError)?;
+
+    Ok(store)
+}
+
+/// 밀린 임베딩을 모두 만들고 만든 수를 돌려준다 (모델을 바꾼 뒤 한 번 돌린다)
+pub async fn backfill_embeddings(
+    config: &config::Config,
+    store: store::Store,
+) -> Result<usize, handle_errors::Error> {
+    let embedder = embedding::from_config(config);
+    let vectors = vector_store::from_config(config, &store).await?;
+    embedding::EmbeddingWorker::new(config, store, embedder, vectors)
+        .backfill()
+        .await
+}
+
+/// 파일을 하나씩 읽어 문서로 저장하고 (파일 이름, 조각 수) 를 돌려준다
+/// 하나라도 읽지 못하면 그 앞까지 저장한 채로 멈춘다
+pub async fn import_documents(
+    config: &config::Config,
+    store: store::Store,
+    paths: &[std::path::PathBuf],
+) -> Result<Vec<(String, i64)>, handle_errors::Error> {
+    use validation::Validate;
+
+    let processor = documents::DocumentProcessor::new(config);
+    let mut imported = Vec::new();
+    for path in paths {
+        let bytes = tokio::fs::read(path).await.map_err(|error| {
+            handle_errors::Error::InvalidBody(format!(
+                "{} can't be read: {}",
+                path.display(),
+                error
+            ))
+        })?;
+        let filename = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let document = processor.process(filename, None, bytes).await?;
+        document.validate()?;
+        let document = store.add_document(document, None).await?;
+        imported.push((document.filename, document.chunks));
+    }
+    Ok(imported)
+}
+
+pub async fn run(config: config::Config, store: store::Store) -> Result<(), handle_errors::Error> {
+    let profanity = profanity::ProfanityClient::new(&config, &store)?;
+    let embedder = embedding::from_config(&config);
+    let vectors = vector_store::from_config(&config, &store).await?;
+    embedding::EmbeddingWorker::new(&config, store.clone(), embedder.clone(), vectors.clone())
+        .spawn();
+    let rag = rag::RagPipeline::new(&config, &store, embedder, vectors);
+    if config.ai_drafts {
+        rag::DraftWorker::new(&config, store.clone(), rag.clone()).spawn();
+    }
+    let documents = documents::DocumentProcessor::new(&config);
+    let token_key = handlers::authentication::TokenKey::new(&config.paseto_key);
+    let routes = build_routes(store, profanity, rag, documents, token_key).await;
     warp::serve(routes).run(([127, 0, 0, 1], config.port)).await;
+    Ok(())
 }
 
-pub async fn oneshot(store: store::Store) -> OneshotHandler {
-    let routes = build_routes(store).await;
+pub async fn oneshot(
+    config: &config::Config,
+    store: store::Store,
+) -> Result<OneshotHandler, handle_errors::Error> {
+    let profanity = profanity::ProfanityClient::new(config, &store)?;
+    let embedder = embedding::from_config(config);
+    let vectors = vector_store::from_config(config, &store).await?;
+    embedding::EmbeddingWorker::new(config, store.clone(), embedder.clone(), vectors.clone())
+        .spawn();
+    let rag = rag::RagPipeline::new(config, &store, embedder, vectors);
+    if config.ai_drafts {
+        rag::DraftWorker::new(config, store.clone(), rag.clone()).spawn();
+    }
+    let documents = documents::DocumentProcessor::new(config);
+    let token_key = handlers::authentication::TokenKey::new(&config.paseto_key);
+    let routes = build_routes(store, profanity, rag, documents, token_key).await;
     let (tx, rx) = oneshot::channel::<i32>();
 
-    let socket: std::net::SocketAddr = "127.0.0.1:3030"
-        .to_string()
-        .parse()
-        .expect("Not a valid address");
-
-    let (_, server) = warp::serve(routes).bind_with_graceful_shutdown(socket, async {
-        rx.await.ok();
-    });
+    // 테스트마다 빈 포트에 띄워서 서로 부딪치지 않는다
+    let (addr, server) =
+        warp::serve(routes).bind_with_graceful_shutdown(([127, 0, 0, 1], 0), async {
+            rx.await.ok();
+        });
 
     tokio::task::spawn(server);
 
-    OneshotHandler { sender: tx }
+    Ok(OneshotHandler { sender: tx, addr })
 }
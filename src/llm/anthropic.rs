@@ -0,0 +1,276 @@
+use async_trait::async_trait;
+use handle_errors::Error;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use super::http::{self, Event};
+use super::{Backend, Completion, LanguageModel, Message, Role, TokenStream, Usage, UsageCallback};
+
+const API_VERSION: &str = "2023-06-01";
+
+#[derive(Serialize, Debug)]
+struct MessagesRequest<'a> {
+    model: &'a str,
+    max_tokens: u32,
+    /// system 메시지는 messages 에 넣지 않고 여기에 모은다
+    #[serde(skip_serializing_if = "Option::is_none")]
+    system: Option<String>,
+    messages: Vec<&'a Message>,
+    temperature: f32,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    stream: bool,
+}
+
+#[derive(Deserialize, Debug)]
+struct MessagesResponse {
+    content: Vec<ContentBlock>,
+    usage: MessagesUsage,
+}
+
+#[derive(Deserialize, Debug)]
+struct ContentBlock {
+    text: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct MessagesUsage {
+    input_tokens: u64,
+    output_tokens: u64,
+}
+
+/// Anthropic `POST /v1/messages` 클라이언트
+pub struct AnthropicModel {
+    client: reqwest::Client,
+    url: String,
+    api_key: String,
+    model: String,
+    timeout: Duration,
+    max_tokens: u32,
+}
+
+impl AnthropicModel {
+    pub fn new(backend: &Backend) -> Self {
+        AnthropicModel {
+            client: reqwest::Client::new(),
+            url: backend.url.clone(),
+            api_key: backend.api_key.clone(),
+            model: backend.model.clone(),
+            timeout: backend.timeout,
+            max_tokens: backend.max_tokens,
+        }
+    }
+
+    async fn send(&self, messages: &[Message], stream: bool) -> Result<reqwest::Response, Error> {
+        let system: Vec<&str> = messages
+            .iter()
+            .filter(|message| message.role == Role::System)
+            .map(|message| message.content.as_str())
+            .collect();
+
+        let mut request = self
+            .client
+            .post(format!("{}/v1/messages", self.url))
+            .header("anthropic-version", API_VERSION)
+            .json(&MessagesRequest {
+                model: &self.model,
+                max_tokens: self.max_tokens,
+                system: (!system.is_empty()).then(|| system.join("\n\n")),
+                messages: messages
+                    .iter()
+                    .filter(|message| message.role != Role::System)
+                    .collect(),
+                // 근거에 붙어서 답하도록 낮게 둔다
+                temperature: 0.2,
+                stream,
+            });
+        if !self.api_key.is_empty() {
+            request = request.header("x-api-key", &self.api_key);
+        }
+
+        http::send(request).await
+    }
+}
+
+#[async_trait]
+impl LanguageModel for AnthropicModel {
+    async fn complete(&self, messages: Vec<Message>) -> Result<Completion, Error> {
+        let res = http::within(self.timeout, async {
+            self.send(&messages, false)
+                .await?
+                .json::<MessagesResponse>()
+                .await
+                .map_err(Error::ReqwestAPIError)
+        })
+        .await?;
+
+        let content: String = res
+            .content
+            .into_iter()
+            .filter_map(|block| block.text)
+            .collect();
+        if content.is_empty() {
+            return Err(http::invalid("language model returned no text"));
+        }
+        Ok(Completion {
+            content,
+            usage: Usage {
+                input_tokens: res.usage.input_tokens,
+                output_tokens: res.usage.output_tokens,
+            },
+        })
+    }
+
+    /// 제한 시간은 응답 헤더를 받을 때까지만 적용한다
+    async fn stream_with_usage(
+        &self,
+        messages: Vec<Message>,
+        on_usage: UsageCallback,
+    ) -> Result<TokenStream, Error> {
+        let res = http::within(self.timeout, self.send(&messages, true)).await?;
+        Ok(http::events(res, event, on_usage))
+    }
+}
+
+/// content_block_delta 의 글자만 내보내고 message_stop 에서 끝낸다
+/// 입력 토큰 수는 message_start, 출력 토큰 수는 message_delta 에 온다
+fn event(data: &str) -> Result<Event, Error> {
+    let event: serde_json::Value = serde_json::from_str(data)
+        .map_err(|_| http::invalid("language model sent an invalid event"))?;
+
+    match event["type"].as_str() {
+        Some("content_block_delta") => match event["delta"]["text"].as_str() {
+            Some(text) if !text.is_empty() => Ok(Event::Token(text.to_string())),
+            _ => Ok(Event::Skip),
+        },
+        Some("message_start") => Ok(Event::Usage(Usage {
+            input_tokens: event["message"]["usage"]["input_tokens"]
+                .as_u64()
+                .unwrap_or(0),
+            output_tokens: 0,
+        })),
+        Some("message_delta") => Ok(Event::Usage(Usage {
+            input_tokens: 0,
+            output_tokens: event["usage"]["output_tokens"].as_u64().unwrap_or(0),
+        })),
+        Some("message_stop") => Ok(Event::Done),
+        Some("error") => Err(http::invalid(
+            event["error"]["message"]
+                .as_str()
+                .unwrap_or("language model stream failed"),
+        )),
+        _ => Ok(Event::Skip),
+    }
+}
+
+#[cfg(test)]
+mod anthropic_tests {
+    use super::*;
+    use crate::config::{Config, LlmProvider};
+    use clap::Parser;
+    use futures_util::StreamExt;
+    use mock_server::{fixtures, Mock, MockResponse, MockServer};
+    use serde_json::json;
+
+    fn backend(url: String) -> Backend {
+        let mut config = Config::parse_from(["test", "--llm-provider", "anthropic"]);
+        config.llm_url = url;
+        config.llm_api_key = "anthropic-key".to_string();
+        config.llm_model = "claude-test".to_string();
+        Backend::primary(&config)
+    }
+
+    #[tokio::test]
+    async fn moves_system_prompt_and_counts_tokens() {
+        let server = MockServer::builder()
+            .mock(
+                fixtures::llm::messages_fn(|messages| format!("{} messages", messages.len()))
+                    .header("x-api-key", "anthropic-key")
+                    .header("anthropic-version", API_VERSION)
+                    .expect(1),
+            )
+            .start();
+        let backend = backend(server.url());
+        assert_eq!(backend.provider, LlmProvider::Anthropic);
+        let model = AnthropicModel::new(&backend);
+
+        let completion = model
+            .complete(vec![
+                Message::system("be brief"),
+                Message::system("cite sources"),
+                Message::user("hi there"),
+            ])
+            .await
+            .unwrap();
+
+        assert_eq!(completion.content, "1 messages");
+        assert_eq!(
+            completion.usage,
+            Usage {
+                input_tokens: 6,
+                output_tokens: 2
+            }
+        );
+        let body = server.requests()[0].json().unwrap();
+        assert_eq!(body["system"], "be brief\n\ncite sources");
+        assert_eq!(body["model"], "claude-test");
+        assert_eq!(body["max_tokens"], 1024);
+        assert_eq!(
+            body["messages"],
+            json!([{"role": "user", "content": "hi there"}])
+        );
+        server.verify();
+    }
+
+    #[tokio::test]
+    async fn streams_text_deltas() {
+        let server = MockServer::builder()
+            .mock(fixtures::llm::messages("Use warp filters"))
+            .start();
+        let model = AnthropicModel::new(&backend(server.url()));
+        let (tx, rx) = std::sync::mpsc::channel();
+
+        let tokens: Vec<String> = model
+            .stream_with_usage(
+                vec![Message::system("be brief"), Message::user("hi")],
+                Box::new(move |usage| tx.send(usage).unwrap()),
+            )
+            .await
+            .unwrap()
+            .map(Result::unwrap)
+            .collect()
+            .await;
+
+        assert_eq!(tokens, ["Use ", "warp ", "filters"]);
+        // message_start 의 output_tokens 는 세지 않는다
+        assert_eq!(
+            rx.try_recv().unwrap(),
+            Usage {
+                input_tokens: 3,
+                output_tokens: 3
+            }
+        );
+        assert_eq!(server.requests()[0].json().unwrap()["stream"], true);
+        assert!(event(r#"{"type": "error", "error": {"message": "Overloaded"}}"#).is_err());
+    }
+
+    #[tokio::test]
+    async fn maps_error_status() {
+        let server = MockServer::builder()
+            .mock(
+                Mock::post("/v1/messages").respond_with(MockResponse::json(
+                    529,
+                    &json!({"type": "error", "error": {"type": "overloaded_error", "message": "Overloaded"}}),
+                )),
+            )
+            .start();
+        let model = AnthropicModel::new(&backend(server.url()));
+
+        match model.complete(vec![Message::user("hi")]).await {
+            Err(Error::ServerError(e)) => {
+                assert_eq!(e.status, 529);
+                assert_eq!(e.message, "Overloaded");
+            }
+            other => panic!("expected server error, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,313 @@
+use async_trait::async_trait;
+use handle_errors::Error;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+
+use super::{Completion, LanguageModel, Message, TokenStream, Usage, UsageCallback};
+use crate::config::Config;
+use crate::resilience::{CircuitBreaker, CircuitBreakerConfig, Failure};
+
+/// 모델 하나와 그 모델의 서킷 브레이커, 지금까지 쓴 토큰 수
+struct Provider {
+    name: String,
+    model: Arc<dyn LanguageModel>,
+    breaker: CircuitBreaker,
+    input_tokens: AtomicU64,
+    output_tokens: AtomicU64,
+}
+
+impl Provider {
+    fn record(&self, usage: &Usage) {
+        let input = self
+            .input_tokens
+            .fetch_add(usage.input_tokens, Ordering::Relaxed)
+            + usage.input_tokens;
+        let output = self
+            .output_tokens
+            .fetch_add(usage.output_tokens, Ordering::Relaxed)
+            + usage.output_tokens;
+        tracing::event!(
+            tracing::Level::INFO,
+            provider = %self.name,
+            input_tokens = usage.input_tokens,
+            output_tokens = usage.output_tokens,
+            total_input_tokens = input,
+            total_output_tokens = output,
+            "language model usage"
+        );
+    }
+}
+
+/// 모델을 설정한 순서대로 시도한다
+/// 실패하거나 서킷이 열린 모델은 건너뛰고, 모두 실패하면 마지막으로 부른 모델의 에러를 돌려준다
+/// 스트리밍은 응답을 받기 시작한 뒤에는 다른 모델로 넘어가지 않는다
+/// 스트리밍 토큰 수는 스트림을 끝까지 받았을 때 센다
+pub struct FailoverModel {
+    providers: Vec<Arc<Provider>>,
+}
+
+impl FailoverModel {
+    pub fn new(config: &Config, models: Vec<(String, Arc<dyn LanguageModel>)>) -> Self {
+        let providers = models
+            .into_iter()
+            .map(|(name, model)| {
+                Arc::new(Provider {
+                    breaker: CircuitBreaker::new(
+                        name.clone(),
+                        CircuitBreakerConfig {
+                            failure_threshold: config.llm_failure_threshold,
+                            reset_timeout: Duration::from_millis(config.llm_reset_timeout_ms),
+                            ..CircuitBreakerConfig::default()
+                        },
+                    ),
+                    name,
+                    model,
+                    input_tokens: AtomicU64::new(0),
+                    output_tokens: AtomicU64::new(0),
+                })
+            })
+            .collect();
+
+        FailoverModel { providers }
+    }
+
+    /// call 이 처음으로 성공한 모델과 그 결과
+    async fn first<'a, T, F, Fut>(&'a self, call: F) -> Result<(T, &'a Provider), Error>
+    where
+        F: Fn(&'a Arc<Provider>) -> Fut,
+        Fut: Future<Output = Result<T, Error>>,
+    {
+        let mut last = None;
+        for provider in &self.providers {
+            let result = provider
+                .breaker
+                .call(
+                    async { call(provider).await.map_err(Failure::Inner) },
+                    is_transient,
+                )
+                .await;
+
+            let error = match result {
+                Ok(value) => return Ok((value, provider)),
+                Err(Failure::Inner(error)) => error,
+                Err(Failure::Timeout) => Error::UpstreamTimeout,
+                Err(Failure::Rejected) => Error::UpstreamBusy,
+                Err(Failure::CircuitOpen) => {
+                    tracing::event!(
+                        tracing::Level::DEBUG,
+                        provider = %provider.name,
+                        "language model skipped, circuit is open"
+                    );
+                    continue;
+                }
+            };
+            tracing::event!(
+                tracing::Level::WARN,
+                provider = %provider.name,
+                "language model failed: {}",
+                error
+            );
+            last = Some(error);
+        }
+
+        Err(last.unwrap_or(Error::CircuitOpen))
+    }
+}
+
+#[async_trait]
+impl LanguageModel for FailoverModel {
+    async fn complete(&self, messages: Vec<Message>) -> Result<Completion, Error> {
+        let (completion, provider) = self
+            .first(|provider| provider.model.complete(messages.clone()))
+            .await?;
+        provider.record(&completion.usage);
+        Ok(completion)
+    }
+
+    /// 답한 모델의 토큰 수로 남기고 on_usage 에도 넘긴다
+    async fn stream_with_usage(
+        &self,
+        messages: Vec<Message>,
+        on_usage: UsageCallback,
+    ) -> Result<TokenStream, Error> {
+        // 스트림을 연 모델 하나만 부르지만 어느 모델일지 모르므로 모든 시도에 나눠 준다
+        let on_usage = Arc::new(Mutex::new(Some(on_usage)));
+        let (tokens, _) = self
+            .first(|provider| {
+                let recorder = provider.clone();
+                let on_usage = on_usage.clone();
+                provider.model.stream_with_usage(
+                    messages.clone(),
+                    Box::new(move |usage| {
+                        recorder.record(&usage);
+                        let on_usage = on_usage
+                            .lock()
+                            .unwrap_or_else(PoisonError::into_inner)
+                            .take();
+                        if let Some(on_usage) = on_usage {
+                            on_usage(usage);
+                        }
+                    }),
+                )
+            })
+            .await?;
+        Ok(tokens)
+    }
+}
+
+/// 서킷 브레이커에 실패로 남길 에러
+/// 잘못된 요청(4xx)은 모델이 응답한 것이므로 다음 모델로 넘어가기만 하고 실패로 세지 않는다
+fn is_transient(error: &Error) -> bool {
+    match error {
+        Error::ReqwestAPIError(e) => e.is_timeout() || e.is_connect(),
+        Error::ServerError(_) | Error::UpstreamTimeout => true,
+        Error::ClientError(e) => e.status == 429,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod failover_tests {
+    use super::*;
+    use crate::llm::{Backend, OpenAiModel, ScriptedModel};
+    use clap::Parser;
+    use futures_util::StreamExt;
+    use mock_server::{fixtures, Mock, MockResponse, MockServer};
+    use serde_json::json;
+
+    fn config() -> Config {
+        let mut config = Config::parse_from(["test"]);
+        config.llm_failure_threshold = 2;
+        config
+    }
+
+    fn openai(url: String, timeout_ms: u64) -> Arc<dyn LanguageModel> {
+        let mut config = config();
+        config.llm_url = url;
+        config.llm_timeout_ms = timeout_ms;
+        Arc::new(OpenAiModel::new(&Backend::primary(&config)))
+    }
+
+    fn scripted(reply: &str) -> Arc<dyn LanguageModel> {
+        Arc::new(ScriptedModel::new(vec![reply.to_string()]))
+    }
+
+    #[tokio::test]
+    async fn fails_over_and_skips_open_circuits() {
+        let server = MockServer::builder()
+            .mock(
+                Mock::post("/v1/chat/completions").respond_with(MockResponse::json(
+                    503,
+                    &json!({"error": {"message": "overloaded"}}),
+                )),
+            )
+            .start();
+        let model = FailoverModel::new(
+            &config(),
+            vec![
+                ("primary".to_string(), openai(server.url(), 1000)),
+                ("fallback".to_string(), scripted("from the fallback")),
+            ],
+        );
+
+        for _ in 0..3 {
+            let completion = model.complete(vec![Message::user("hi")]).await.unwrap();
+            assert_eq!(completion.content, "from the fallback");
+            assert_eq!(completion.usage.output_tokens, 3);
+        }
+        // 두 번 실패한 뒤로는 서킷이 열려 부르지 않는다
+        assert_eq!(server.requests().len(), 2);
+
+        let tokens: Vec<String> = model
+            .stream(vec![Message::user("hi")])
+            .await
+            .unwrap()
+            .map(Result::unwrap)
+            .collect()
+            .await;
+        assert_eq!(tokens.concat(), "from the fallback");
+        // 스트리밍도 complete 처럼 답한 모델의 토큰 수로 센다
+        let fallback = &model.providers[1];
+        assert_eq!(fallback.output_tokens.load(Ordering::Relaxed), 4 * 3);
+        assert_eq!(model.providers[0].output_tokens.load(Ordering::Relaxed), 0);
+    }
+
+    #[tokio::test]
+    async fn records_streamed_usage() {
+        let server = MockServer::builder()
+            .mock(fixtures::llm::chat("streamed reply"))
+            .start();
+        let model = FailoverModel::new(
+            &config(),
+            vec![("primary".to_string(), openai(server.url(), 1000))],
+        );
+        let (tx, rx) = std::sync::mpsc::channel();
+
+        let tokens: Vec<String> = model
+            .stream_with_usage(
+                vec![Message::user("hi there")],
+                Box::new(move |usage| tx.send(usage).unwrap()),
+            )
+            .await
+            .unwrap()
+            .map(Result::unwrap)
+            .collect()
+            .await;
+
+        assert_eq!(tokens.concat(), "streamed reply");
+        let usage = Usage {
+            input_tokens: 2,
+            output_tokens: 2,
+        };
+        assert_eq!(rx.try_recv().unwrap(), usage);
+        let primary = &model.providers[0];
+        assert_eq!(primary.input_tokens.load(Ordering::Relaxed), 2);
+        assert_eq!(primary.output_tokens.load(Ordering::Relaxed), 2);
+    }
+
+    #[tokio::test]
+    async fn times_out_slow_models() {
+        let server = MockServer::builder()
+            .mock(Mock::post("/v1/chat/completions").respond_with(
+                MockResponse::json(200, &json!({"choices": []})).delay(Duration::from_millis(500)),
+            ))
+            .start();
+        let model = FailoverModel::new(
+            &config(),
+            vec![
+                ("slow".to_string(), openai(server.url(), 50)),
+                ("fallback".to_string(), scripted("in time")),
+            ],
+        );
+
+        let completion = model.complete(vec![Message::user("hi")]).await.unwrap();
+        assert_eq!(completion.content, "in time");
+    }
+
+    #[tokio::test]
+    async fn returns_the_last_error() {
+        let server = MockServer::builder()
+            .mock(
+                Mock::post("/v1/chat/completions").respond_with(MockResponse::json(
+                    400,
+                    &json!({"error": {"message": "context too long"}}),
+                )),
+            )
+            .start();
+        let model = FailoverModel::new(
+            &config(),
+            vec![("only".to_string(), openai(server.url(), 1000))],
+        );
+
+        // 잘못된 요청은 서킷을 열지 않는다
+        for _ in 0..3 {
+            match model.complete(vec![Message::user("hi")]).await {
+                Err(Error::ClientError(e)) => assert_eq!(e.message, "context too long"),
+                other => panic!("expected client error, got {:?}", other),
+            }
+        }
+        assert_eq!(server.requests().len(), 3);
+    }
+}
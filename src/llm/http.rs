@@ -0,0 +1,132 @@
+use futures_util::{stream, StreamExt};
+use handle_errors::{APILayerError, Error};
+use std::future::Future;
+use std::time::Duration;
+
+use super::{TokenStream, Usage, UsageCallback};
+
+/// SSE `data:` 줄 하나를 읽은 결과
+#[derive(Debug, PartialEq)]
+pub enum Event {
+    Token(String),
+    /// 토큰 수 (여러 번 오면 더한다)
+    Usage(Usage),
+    Done,
+    Skip,
+}
+
+/// 스트림에서 받은 토큰 수를 모아 끝날 때 한 번 알린다
+struct Tally {
+    usage: Option<Usage>,
+    on_usage: Option<UsageCallback>,
+}
+
+impl Tally {
+    fn add(&mut self, usage: Usage) {
+        *self.usage.get_or_insert_with(Usage::default) += usage;
+    }
+
+    fn finish(&mut self) {
+        if let (Some(usage), Some(on_usage)) = (self.usage.take(), self.on_usage.take()) {
+            on_usage(usage);
+        }
+    }
+}
+
+/// 제한 시간 안에 끝나지 않으면 UpstreamTimeout
+pub async fn within<T>(
+    timeout: Duration,
+    future: impl Future<Output = Result<T, Error>>,
+) -> Result<T, Error> {
+    tokio::time::timeout(timeout, future)
+        .await
+        .map_err(|_| Error::UpstreamTimeout)?
+}
+
+/// 요청을 보내고 2xx 가 아니면 에러 본문으로 에러를 만든다 (임베딩 클라이언트도 쓴다)
+pub async fn send(request: reqwest::RequestBuilder) -> Result<reqwest::Response, Error> {
+    let res = request.send().await.map_err(Error::ReqwestAPIError)?;
+    if res.status().is_success() {
+        return Ok(res);
+    }
+
+    let err = transform_error(res).await;
+    if err.status < 500 {
+        Err(Error::ClientError(err))
+    } else {
+        Err(Error::ServerError(err))
+    }
+}
+
+/// 본문을 줄 단위로 읽어 `data:` 줄마다 parse 를 부른다
+/// 끝까지 읽으면 받은 토큰 수로 on_usage 를 부른다 (토큰 수가 오지 않았으면 부르지 않는다)
+/// 스트림을 버리면 본문도 버려져 연결이 끊긴다
+pub fn events(
+    res: reqwest::Response,
+    parse: fn(&str) -> Result<Event, Error>,
+    on_usage: UsageCallback,
+) -> TokenStream {
+    let body = res.bytes_stream().boxed();
+    let tally = Tally {
+        usage: None,
+        on_usage: Some(on_usage),
+    };
+
+    let tokens = stream::unfold(
+        (body, Vec::new(), tally),
+        move |(mut body, mut buffer, mut tally)| async move {
+            loop {
+                if let Some(end) = buffer.iter().position(|b| *b == b'\n') {
+                    let line: Vec<u8> = buffer.drain(..=end).collect();
+                    let line = String::from_utf8_lossy(&line);
+                    let data = match line.trim().strip_prefix("data:") {
+                        Some(data) => data.trim().to_string(),
+                        None => continue,
+                    };
+                    match parse(&data) {
+                        Ok(Event::Token(token)) => return Some((Ok(token), (body, buffer, tally))),
+                        Ok(Event::Usage(usage)) => tally.add(usage),
+                        Ok(Event::Done) => {
+                            tally.finish();
+                            return None;
+                        }
+                        Ok(Event::Skip) => continue,
+                        Err(e) => return Some((Err(e), (body, Vec::new(), tally))),
+                    }
+                    continue;
+                }
+                match body.next().await {
+                    Some(Ok(bytes)) => buffer.extend_from_slice(&bytes),
+                    Some(Err(e)) => {
+                        return Some((Err(Error::ReqwestAPIError(e)), (body, buffer, tally)))
+                    }
+                    None => {
+                        tally.finish();
+                        return None;
+                    }
+                }
+            }
+        },
+    );
+    Box::pin(tokens)
+}
+
+/// 모델 API 가 알 수 없는 응답을 보냈다
+pub fn invalid(message: &str) -> Error {
+    Error::ServerError(APILayerError {
+        status: 502,
+        message: message.to_string(),
+    })
+}
+
+/// `{"error": {"message": ...}}` 에서 메시지를 꺼낸다 (OpenAI, Anthropic 모두 이 형식이다)
+async fn transform_error(res: reqwest::Response) -> APILayerError {
+    let status = res.status().as_u16();
+    let body = res.text().await.unwrap_or_default();
+    let message = serde_json::from_str::<serde_json::Value>(&body)
+        .ok()
+        .and_then(|value| value["error"]["message"].as_str().map(str::to_string))
+        .unwrap_or(body);
+
+    APILayerError { status, message }
+}
@@ -0,0 +1,192 @@
+use async_trait::async_trait;
+use futures_util::stream::{self, BoxStream};
+use handle_errors::Error;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::config::{Config, LlmProvider};
+
+mod anthropic;
+mod failover;
+pub(crate) mod http;
+mod openai;
+mod scripted;
+
+pub use anthropic::AnthropicModel;
+pub use failover::FailoverModel;
+pub use openai::OpenAiModel;
+pub use scripted::ScriptedModel;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    System,
+    User,
+    Assistant,
+}
+
+impl Role {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::System => "system",
+            Role::User => "user",
+            Role::Assistant => "assistant",
+        }
+    }
+
+    pub fn from_db(value: &str) -> Self {
+        match value {
+            "system" => Role::System,
+            "assistant" => Role::Assistant,
+            _ => Role::User,
+        }
+    }
+}
+
+/// 대화의 메시지 하나
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Message {
+    pub role: Role,
+    pub content: String,
+}
+
+impl Message {
+    pub fn system(content: impl Into<String>) -> Self {
+        Message {
+            role: Role::System,
+            content: content.into(),
+        }
+    }
+
+    pub fn user(content: impl Into<String>) -> Self {
+        Message {
+            role: Role::User,
+            content: content.into(),
+        }
+    }
+}
+
+/// 호출 한 번에 쓴 토큰 수 (API 가 알려 주지 않으면 0)
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Usage {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+}
+
+impl std::ops::AddAssign for Usage {
+    fn add_assign(&mut self, other: Usage) {
+        self.input_tokens += other.input_tokens;
+        self.output_tokens += other.output_tokens;
+    }
+}
+
+/// 모델이 만든 답
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Completion {
+    pub content: String,
+    pub usage: Usage,
+}
+
+/// 모델이 만드는 대로 받는 답의 조각
+/// 스트림을 버리면 모델 호출도 멈춘다
+pub type TokenStream = BoxStream<'static, Result<String, Error>>;
+
+/// 스트림이 끝까지 오면 그 호출에 쓴 토큰 수를 받는다
+pub type UsageCallback = Box<dyn FnOnce(Usage) + Send>;
+
+/// 메시지 목록을 받아 다음 assistant 메시지를 만든다
+#[async_trait]
+pub trait LanguageModel: Send + Sync {
+    async fn complete(&self, messages: Vec<Message>) -> Result<Completion, Error>;
+
+    async fn stream(&self, messages: Vec<Message>) -> Result<TokenStream, Error> {
+        self.stream_with_usage(messages, Box::new(|_| {})).await
+    }
+
+    /// stream 과 같고, 스트림이 끝까지 오면 API 가 알려 준 토큰 수로 on_usage 를 부른다
+    /// 스트리밍을 지원하지 않는 모델은 완성된 답을 한 조각으로 보낸다
+    async fn stream_with_usage(
+        &self,
+        messages: Vec<Message>,
+        on_usage: UsageCallback,
+    ) -> Result<TokenStream, Error> {
+        let completion = self.complete(messages).await?;
+        on_usage(completion.usage);
+        Ok(Box::pin(stream::once(
+            async move { Ok(completion.content) },
+        )))
+    }
+}
+
+/// LLM API 하나에 연결할 설정
+#[derive(Debug, Clone, PartialEq)]
+pub struct Backend {
+    pub provider: LlmProvider,
+    pub url: String,
+    pub api_key: String,
+    pub model: String,
+    /// 응답 헤더를 받을 때까지(스트리밍이 아니면 본문까지)의 제한 시간
+    pub timeout: Duration,
+    pub max_tokens: u32,
+}
+
+impl Backend {
+    pub fn primary(config: &Config) -> Self {
+        Backend {
+            provider: config.llm_provider,
+            url: config.llm_url.trim_end_matches('/').to_string(),
+            api_key: config.llm_api_key.clone(),
+            model: config.llm_model.clone(),
+            timeout: Duration::from_millis(config.llm_timeout_ms),
+            max_tokens: config.llm_max_tokens,
+        }
+    }
+
+    /// llm_fallback_provider 가 없으면 None
+    pub fn fallback(config: &Config) -> Option<Self> {
+        let provider = config.llm_fallback_provider?;
+        let url = config.llm_fallback_url.as_ref().unwrap_or(&config.llm_url);
+
+        Some(Backend {
+            provider,
+            url: url.trim_end_matches('/').to_string(),
+            api_key: config.llm_fallback_api_key.clone(),
+            model: config
+                .llm_fallback_model
+                .clone()
+                .unwrap_or_else(|| config.llm_model.clone()),
+            timeout: Duration::from_millis(config.llm_fallback_timeout_ms),
+            max_tokens: config.llm_max_tokens,
+        })
+    }
+
+    /// 로그와 서킷 브레이커에 쓸 이름
+    pub fn name(&self) -> String {
+        match self.provider {
+            LlmProvider::OpenAi => format!("openai:{}", self.model),
+            LlmProvider::Anthropic => format!("anthropic:{}", self.model),
+            LlmProvider::Fake => "fake".to_string(),
+        }
+    }
+
+    pub fn connect(&self, config: &Config) -> Arc<dyn LanguageModel> {
+        match self.provider {
+            LlmProvider::OpenAi => Arc::new(OpenAiModel::new(self)),
+            LlmProvider::Anthropic => Arc::new(AnthropicModel::new(self)),
+            LlmProvider::Fake => Arc::new(ScriptedModel::new(config.llm_fake_replies.clone())),
+        }
+    }
+}
+
+/// 설정한 LLM 을 순서대로 시도하는 모델
+/// LLM 마다 서킷 브레이커를 두어 계속 실패하는 LLM 은 건너뛴다
+pub fn language_model(config: &Config) -> Arc<dyn LanguageModel> {
+    let backends = std::iter::once(Backend::primary(config)).chain(Backend::fallback(config));
+    Arc::new(FailoverModel::new(
+        config,
+        backends
+            .map(|backend| (backend.name(), backend.connect(config)))
+            .collect(),
+    ))
+}
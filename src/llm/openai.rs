@@ -0,0 +1,276 @@
+use async_trait::async_trait;
+use handle_errors::Error;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use super::http::{self, Event};
+use super::{Backend, Completion, LanguageModel, Message, TokenStream, Usage, UsageCallback};
+
+#[derive(Serialize, Debug)]
+struct ChatCompletionRequest<'a> {
+    model: &'a str,
+    messages: &'a [Message],
+    temperature: f32,
+    max_tokens: u32,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    stream: bool,
+    /// 스트리밍이면 마지막 조각에 토큰 수를 받는다
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stream_options: Option<StreamOptions>,
+}
+
+#[derive(Serialize, Debug)]
+struct StreamOptions {
+    include_usage: bool,
+}
+
+#[derive(Deserialize, Debug)]
+struct ChatCompletionResponse {
+    choices: Vec<Choice>,
+    /// 로컬 서버는 보내지 않을 수도 있다
+    usage: Option<ChatCompletionUsage>,
+}
+
+#[derive(Deserialize, Debug)]
+struct Choice {
+    message: ChoiceMessage,
+}
+
+#[derive(Deserialize, Debug)]
+struct ChoiceMessage {
+    content: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct ChatCompletionUsage {
+    prompt_tokens: u64,
+    completion_tokens: u64,
+}
+
+/// `"stream": true` 일 때 `data:` 줄마다 오는 조각
+/// include_usage 를 보내면 마지막 조각은 choices 가 비어 있고 usage 만 있다
+#[derive(Deserialize, Debug)]
+struct ChatCompletionChunk {
+    choices: Vec<ChunkChoice>,
+    usage: Option<ChatCompletionUsage>,
+}
+
+#[derive(Deserialize, Debug)]
+struct ChunkChoice {
+    delta: ChoiceMessage,
+}
+
+/// OpenAI 호환 `POST /v1/chat/completions` 클라이언트
+/// llama.cpp, vLLM 같은 로컬 서버도 같은 API 를 쓴다
+pub struct OpenAiModel {
+    client: reqwest::Client,
+    url: String,
+    api_key: String,
+    model: String,
+    timeout: Duration,
+    max_tokens: u32,
+}
+
+impl OpenAiModel {
+    pub fn new(backend: &Backend) -> Self {
+        OpenAiModel {
+            client: reqwest::Client::new(),
+            url: backend.url.clone(),
+            api_key: backend.api_key.clone(),
+            model: backend.model.clone(),
+            timeout: backend.timeout,
+            max_tokens: backend.max_tokens,
+        }
+    }
+
+    async fn send(&self, messages: &[Message], stream: bool) -> Result<reqwest::Response, Error> {
+        let mut request = self
+            .client
+            .post(format!("{}/v1/chat/completions", self.url))
+            .json(&ChatCompletionRequest {
+                model: &self.model,
+                messages,
+                // 근거에 붙어서 답하도록 낮게 둔다
+                temperature: 0.2,
+                max_tokens: self.max_tokens,
+                stream,
+                stream_options: stream.then_some(StreamOptions {
+                    include_usage: true,
+                }),
+            });
+        if !self.api_key.is_empty() {
+            request = request.bearer_auth(&self.api_key);
+        }
+
+        http::send(request).await
+    }
+}
+
+#[async_trait]
+impl LanguageModel for OpenAiModel {
+    async fn complete(&self, messages: Vec<Message>) -> Result<Completion, Error> {
+        let res = http::within(self.timeout, async {
+            self.send(&messages, false)
+                .await?
+                .json::<ChatCompletionResponse>()
+                .await
+                .map_err(Error::ReqwestAPIError)
+        })
+        .await?;
+
+        let usage = res
+            .usage
+            .map(|usage| Usage {
+                input_tokens: usage.prompt_tokens,
+                output_tokens: usage.completion_tokens,
+            })
+            .unwrap_or_default();
+        match res
+            .choices
+            .into_iter()
+            .next()
+            .and_then(|c| c.message.content)
+        {
+            Some(content) => Ok(Completion { content, usage }),
+            None => Err(http::invalid("language model returned no choices")),
+        }
+    }
+
+    /// 제한 시간은 응답 헤더를 받을 때까지만 적용한다
+    async fn stream_with_usage(
+        &self,
+        messages: Vec<Message>,
+        on_usage: UsageCallback,
+    ) -> Result<TokenStream, Error> {
+        let res = http::within(self.timeout, self.send(&messages, true)).await?;
+        Ok(http::events(res, chunk, on_usage))
+    }
+}
+
+fn chunk(data: &str) -> Result<Event, Error> {
+    if data == "[DONE]" {
+        return Ok(Event::Done);
+    }
+
+    let chunk: ChatCompletionChunk = serde_json::from_str(data)
+        .map_err(|_| http::invalid("language model sent an invalid chunk"))?;
+    if let Some(usage) = chunk.usage {
+        return Ok(Event::Usage(Usage {
+            input_tokens: usage.prompt_tokens,
+            output_tokens: usage.completion_tokens,
+        }));
+    }
+    match chunk
+        .choices
+        .into_iter()
+        .next()
+        .and_then(|c| c.delta.content)
+    {
+        Some(token) if !token.is_empty() => Ok(Event::Token(token)),
+        _ => Ok(Event::Skip),
+    }
+}
+
+#[cfg(test)]
+mod openai_tests {
+    use super::*;
+    use crate::config::Config;
+    use clap::Parser;
+    use futures_util::StreamExt;
+    use mock_server::{fixtures, Mock, MockResponse, MockServer};
+    use serde_json::json;
+
+    fn backend(url: String) -> Backend {
+        let mut config = Config::parse_from(["test"]);
+        config.llm_url = url;
+        config.llm_api_key = "llm-key".to_string();
+        Backend::primary(&config)
+    }
+
+    #[tokio::test]
+    async fn sends_messages_and_reads_first_choice() {
+        let server = MockServer::builder()
+            .mock(
+                fixtures::llm::chat_fn(|messages| format!("{} messages", messages.len()))
+                    .header("authorization", "Bearer llm-key")
+                    .expect(1),
+            )
+            .start();
+        let model = OpenAiModel::new(&backend(server.url()));
+
+        let completion = model
+            .complete(vec![Message::system("be brief"), Message::user("hi")])
+            .await
+            .unwrap();
+
+        assert_eq!(completion.content, "2 messages");
+        assert_eq!(
+            completion.usage,
+            Usage {
+                input_tokens: 3,
+                output_tokens: 2
+            }
+        );
+        let request = &server.requests()[0];
+        let body = request.json().unwrap();
+        assert_eq!(body["messages"][0]["role"], "system");
+        assert_eq!(body["messages"][1]["content"], "hi");
+        server.verify();
+    }
+
+    #[tokio::test]
+    async fn streams_chunks_until_done() {
+        let server = MockServer::builder()
+            .mock(fixtures::llm::chat("Use warp filters").expect(1))
+            .start();
+        let model = OpenAiModel::new(&backend(server.url()));
+        let (tx, rx) = std::sync::mpsc::channel();
+
+        let tokens: Vec<String> = model
+            .stream_with_usage(
+                vec![Message::user("hi")],
+                Box::new(move |usage| tx.send(usage).unwrap()),
+            )
+            .await
+            .unwrap()
+            .map(Result::unwrap)
+            .collect()
+            .await;
+
+        assert_eq!(tokens, ["Use ", "warp ", "filters"]);
+        let body = server.requests()[0].json().unwrap();
+        assert_eq!(body["stream"], true);
+        assert_eq!(body["stream_options"]["include_usage"], true);
+        assert_eq!(
+            rx.try_recv().unwrap(),
+            Usage {
+                input_tokens: 1,
+                output_tokens: 3
+            }
+        );
+        assert_eq!(chunk(r#"{"choices": []}"#).unwrap(), Event::Skip);
+        assert!(chunk("{oops").is_err());
+        server.verify();
+    }
+
+    #[tokio::test]
+    async fn maps_error_status() {
+        let server = MockServer::builder()
+            .mock(
+                Mock::post("/v1/chat/completions").respond_with(MockResponse::json(
+                    429,
+                    &json!({"error": {"message": "Rate limit reached"}}),
+                )),
+            )
+            .start();
+        let model = OpenAiModel::new(&backend(server.url()));
+
+        match model.complete(vec![Message::user("hi")]).await {
+            Err(Error::ClientError(e)) => {
+                assert_eq!(e.status, 429);
+                assert_eq!(e.message, "Rate limit reached");
+            }
+            other => panic!("expected client error, got {:?}", other),
+        }
+    }
+}
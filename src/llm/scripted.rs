@@ -0,0 +1,104 @@
+use async_trait::async_trait;
+use futures_util::stream;
+use handle_errors::Error;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use super::{Completion, LanguageModel, Message, TokenStream, Usage, UsageCallback};
+
+/// 답을 정하지 않았을 때 돌려주는 답
+pub const FAKE_ANSWER: &str = "This is a scripted answer from the fake language model.";
+
+/// 정해 둔 답을 차례로 돌려주는 모델 (다 쓰면 처음부터 다시)
+/// API 키 없이 개발하거나 테스트에서 LLM 을 부르지 않을 때 쓴다
+/// 토큰 수는 단어 수로 센다
+pub struct ScriptedModel {
+    replies: Vec<String>,
+    next: AtomicUsize,
+}
+
+impl ScriptedModel {
+    pub fn new(replies: Vec<String>) -> Self {
+        let replies = if replies.is_empty() {
+            vec![FAKE_ANSWER.to_string()]
+        } else {
+            replies
+        };
+
+        ScriptedModel {
+            replies,
+            next: AtomicUsize::new(0),
+        }
+    }
+}
+
+#[async_trait]
+impl LanguageModel for ScriptedModel {
+    async fn complete(&self, messages: Vec<Message>) -> Result<Completion, Error> {
+        let next = self.next.fetch_add(1, Ordering::Relaxed);
+        let content = self.replies[next % self.replies.len()].clone();
+
+        let usage = Usage {
+            input_tokens: messages
+                .iter()
+                .map(|message| message.content.split_whitespace().count() as u64)
+                .sum(),
+            output_tokens: content.split_whitespace().count() as u64,
+        };
+        Ok(Completion { content, usage })
+    }
+
+    /// 단어마다 한 조각씩 보낸다
+    async fn stream_with_usage(
+        &self,
+        messages: Vec<Message>,
+        on_usage: UsageCallback,
+    ) -> Result<TokenStream, Error> {
+        let completion = self.complete(messages).await?;
+        on_usage(completion.usage);
+        let tokens: Vec<Result<String, Error>> = completion
+            .content
+            .split_inclusive(' ')
+            .map(|word| Ok(word.to_string()))
+            .collect();
+        Ok(Box::pin(stream::iter(tokens)))
+    }
+}
+
+#[cfg(test)]
+mod scripted_tests {
+    use super::*;
+    use futures_util::StreamExt;
+
+    #[tokio::test]
+    async fn replies_in_order_and_repeats() {
+        let model = ScriptedModel::new(vec!["first".to_string(), "second answer".to_string()]);
+
+        let mut contents = Vec::new();
+        for _ in 0..3 {
+            contents.push(
+                model
+                    .complete(vec![Message::user("hi")])
+                    .await
+                    .unwrap()
+                    .content,
+            );
+        }
+        assert_eq!(contents, ["first", "second answer", "first"]);
+
+        let tokens: Vec<String> = model
+            .stream(vec![Message::user("hi")])
+            .await
+            .unwrap()
+            .map(Result::unwrap)
+            .collect()
+            .await;
+        assert_eq!(tokens, ["second ", "answer"]);
+
+        let completion = ScriptedModel::new(Vec::new())
+            .complete(vec![Message::system("be brief"), Message::user("hi")])
+            .await
+            .unwrap();
+        assert_eq!(completion.content, FAKE_ANSWER);
+        assert_eq!(completion.usage.input_tokens, 3);
+    }
+}
@@ -0,0 +1,601 @@
+use async_trait::async_trait;
+use handle_errors::Error;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use super::cache::{content_hash, ProfanityCache};
+use super::{is_field_error, Censored, ProfanityFilter};
+use crate::config::{Config, ProfanityFallback};
+use crate::resilience::{
+    retry, Bulkhead, CircuitBreaker, CircuitBreakerConfig, Failure, RetryPolicy,
+};
+use crate::types::moderation::{Flag, FlagReason};
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct APIResponse {
+    message: String,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+struct BadWord {
+    original: String,
+    word: String,
+    deviations: i64,
+    info: i64,
+    #[serde(rename = "replacedLen")]
+    replaced_len: i64,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+struct BadWordsResponse {
+    content: String,
+    bad_words_total: i64,
+    bad_words_list: Vec<BadWord>,
+    censored_content: String,
+}
+
+/// APILayer bad_words API 클라이언트
+/// HTTP 커넥션 풀, 서킷 브레이커, 벌크헤드, 결과 캐시를 앱 전체에서 공유한다
+pub struct ApiLayerFilter {
+    client: reqwest::Client,
+    api_layer_url: String,
+    api_key: String,
+    fallback: ProfanityFallback,
+    retry: RetryPolicy,
+    breaker: CircuitBreaker,
+    bulkhead: Bulkhead,
+    cache: ProfanityCache,
+}
+
+impl ApiLayerFilter {
+    pub fn new(config: &Config) -> Self {
+        let attempt_timeout = Duration::from_millis(config.profanity_timeout_ms);
+
+        ApiLayerFilter {
+            client: reqwest::Client::new(),
+            api_layer_url: config.api_layer_url.clone(),
+            api_key: config.bad_words_api_key.clone(),
+            fallback: config.profanity_fallback,
+            retry: RetryPolicy {
+                max_retries: config.profanity_max_retries,
+                attempt_timeout: Some(attempt_timeout),
+                ..RetryPolicy::default()
+            },
+            breaker: CircuitBreaker::new(
+                "apilayer",
+                CircuitBreakerConfig {
+                    failure_threshold: config.profanity_failure_threshold,
+                    reset_timeout: Duration::from_millis(config.profanity_reset_timeout_ms),
+                    ..CircuitBreakerConfig::default()
+                },
+            ),
+            // 자리가 나기를 한 번의 시도 시간만큼만 기다린다
+            bulkhead: Bulkhead::new("apilayer", config.profanity_max_concurrent, attempt_timeout),
+            cache: ProfanityCache::new(config, None),
+        }
+    }
+
+    /// 기본 메모리 캐시 대신 쓸 캐시 (DB 저장소를 붙일 때)
+    pub fn with_cache(mut self, cache: ProfanityCache) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    async fn cached(&self, key: &str) -> Option<Censored> {
+        let censored = self.cache.get(key).await;
+        let outcome = match censored {
+            Some(_) => "hit",
+            None => "miss",
+        };
+        tracing::event!(tracing::Level::TRACE, key, "profanity cache {}", outcome);
+
+        // 카운터는 가끔만 남긴다
+        let metrics = self.cache.metrics();
+        if censored.is_none() && metrics.memory.misses.is_multiple_of(CACHE_METRICS_EVERY) {
+            tracing::event!(tracing::Level::DEBUG, "profanity cache: {:?}", metrics);
+        }
+        censored
+    }
+
+    /// API 를 쓸 수 없을 때의 결과 (reject 이면 None)
+    fn fallback(&self, content: String, error: &Error) -> Option<Censored> {
+        match self.fallback {
+            ProfanityFallback::Reject => None,
+            ProfanityFallback::Accept => {
+                tracing::event!(tracing::Level::WARN, "profanity check skipped: {}", error);
+                Some(Censored {
+                    content,
+                    bad_words: Vec::new(),
+                    flag: None,
+                })
+            }
+            ProfanityFallback::Queue => {
+                tracing::event!(
+                    tracing::Level::WARN,
+                    "profanity check queued for review: {}",
+                    error
+                );
+                Some(Censored {
+                    content,
+                    bad_words: Vec::new(),
+                    flag: Some(Flag {
+                        reason: FlagReason::Unchecked,
+                        words: Vec::new(),
+                    }),
+                })
+            }
+        }
+    }
+
+    async fn check(&self, content: &str) -> Result<BadWordsResponse, Error> {
+        // 잘못된 요청(4xx)은 API 가 응답한 것이므로 서킷 브레이커에는 성공으로 남는다
+        let result = self
+            .breaker
+            .call(
+                self.bulkhead
+                    .run(retry(&self.retry, || self.request(content), is_transient)),
+                is_transient,
+            )
+            .await;
+
+        result.map_err(|failure| match failure {
+            Failure::CircuitOpen => Error::CircuitOpen,
+            Failure::Rejected => Error::UpstreamBusy,
+            Failure::Timeout => Error::UpstreamTimeout,
+            Failure::Inner(error) => error,
+        })
+    }
+
+    async fn request(&self, content: &str) -> Result<BadWordsResponse, Error> {
+        let res = self
+            .client
+            .post(format!(
+                "{}/bad_words?censor_character=*",
+                self.api_layer_url
+            ))
+            .header("apikey", &self.api_key)
+            .body(content.to_string())
+            .send()
+            .await
+            .map_err(Error::ReqwestAPIError)?;
+
+        if !res.status().is_success() {
+            if res.status().is_client_error() {
+                let err = transform_error(res).await;
+                return Err(Error::ClientError(err));
+            } else {
+                let err = transform_error(res).await;
+                return Err(Error::ServerError(err));
+            }
+        }
+        match res.json::<BadWordsResponse>().await {
+            Ok(res) => Ok(res),
+            Err(e) => Err(Error::ReqwestAPIError(e)),
+        }
+    }
+}
+
+#[async_trait]
+impl ProfanityFilter for ApiLayerFilter {
+    /// API 를 쓸 수 없으면 설정한 fallback 에 따라 거절하거나 원문을 돌려준다
+    /// 실제로 검사한 결과만 캐시한다 (fallback 결과는 다음에 다시 검사한다)
+    async fn censor(&self, content: String) -> Result<Censored, Error> {
+        let key = content_hash(&content);
+        if let Some(censored) = self.cached(&key).await {
+            return Ok(censored);
+        }
+
+        match self.check(&content).await {
+            Ok(res) => {
+                let bad_words = res
+                    .bad_words_list
+                    .into_iter()
+                    .map(|bad_word| bad_word.original)
+                    .collect();
+                let censored = censored(&content, res.censored_content, bad_words);
+                self.cache.insert(key, &censored).await;
+                Ok(censored)
+            }
+            Err(error) if is_unavailable(&error) => self.fallback(content, &error).ok_or(error),
+            Err(error) => Err(error),
+        }
+    }
+
+    /// 캐시에 없는 글만 구분자로 이어 붙여 한 번에 보내고, 가린 결과를 다시 나눈다
+    /// 나눌 수 없거나 API 가 요청을 거절하면 어느 글이 문제인지 알 수 있도록 하나씩 다시 검사한다
+    async fn censor_batch(
+        &self,
+        contents: Vec<String>,
+    ) -> Result<Vec<Result<Censored, Error>>, Error> {
+        let keys: Vec<String> = contents
+            .iter()
+            .map(|content| content_hash(content))
+            .collect();
+        let mut results = Vec::with_capacity(contents.len());
+        for key in &keys {
+            results.push(self.cached(key).await.map(Ok));
+        }
+
+        let pending: Vec<usize> = (0..contents.len())
+            .filter(|&index| results[index].is_none())
+            .collect();
+        let splittable = pending
+            .iter()
+            .all(|&index| !contents[index].contains(BATCH_DELIMITER));
+
+        if !pending.is_empty() && splittable {
+            let joined = pending
+                .iter()
+                .map(|&index| contents[index].as_str())
+                .collect::<Vec<_>>()
+                .join(BATCH_DELIMITER);
+
+            match self.check(&joined).await {
+                Ok(res) => {
+                    let parts: Vec<&str> = res.censored_content.split(BATCH_DELIMITER).collect();
+                    if parts.len() == pending.len() {
+                        for (&index, part) in pending.iter().zip(parts) {
+                            let censored = censored(
+                                &contents[index],
+                                part.to_string(),
+                                words_in(&contents[index], &res.bad_words_list),
+                            );
+                            self.cache.insert(keys[index].clone(), &censored).await;
+                            results[index] = Some(Ok(censored));
+                        }
+                    } else {
+                        tracing::event!(
+                            tracing::Level::WARN,
+                            "profanity batch could not be split: {} parts for {} fields",
+                            parts.len(),
+                            pending.len()
+                        );
+                    }
+                }
+                // 글 하나만 보냈거나 키가 틀린 것처럼 글과 상관없는 거절이면 하나씩 다시 보내도 같다
+                Err(Error::ClientError(error)) if pending.len() == 1 => {
+                    results[pending[0]] = Some(Err(Error::ClientError(error)));
+                }
+                Err(Error::ClientError(error)) if !is_field_error(&error) => {
+                    return Err(Error::ClientError(error));
+                }
+                Err(Error::ClientError(_)) => (),
+                Err(error) if is_unavailable(&error) => {
+                    for &index in &pending {
+                        match self.fallback(contents[index].clone(), &error) {
+                            Some(censored) => results[index] = Some(Ok(censored)),
+                            None => return Err(error),
+                        }
+                    }
+                }
+                Err(error) => return Err(error),
+            }
+        }
+
+        let mut censored = Vec::with_capacity(contents.len());
+        for (result, content) in results.into_iter().zip(contents) {
+            censored.push(match result {
+                Some(result) => result,
+                None => self.censor(content).await,
+            });
+        }
+        Ok(censored)
+    }
+}
+
+/// 캐시 카운터를 이만큼 놓칠 때마다 한 번 남긴다
+const CACHE_METRICS_EVERY: u64 = 1000;
+
+/// 여러 글을 한 번에 보낼 때 사이에 넣는 구분자
+/// 단어 문자가 없어서 API 가 가리지 않고 그대로 돌려준다
+const BATCH_DELIMITER: &str = "\n\n\u{241F}\n\n";
+
+/// 찾은 비속어가 없으면 API 가 돌려준 텍스트 대신 원문을 그대로 쓴다
+/// 로컬 엔진처럼 깨끗한 글은 대소문자까지 바뀌지 않고, 빈 문자열이 되지 않는다
+fn censored(content: &str, censored_content: String, bad_words: Vec<String>) -> Censored {
+    Censored {
+        content: match bad_words.is_empty() {
+            true => content.to_string(),
+            false => censored_content,
+        },
+        bad_words,
+        flag: None,
+    }
+}
+
+/// 이어 붙여 보낸 글 가운데 이 글에 들어 있는 비속어
+fn words_in(content: &str, bad_words: &[BadWord]) -> Vec<String> {
+    let content = content.to_lowercase();
+    let mut words: Vec<String> = Vec::new();
+    for bad_word in bad_words {
+        if contains_word(&content, &bad_word.original.to_lowercase())
+            && !words.contains(&bad_word.original)
+        {
+            words.push(bad_word.original.clone());
+        }
+    }
+    words
+}
+
+/// 단어 경계에서만 찾는다 (class 안의 ass 는 ass 가 아니다)
+fn contains_word(content: &str, word: &str) -> bool {
+    let is_word_char = |c: Option<char>| c.is_some_and(char::is_alphanumeric);
+    !word.is_empty()
+        && content.match_indices(word).any(|(start, _)| {
+            !is_word_char(content[..start].chars().next_back())
+                && !is_word_char(content[start + word.len()..].chars().next())
+        })
+}
+
+/// 다시 시도하면 성공할 수도 있는 에러 (연결 실패, 타임아웃, 5xx, 429)
+fn is_transient(error: &Error) -> bool {
+    match error {
+        Error::ReqwestAPIError(e) => e.is_timeout() || e.is_connect(),
+        Error::ServerError(_) => true,
+        Error::ClientError(e) => e.status == 429,
+        _ => false,
+    }
+}
+
+/// fallback 을 적용할 에러 (재시도를 모두 실패했거나 호출하지 못했다)
+fn is_unavailable(error: &Error) -> bool {
+    matches!(
+        error,
+        Error::CircuitOpen | Error::UpstreamBusy | Error::UpstreamTimeout
+    ) || is_transient(error)
+}
+
+async fn transform_error(res: reqwest::Response) -> handle_errors::APILayerError {
+    let status = res.status().as_u16();
+    // 에러 본문이 APILayer 형식의 JSON 이 아닐 수도 있다 (프록시 에러 페이지 등)
+    let message = match res.text().await {
+        Ok(body) => serde_json::from_str::<APIResponse>(&body)
+            .map(|res| res.message)
+            .unwrap_or(body),
+        Err(e) => e.to_string(),
+    };
+
+    handle_errors::APILayerError { status, message }
+}
+
+#[cfg(test)]
+mod profanity_tests {
+    use super::{
+        words_in, ApiLayerFilter, BadWord, Censored, Error, Flag, FlagReason, ProfanityCache,
+        ProfanityFilter,
+    };
+    use crate::config::{Config, ProfanityFallback};
+
+    use clap::Parser;
+    use mock_server::{fixtures, Mock, MockResponse, MockServer};
+    use serde_json::json;
+
+    fn client(api_layer_url: &str, fallback: ProfanityFallback) -> ApiLayerFilter {
+        let mut config = Config::parse_from(["server"]);
+        config.api_layer_url = api_layer_url.to_string();
+        config.bad_words_api_key = "YES".to_string();
+        config.profanity_fallback = fallback;
+        config.profanity_max_retries = 1;
+        config.profanity_failure_threshold = 2;
+        ApiLayerFilter::new(&config)
+    }
+
+    fn apilayer() -> MockServer {
+        MockServer::builder()
+            .mock(fixtures::apilayer::bad_words("YES", &["shitty"]))
+            .start()
+    }
+
+    /// 아무도 듣고 있지 않은 포트 (연결 거부로 바로 실패한다)
+    const UNREACHABLE: &str = "http://127.0.0.1:9";
+
+    #[tokio::test]
+    async fn run() {
+        let server = apilayer();
+        let cache = ProfanityCache::new(&Config::parse_from(["server"]), None);
+        let client = client(&server.url(), ProfanityFallback::Reject).with_cache(cache.clone());
+        censor_profane_words(&client).await;
+        no_profane_words(&client).await;
+        assert_eq!(server.requests()[0].header("apikey"), Some("YES"));
+        drop(server);
+
+        // 서버를 내린 뒤에도 같은 내용은 캐시에서 돌려준다
+        censor_profane_words(&client).await;
+        let metrics = cache.metrics();
+        assert_eq!((metrics.memory.hits, metrics.memory.misses), (1, 2));
+    }
+
+    async fn censor_profane_words(client: &ApiLayerFilter) {
+        let content = "This is a shitty sentence".to_string();
+        let censored = client.censor(content).await.unwrap();
+        assert_eq!(censored.content, "This is a ****** sentence");
+        assert_eq!(censored.bad_words, vec!["shitty"]);
+    }
+
+    async fn no_profane_words(client: &ApiLayerFilter) {
+        let content = "this is a sentence".to_string();
+        let censored_content = client.censor(content).await;
+        assert_eq!(censored_content.unwrap().content, "this is a sentence");
+    }
+
+    #[tokio::test]
+    async fn clean_text_is_returned_unchanged() {
+        let server = MockServer::builder()
+            .mock(Mock::post("/bad_words").respond_with(MockResponse::json(
+                200,
+                &json!({
+                    "content": "this is a sentence",
+                    "bad_words_total": 0,
+                    "bad_words_list": [],
+                    "censored_content": "",
+                }),
+            )))
+            .start();
+        let client = client(&server.url(), ProfanityFallback::Reject);
+
+        let censored = client.censor("This is a Sentence".to_string()).await;
+        assert_eq!(censored.unwrap().content, "This is a Sentence");
+    }
+
+    #[tokio::test]
+    async fn batch_checks_fields_in_one_request() {
+        let server = apilayer();
+        let client = client(&server.url(), ProfanityFallback::Reject);
+
+        let results = client
+            .censor_batch(vec!["shitty title".to_string(), "fine content".to_string()])
+            .await
+            .unwrap();
+        let results: Vec<Censored> = results.into_iter().map(Result::unwrap).collect();
+        assert_eq!(results[0].content, "****** title");
+        assert_eq!(results[0].bad_words, vec!["shitty"]);
+        assert_eq!(results[1].content, "fine content");
+        assert!(results[1].bad_words.is_empty());
+        assert_eq!(server.received("POST /bad_words"), 1);
+
+        // 두 번째는 캐시에서 나온다
+        client
+            .censor_batch(vec!["shitty title".to_string(), "fine content".to_string()])
+            .await
+            .unwrap();
+        assert_eq!(server.received("POST /bad_words"), 1);
+    }
+
+    #[tokio::test]
+    async fn retries_transient_server_errors() {
+        let server = MockServer::builder()
+            .mock(
+                Mock::post("/bad_words")
+                    .respond_with_sequence([
+                        MockResponse::json(503, &json!({"message": "busy"})),
+                        MockResponse::json(
+                            200,
+                            &json!({
+                                "content": "text",
+                                "bad_words_total": 0,
+                                "bad_words_list": [],
+                                "censored_content": "text",
+                            }),
+                        ),
+                    ])
+                    .expect(2),
+            )
+            .start();
+
+        let censored = client(&server.url(), ProfanityFallback::Reject)
+            .censor("text".to_string())
+            .await
+            .unwrap();
+        assert_eq!(censored.content, "text");
+        server.verify();
+    }
+
+    #[test]
+    fn words_in_matches_whole_words() {
+        let bad_words: Vec<BadWord> = serde_json::from_value(json!([{
+            "original": "Ass",
+            "word": "ass",
+            "deviations": 0,
+            "info": 2,
+            "replacedLen": 3,
+        }]))
+        .unwrap();
+
+        assert_eq!(
+            words_in("a classic assessment", &bad_words),
+            Vec::<String>::new()
+        );
+        assert_eq!(words_in("you ass!", &bad_words), vec!["Ass"]);
+        assert_eq!(words_in("ASS", &bad_words), vec!["Ass"]);
+    }
+
+    #[tokio::test]
+    async fn rejected_batch_is_not_resent_when_it_cannot_help() {
+        let server = MockServer::builder()
+            .mock(fixtures::apilayer::bad_words("OTHER", &["shitty"]))
+            .start();
+        let client = client(&server.url(), ProfanityFallback::Reject);
+
+        // 키가 틀리면 필드마다 다시 보내지 않는다
+        match client
+            .censor_batch(vec!["title".to_string(), "content".to_string()])
+            .await
+        {
+            Err(Error::ClientError(e)) => assert_eq!(e.status, 401),
+            other => panic!("expected client error, got {:?}", other),
+        }
+        assert_eq!(server.received("POST /bad_words"), 1);
+
+        // 글이 하나뿐이면 그 글의 결과다
+        let results = client
+            .censor_batch(vec!["content".to_string()])
+            .await
+            .unwrap();
+        assert!(matches!(results[0], Err(Error::ClientError(_))));
+        assert_eq!(server.received("POST /bad_words"), 2);
+    }
+
+    #[tokio::test]
+    async fn wrong_api_key_is_client_error() {
+        let server = MockServer::builder()
+            .mock(fixtures::apilayer::bad_words("OTHER", &["shitty"]))
+            .start();
+
+        match client(&server.url(), ProfanityFallback::Accept)
+            .censor("text".to_string())
+            .await
+        {
+            Err(Error::ClientError(e)) => assert_eq!(e.status, 401),
+            other => panic!("expected client error, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn circuit_opens_after_repeated_failures() {
+        let client = client(UNREACHABLE, ProfanityFallback::Reject);
+
+        for _ in 0..2 {
+            match client.censor("text".to_string()).await {
+                Err(Error::ReqwestAPIError(e)) => assert!(e.is_connect()),
+                other => panic!("expected connect error, got {:?}", other),
+            }
+        }
+
+        match client.censor("text".to_string()).await {
+            Err(Error::CircuitOpen) => (),
+            other => panic!("expected open circuit, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn fallback_when_unavailable() {
+        let accepted = client(UNREACHABLE, ProfanityFallback::Accept)
+            .censor("shitty text".to_string())
+            .await
+            .unwrap();
+        assert_eq!(
+            accepted,
+            Censored {
+                content: "shitty text".to_string(),
+                bad_words: Vec::new(),
+                flag: None,
+            }
+        );
+
+        let queued = client(UNREACHABLE, ProfanityFallback::Queue)
+            .censor("shitty text".to_string())
+            .await
+            .unwrap();
+        assert_eq!(
+            queued,
+            Censored {
+                content: "shitty text".to_string(),
+                bad_words: Vec::new(),
+                flag: Some(Flag {
+                    reason: FlagReason::Unchecked,
+                    words: Vec::new(),
+                }),
+            }
+        );
+    }
+}
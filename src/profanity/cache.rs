@@ -0,0 +1,108 @@
+use sha2::{Digest, Sha256};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use super::Censored;
+use crate::cache::{CacheMetrics, TtlCache};
+use crate::config::Config;
+use crate::store::Store;
+
+/// 캐시 카운터 스냅숏
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProfanityCacheMetrics {
+    pub memory: CacheMetrics,
+    /// 메모리에 없어서 DB 에서 찾은 수
+    pub persistent_hits: u64,
+    pub persistent_misses: u64,
+}
+
+/// 같은 내용을 다시 검사하지 않도록 결과를 내용 해시로 저장한다
+/// 메모리를 먼저 보고, 설정하면 DB 도 본다. DB 에러는 캐시가 없는 것으로 본다
+#[derive(Clone)]
+pub struct ProfanityCache {
+    memory: TtlCache<String, Censored>,
+    store: Option<Store>,
+    ttl: Duration,
+    persistent_hits: Arc<AtomicU64>,
+    persistent_misses: Arc<AtomicU64>,
+}
+
+impl ProfanityCache {
+    pub fn new(config: &Config, store: Option<Store>) -> Self {
+        let ttl = Duration::from_secs(config.profanity_cache_ttl_secs);
+
+        ProfanityCache {
+            memory: TtlCache::new(config.profanity_cache_capacity, ttl),
+            store: store.filter(|_| config.profanity_cache_persistent),
+            ttl,
+            persistent_hits: Arc::new(AtomicU64::new(0)),
+            persistent_misses: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    pub async fn get(&self, key: &str) -> Option<Censored> {
+        if let Some(censored) = self.memory.get(&key.to_string()) {
+            return Some(censored);
+        }
+
+        let store = self.store.as_ref()?;
+        match store.get_profanity_cache(key, self.ttl.as_secs()).await {
+            Ok(Some((content, bad_words))) => {
+                self.persistent_hits.fetch_add(1, Ordering::Relaxed);
+                let censored = Censored {
+                    content,
+                    bad_words,
+                    flag: None,
+                };
+                self.memory.insert(key.to_string(), censored.clone());
+                Some(censored)
+            }
+            Ok(None) | Err(_) => {
+                self.persistent_misses.fetch_add(1, Ordering::Relaxed);
+                None
+            }
+        }
+    }
+
+    pub async fn insert(&self, key: String, censored: &Censored) {
+        if let Some(store) = &self.store {
+            // 저장하지 못해도 검사 결과는 그대로 쓴다
+            let _ = store
+                .put_profanity_cache(
+                    &key,
+                    &censored.content,
+                    &censored.bad_words,
+                    self.ttl.as_secs(),
+                )
+                .await;
+        }
+        self.memory.insert(key, censored.clone());
+    }
+
+    pub fn metrics(&self) -> ProfanityCacheMetrics {
+        ProfanityCacheMetrics {
+            memory: self.memory.metrics(),
+            persistent_hits: self.persistent_hits.load(Ordering::Relaxed),
+            persistent_misses: self.persistent_misses.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// 캐시 키 (내용의 SHA-256 16진수)
+pub fn content_hash(content: &str) -> String {
+    format!("{:x}", Sha256::digest(content.as_bytes()))
+}
+
+#[cfg(test)]
+mod cache_tests {
+    use super::content_hash;
+
+    #[test]
+    fn hash_is_stable_hex() {
+        assert_eq!(
+            content_hash("abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+}
@@ -0,0 +1,256 @@
+use aho_corasick::{AhoCorasick, MatchKind};
+use async_trait::async_trait;
+use handle_errors::Error;
+use std::fs;
+use unicode_normalization::char::{decompose_canonical, is_combining_mark};
+
+use super::{Censored, ProfanityFilter};
+use crate::config::Config;
+
+const ENGLISH_WORDS: &str = include_str!("words/en.txt");
+const KOREAN_WORDS: &str = include_str!("words/ko.txt");
+
+/// 외부 API 없이 단어 목록으로 비속어를 가린다
+/// 대소문자, 발음 구별 기호(é → e), 리트 문자(sh1t → shit)를 정규화한 뒤 Aho-Corasick 으로 한 번에 찾는다
+pub struct LocalFilter {
+    automaton: AhoCorasick,
+    patterns: Vec<Pattern>,
+}
+
+#[derive(Debug)]
+struct Pattern {
+    /// 예외 단어는 찾더라도 가리지 않는다
+    allowed: bool,
+    /// 이 단어로 시작하는 단어 전체를 가린다
+    prefix: bool,
+    /// 라틴 문자 단어는 단어 경계에서만 찾는다 (class 안의 ass 를 가리지 않도록)
+    whole_word: bool,
+}
+
+impl LocalFilter {
+    /// 기본 목록에 설정의 단어 목록 파일을 더한다
+    pub fn new(config: &Config) -> Result<Self, Error> {
+        let mut lists = vec![ENGLISH_WORDS.to_string(), KOREAN_WORDS.to_string()];
+        if let Some(path) = &config.profanity_words {
+            let words = fs::read_to_string(path).map_err(|e| {
+                Error::InvalidConfig(format!(
+                    "Profanity word list {} cannot be read: {}",
+                    path, e
+                ))
+            })?;
+            lists.push(words);
+        }
+
+        Ok(LocalFilter::from_lists(lists.iter().map(String::as_str)))
+    }
+
+    /// 한 줄에 한 단어, `#` 은 주석, `*` 로 끝나면 접두어, `!` 로 시작하면 예외 단어다
+    pub fn from_lists<'a>(lists: impl IntoIterator<Item = &'a str>) -> Self {
+        let mut words = Vec::new();
+        let mut patterns = Vec::new();
+
+        for line in lists.into_iter().flat_map(str::lines) {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (allowed, word) = match line.strip_prefix('!') {
+                Some(word) => (true, word),
+                None => (false, line),
+            };
+            let (prefix, word) = match word.strip_suffix('*') {
+                Some(word) => (true, word),
+                None => (false, word),
+            };
+            let word = normalize(word).text;
+            if word.is_empty() {
+                continue;
+            }
+
+            patterns.push(Pattern {
+                allowed,
+                prefix,
+                whole_word: word.chars().all(|c| c.is_ascii_alphabetic()),
+            });
+            words.push(word);
+        }
+
+        let automaton = AhoCorasick::builder()
+            .match_kind(MatchKind::LeftmostLongest)
+            .build(&words)
+            .expect("profanity word list is too large");
+
+        LocalFilter {
+            automaton,
+            patterns,
+        }
+    }
+
+    pub fn censor_text(&self, content: &str) -> Censored {
+        let normalized = normalize(content);
+        let mut chars: Vec<char> = content.chars().collect();
+        let mut masked = vec![false; chars.len()];
+        let mut bad_words = Vec::new();
+        // 단어 경계는 원문 글자로 판단한다 (`ass!` 의 `!` 는 리트 문자 i 가 아니다)
+        let is_letter = |index: usize| chars.get(index).is_some_and(|c| c.is_alphabetic());
+
+        for found in self.automaton.find_iter(&normalized.text) {
+            let pattern = &self.patterns[found.pattern().as_usize()];
+            if pattern.allowed {
+                continue;
+            }
+
+            let first = normalized.origins[found.start()];
+            let mut last = normalized.origins[found.end() - 1];
+            if pattern.whole_word && first > 0 && is_letter(first - 1) {
+                continue;
+            }
+            if pattern.prefix {
+                while is_letter(last + 1) {
+                    last += 1;
+                }
+            } else if pattern.whole_word && is_letter(last + 1) {
+                continue;
+            }
+
+            masked[first..=last].iter_mut().for_each(|m| *m = true);
+            let word: String = chars[first..=last].iter().collect();
+            if !bad_words.contains(&word) {
+                bad_words.push(word);
+            }
+        }
+
+        for (c, masked) in chars.iter_mut().zip(masked) {
+            if masked {
+                *c = '*';
+            }
+        }
+
+        Censored {
+            content: chars.into_iter().collect(),
+            bad_words,
+            flag: None,
+        }
+    }
+}
+
+#[async_trait]
+impl ProfanityFilter for LocalFilter {
+    async fn censor(&self, content: String) -> Result<Censored, Error> {
+        Ok(self.censor_text(&content))
+    }
+}
+
+struct Normalized {
+    text: String,
+    /// text 의 바이트마다 원문에서 몇 번째 글자에서 왔는지
+    origins: Vec<usize>,
+}
+
+fn normalize(content: &str) -> Normalized {
+    let mut text = String::with_capacity(content.len());
+    let mut origins = Vec::with_capacity(content.len());
+
+    for (index, c) in content.chars().enumerate() {
+        let mut push = |c: char| {
+            text.push(c);
+            origins.resize(text.len(), index);
+        };
+
+        if let Some(c) = leet(c) {
+            push(c);
+        } else if is_hangul(c) {
+            // 한글 음절을 분해하면 자모가 되어 목록과 맞지 않는다
+            push(c);
+        } else {
+            decompose_canonical(c, |d| {
+                if !is_combining_mark(d) {
+                    d.to_lowercase().for_each(&mut push);
+                }
+            });
+        }
+    }
+
+    Normalized { text, origins }
+}
+
+fn leet(c: char) -> Option<char> {
+    match c {
+        '0' => Some('o'),
+        '1' | '!' | '|' => Some('i'),
+        '3' => Some('e'),
+        '4' | '@' => Some('a'),
+        '5' | '$' => Some('s'),
+        '7' | '+' => Some('t'),
+        _ => None,
+    }
+}
+
+fn is_hangul(c: char) -> bool {
+    matches!(c, '\u{AC00}'..='\u{D7A3}' | '\u{3131}'..='\u{318E}' | '\u{1100}'..='\u{11FF}')
+}
+
+#[cfg(test)]
+mod local_tests {
+    use super::{Error, LocalFilter, ENGLISH_WORDS, KOREAN_WORDS};
+    use crate::config::Config;
+    use clap::Parser;
+
+    fn censor(content: &str) -> String {
+        LocalFilter::from_lists([ENGLISH_WORDS, KOREAN_WORDS])
+            .censor_text(content)
+            .content
+    }
+
+    #[test]
+    fn censors_like_apilayer() {
+        assert_eq!(
+            censor("This is a shitty sentence"),
+            "This is a ****** sentence"
+        );
+        assert_eq!(censor("this is a sentence"), "this is a sentence");
+    }
+
+    #[test]
+    fn normalizes_leetspeak_case_and_diacritics() {
+        assert_eq!(censor("SH1T happens"), "**** happens");
+        assert_eq!(censor("what the fück"), "what the ****");
+        assert_eq!(censor("@$$ hat"), "*** hat");
+    }
+
+    #[test]
+    fn only_matches_whole_latin_words() {
+        assert_eq!(censor("a classic assessment"), "a classic assessment");
+        assert_eq!(censor("you ass!"), "you ***!");
+    }
+
+    #[test]
+    fn censors_korean_with_particles() {
+        assert_eq!(censor("이 병신아"), "이 **아");
+        assert_eq!(censor("존나 좋다"), "** 좋다");
+    }
+
+    #[test]
+    fn allowed_words_win_over_profanity() {
+        assert_eq!(censor("시발점이 어디인가요"), "시발점이 어디인가요");
+    }
+
+    #[test]
+    fn custom_list() {
+        let filter = LocalFilter::from_lists(["# test\nfoo*\n!food"]);
+        let censored = filter.censor_text("foobar food foo");
+        assert_eq!(censored.content, "****** food ***");
+        assert_eq!(censored.bad_words, vec!["foobar", "foo"]);
+    }
+
+    #[test]
+    fn unreadable_word_list_is_a_config_error() {
+        let mut config = Config::parse_from(["server"]);
+        config.profanity_words = Some("/nonexistent/words.txt".to_string());
+        assert!(matches!(
+            LocalFilter::new(&config),
+            Err(Error::InvalidConfig(_))
+        ));
+    }
+}
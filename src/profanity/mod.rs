@@ -0,0 +1,187 @@
+use async_trait::async_trait;
+use handle_errors::{APILayerError, Error, FieldError};
+use std::sync::Arc;
+
+use crate::config::{Config, ProfanityAction, ProfanityEngine};
+use crate::store::Store;
+use crate::types::moderation::{Flag, FlagReason};
+
+mod apilayer;
+mod cache;
+mod local;
+
+pub use apilayer::ApiLayerFilter;
+pub use cache::ProfanityCache;
+pub use local::LocalFilter;
+
+/// 비속어 검사 결과
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Censored {
+    pub content: String,
+    /// 찾은 비속어 (원문 그대로)
+    pub bad_words: Vec<String>,
+    /// 검토 대기열에 보내야 하는 이유
+    pub flag: Option<Flag>,
+}
+
+/// 비속어를 글자 수만큼 `*` 로 가린다
+#[async_trait]
+pub trait ProfanityFilter: Send + Sync {
+    async fn censor(&self, content: String) -> Result<Censored, Error>;
+
+    /// 여러 글을 한 번에 검사한다. 결과는 입력 순서대로 글마다 하나씩이다
+    /// 전체가 실패하면 바깥 Err, 글 하나만 실패하면 그 자리의 Err 이다
+    async fn censor_batch(
+        &self,
+        contents: Vec<String>,
+    ) -> Result<Vec<Result<Censored, Error>>, Error> {
+        let mut results = Vec::with_capacity(contents.len());
+        for content in contents {
+            results.push(self.censor(content).await);
+        }
+        Ok(results)
+    }
+}
+
+/// 설정에서 고른 필터를 앱 전체에서 공유한다
+#[derive(Clone)]
+pub struct ProfanityClient {
+    filter: Arc<dyn ProfanityFilter>,
+    action: ProfanityAction,
+}
+
+impl ProfanityClient {
+    pub fn new(config: &Config, store: &Store) -> Result<Self, Error> {
+        // 외부 API 를 쓸 때만 결과를 캐시한다
+        let filter: Arc<dyn ProfanityFilter> = match config.profanity_engine {
+            ProfanityEngine::ApiLayer => Arc::new(
+                ApiLayerFilter::new(config)
+                    .with_cache(ProfanityCache::new(config, Some(store.clone()))),
+            ),
+            ProfanityEngine::Local => Arc::new(LocalFilter::new(config)?),
+        };
+
+        Ok(ProfanityClient {
+            filter,
+            action: config.profanity_action,
+        })
+    }
+
+    /// 이름 붙은 필드를 한 번에 검사해서 같은 순서로 돌려준다
+    /// 필드 내용 때문에 거절된 필드는 모아서 ValidationError 로 알려준다
+    pub async fn censor_fields<const N: usize>(
+        &self,
+        fields: [(&'static str, String); N],
+    ) -> Result<[Censored; N], Error> {
+        let contents: Vec<String> = fields.iter().map(|(_, content)| content.clone()).collect();
+        let results = self.filter.censor_batch(contents).await?;
+
+        let mut censored = Vec::with_capacity(N);
+        let mut field_errors = Vec::new();
+        for ((name, content), result) in fields.into_iter().zip(results) {
+            match result {
+                Ok(res) => censored.push(self.apply_action(content, res)),
+                Err(Error::ClientError(e)) if is_field_error(&e) => field_errors.push(FieldError {
+                    field: name.to_string(),
+                    message: e.message,
+                }),
+                Err(e) => return Err(e),
+            }
+        }
+
+        if !field_errors.is_empty() {
+            return Err(Error::ValidationError(field_errors));
+        }
+        censored.try_into().map_err(|censored: Vec<Censored>| {
+            Error::ServerError(APILayerError {
+                status: 500,
+                message: format!(
+                    "profanity filter returned {} results for {} fields",
+                    censored.len(),
+                    N
+                ),
+            })
+        })
+    }
+
+    /// queue 로 설정하면 비속어를 가리지 않고 원문 그대로 검토 대기열에 보낸다
+    fn apply_action(&self, content: String, censored: Censored) -> Censored {
+        if self.action == ProfanityAction::Censor || censored.bad_words.is_empty() {
+            return censored;
+        }
+
+        Censored {
+            content,
+            flag: Some(Flag {
+                reason: FlagReason::Profanity,
+                words: censored.bad_words.clone(),
+            }),
+            bad_words: censored.bad_words,
+        }
+    }
+}
+
+/// 인증 에러 같은 설정 문제가 아니라 글 내용 때문에 거절되었다
+fn is_field_error(error: &APILayerError) -> bool {
+    matches!(error.status, 400 | 413 | 422)
+}
+
+#[cfg(test)]
+mod profanity_client_tests {
+    use super::{Censored, ProfanityClient, ProfanityFilter};
+    use crate::config::ProfanityAction;
+    use async_trait::async_trait;
+    use handle_errors::{APILayerError, Error};
+    use std::sync::Arc;
+
+    /// `bad` 가 들어 있으면 400 으로 거절한다
+    struct Strict;
+
+    #[async_trait]
+    impl ProfanityFilter for Strict {
+        async fn censor(&self, content: String) -> Result<Censored, Error> {
+            if content.contains("bad") {
+                return Err(Error::ClientError(APILayerError {
+                    status: 400,
+                    message: "content rejected".to_string(),
+                }));
+            }
+            Ok(Censored {
+                content,
+                bad_words: Vec::new(),
+                flag: None,
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn reports_rejected_fields() {
+        let client = ProfanityClient {
+            filter: Arc::new(Strict),
+            action: ProfanityAction::Censor,
+        };
+
+        let [title, content] = client
+            .censor_fields([("title", "ok".to_string()), ("content", "fine".to_string())])
+            .await
+            .unwrap();
+        assert_eq!(
+            (title.content.as_str(), content.content.as_str()),
+            ("ok", "fine")
+        );
+
+        match client
+            .censor_fields([
+                ("title", "bad".to_string()),
+                ("content", "fine".to_string()),
+            ])
+            .await
+        {
+            Err(Error::ValidationError(errors)) => {
+                assert_eq!(errors.len(), 1);
+                assert_eq!(errors[0].field, "title");
+            }
+            other => panic!("expected validation error, got {:?}", other.map(|_| ())),
+        }
+    }
+}
@@ -0,0 +1,194 @@
+use crate::config::{Config, HistoryStrategy};
+use crate::llm;
+use crate::types::conversation::{Conversation, Message};
+
+/// 프롬프트에 넣을 지난 대화
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct History {
+    /// 창에 들어가지 않은 앞부분 대화의 요약
+    pub summary: Option<String>,
+    /// 그대로 넣는 최근 메시지 (오래된 것부터)
+    pub messages: Vec<llm::Message>,
+}
+
+impl History {
+    /// 후속 질문의 검색어에 붙일 직전 질문
+    pub fn last_question(&self) -> Option<&str> {
+        self.messages
+            .iter()
+            .rev()
+            .find(|message| message.role == llm::Role::User)
+            .map(|message| message.content.as_str())
+    }
+}
+
+/// 지난 대화가 max_chars 글자를 넘지 않게 최근 메시지만 남긴다
+/// 밀려난 메시지는 버리거나(truncate) 요약에 합친다(summarize)
+#[derive(Debug, Clone)]
+pub struct ContextWindow {
+    max_chars: usize,
+    pub strategy: HistoryStrategy,
+}
+
+impl ContextWindow {
+    pub fn new(config: &Config) -> Self {
+        ContextWindow {
+            max_chars: config.chat_history_chars,
+            strategy: config.chat_history_strategy,
+        }
+    }
+
+    /// DB 에서 읽을 최근 메시지의 글자 수
+    /// summarize 는 창에 남길 만큼에 더해 한 번에 요약할 만큼을 더 읽는다
+    /// (요약이 계속 실패해 밀린 메시지가 그보다 많으면 오래된 쪽은 요약하지 않고 버린다)
+    pub fn load_chars(&self) -> usize {
+        match self.strategy {
+            HistoryStrategy::Truncate => self.max_chars,
+            HistoryStrategy::Summarize => self.max_chars.saturating_mul(2),
+        }
+    }
+
+    /// (밀려난 메시지, 남길 메시지)
+    /// 이미 요약한 메시지는 둘 다에서 뺀다
+    pub fn split<'a>(
+        &self,
+        conversation: &Conversation,
+        messages: &'a [Message],
+    ) -> (&'a [Message], &'a [Message]) {
+        let start = match &conversation.summarized_until {
+            Some(until) => messages.partition_point(|message| message.id <= *until),
+            None => 0,
+        };
+        let messages = &messages[start..];
+
+        let mut used = 0;
+        let mut keep = messages.len();
+        for (i, message) in messages.iter().enumerate().rev() {
+            used += message.content.chars().count();
+            if used > self.max_chars {
+                break;
+            }
+            keep = i;
+        }
+        // 답만 남아 질문 없이 시작하지 않게 한다
+        while keep < messages.len() && messages[keep].role != llm::Role::User {
+            keep += 1;
+        }
+
+        messages.split_at(keep)
+    }
+}
+
+/// 이전 요약과 밀려난 메시지를 하나의 요약으로 합치는 프롬프트
+pub fn summary_prompt(summary: Option<&str>, dropped: &[Message]) -> Vec<llm::Message> {
+    let transcript = dropped
+        .iter()
+        .map(|message| format!("{}: {}", message.role.as_str(), message.content))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    vec![
+        llm::Message::system(
+            "다음 대화를 이어질 질문에 답하는 데 필요한 사실 위주로 다섯 문장 이내로 요약하세요. \
+             대화와 같은 언어로 쓰세요.",
+        ),
+        llm::Message::user(match summary {
+            Some(summary) => format!("이전 요약:\n{}\n\n대화:\n{}", summary, transcript),
+            None => format!("대화:\n{}", transcript),
+        }),
+    ]
+}
+
+#[cfg(test)]
+pub(crate) mod context_tests {
+    use super::*;
+    use crate::types::{
+        account::AccountId,
+        conversation::{ConversationId, MessageId},
+    };
+    use chrono::Utc;
+
+    fn conversation(summarized_until: Option<i32>) -> Conversation {
+        Conversation {
+            id: ConversationId(1),
+            account_id: AccountId(1),
+            title: "title".to_string(),
+            summary: summarized_until.map(|_| "summary".to_string()),
+            summarized_until: summarized_until.map(MessageId),
+            created_on: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    /// 질문과 답을 번갈아 만든다 (id 는 1 부터)
+    pub(crate) fn turns<S: ToString>(contents: &[S]) -> Vec<Message> {
+        contents
+            .iter()
+            .enumerate()
+            .map(|(i, content)| Message {
+                id: MessageId(i as i32 + 1),
+                role: if i % 2 == 0 {
+                    llm::Role::User
+                } else {
+                    llm::Role::Assistant
+                },
+                content: content.to_string(),
+                citations: Vec::new(),
+                created_on: Utc::now(),
+            })
+            .collect()
+    }
+
+    /// 질문과 답을 번갈아 content 길이만큼 만든다
+    fn messages(lengths: &[usize]) -> Vec<Message> {
+        let contents: Vec<String> = lengths.iter().map(|length| "x".repeat(*length)).collect();
+        turns(&contents)
+    }
+
+    fn ids(messages: &[Message]) -> Vec<i32> {
+        messages.iter().map(|message| message.id.0).collect()
+    }
+
+    #[test]
+    fn keeps_recent_turns_within_budget() {
+        let window = ContextWindow {
+            max_chars: 100,
+            strategy: HistoryStrategy::Truncate,
+        };
+        let all = messages(&[40, 40, 30, 30, 20, 20]);
+
+        let (dropped, kept) = window.split(&conversation(None), &all);
+        assert_eq!(ids(dropped), vec![1, 2]);
+        assert_eq!(ids(kept), vec![3, 4, 5, 6]);
+
+        // 4, 5, 6 이 들어가지만 답(4)으로 시작하지 않게 한 칸 더 민다
+        let window = ContextWindow {
+            max_chars: 80,
+            ..window
+        };
+        let (dropped, kept) = window.split(&conversation(None), &all);
+        assert_eq!(ids(dropped), vec![1, 2, 3, 4]);
+        assert_eq!(ids(kept), vec![5, 6]);
+
+        let window = ContextWindow {
+            max_chars: 1000,
+            ..window
+        };
+        let (dropped, kept) = window.split(&conversation(Some(2)), &all);
+        assert!(dropped.is_empty());
+        assert_eq!(ids(kept), vec![3, 4, 5, 6]);
+    }
+
+    #[test]
+    fn summary_prompt_carries_previous_summary() {
+        let all = messages(&[3, 3]);
+
+        let prompt = summary_prompt(Some("earlier"), &all);
+
+        assert_eq!(prompt[0].role, llm::Role::System);
+        assert_eq!(
+            prompt[1].content,
+            "이전 요약:\nearlier\n\n대화:\nuser: xxx\nassistant: xxx"
+        );
+    }
+}
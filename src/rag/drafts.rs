@@ -0,0 +1,88 @@
+use handle_errors::Error;
+use tokio::task::JoinHandle;
+use tokio::time::Duration;
+
+use super::RagPipeline;
+use crate::config::Config;
+use crate::store::Store;
+
+/// 한동안 답변이 없는 질문에 모델이 쓴 답변 초안을 단다
+/// 초안은 질문 작성자가 받아들이기 전까지 공개되지 않고, 질문마다 한 번만 만든다
+pub struct DraftWorker {
+    store: Store,
+    rag: RagPipeline,
+    delay_mins: u32,
+    batch_size: usize,
+    max_attempts: u32,
+    interval: Duration,
+}
+
+impl DraftWorker {
+    pub fn new(config: &Config, store: Store, rag: RagPipeline) -> Self {
+        DraftWorker {
+            store,
+            rag,
+            delay_mins: config.ai_draft_delay_mins,
+            batch_size: config.ai_draft_batch_size.max(1),
+            max_attempts: config.ai_draft_max_attempts.max(1),
+            interval: Duration::from_secs(config.ai_draft_interval_secs),
+        }
+    }
+
+    /// 한 묶음을 처리하고 만든 초안 수를 돌려준다
+    /// 초안을 만들지 못한 질문은 실패 횟수를 남기고 건너뛰며, max_attempts 번 실패할 때까지 다음 주기에 다시 시도한다
+    pub async fn run_once(&self) -> Result<usize, Error> {
+        let questions = self
+            .store
+            .get_undrafted_questions(self.delay_mins, self.batch_size as i64)
+            .await?;
+
+        let mut drafted = 0;
+        for question in questions {
+            let content = match self.rag.draft_answer(&question).await {
+                Ok(content) => content,
+                Err(error) => {
+                    let given_up = self
+                        .store
+                        .record_ai_draft_failure(&question.id, self.max_attempts)
+                        .await?;
+                    tracing::event!(
+                        tracing::Level::WARN,
+                        question_id = question.id.0,
+                        given_up,
+                        "answer draft failed: {}",
+                        error
+                    );
+                    continue;
+                }
+            };
+            if self
+                .store
+                .add_ai_draft(&question.id, content)
+                .await?
+                .is_some()
+            {
+                drafted += 1;
+            }
+        }
+        Ok(drafted)
+    }
+
+    /// interval 마다 한 묶음을 처리한다 (실패하면 다음 주기에 다시 시도한다)
+    pub fn spawn(self) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            loop {
+                match self.run_once().await {
+                    Ok(0) => {}
+                    Ok(drafted) => {
+                        tracing::event!(tracing::Level::INFO, "drafted {} answers", drafted)
+                    }
+                    Err(error) => {
+                        tracing::event!(tracing::Level::WARN, "answer drafts failed: {}", error)
+                    }
+                }
+                tokio::time::sleep(self.interval).await;
+            }
+        })
+    }
+}
@@ -0,0 +1,153 @@
+use async_trait::async_trait;
+use handle_errors::Error;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use super::Retriever;
+use crate::types::chat::{Passage, RetrievalFilter};
+
+/// 두 검색 결과를 합치기 전에 이만큼 더 넉넉히 가져온다
+const CANDIDATE_FACTOR: usize = 4;
+
+/// 의미 검색과 키워드 검색 점수의 비중
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HybridWeights {
+    pub semantic: f32,
+    pub keyword: f32,
+}
+
+/// 두 검색기의 점수를 각각 0..1 로 맞춘 뒤 가중합으로 순위를 다시 매긴다
+pub struct HybridRetriever {
+    semantic: Arc<dyn Retriever>,
+    keyword: Arc<dyn Retriever>,
+    weights: HybridWeights,
+}
+
+impl HybridRetriever {
+    pub fn new(
+        semantic: Arc<dyn Retriever>,
+        keyword: Arc<dyn Retriever>,
+        weights: HybridWeights,
+    ) -> Self {
+        HybridRetriever {
+            semantic,
+            keyword,
+            weights,
+        }
+    }
+}
+
+#[async_trait]
+impl Retriever for HybridRetriever {
+    async fn retrieve(
+        &self,
+        query: &str,
+        top_k: usize,
+        filter: &RetrievalFilter,
+    ) -> Result<Vec<Passage>, Error> {
+        let candidates = top_k.saturating_mul(CANDIDATE_FACTOR);
+        let (semantic, keyword) = tokio::try_join!(
+            self.semantic.retrieve(query, candidates, filter),
+            self.keyword.retrieve(query, candidates, filter),
+        )?;
+
+        let mut fused: HashMap<_, Passage> = HashMap::new();
+        for (passages, weight) in [
+            (semantic, self.weights.semantic),
+            (keyword, self.weights.keyword),
+        ] {
+            let max = passages.iter().map(|p| p.score).fold(0.0, f32::max);
+            for passage in passages {
+                let score = if max > 0.0 {
+                    weight * passage.score / max
+                } else {
+                    0.0
+                };
+                fused
+                    .entry(passage.key())
+                    .or_insert(Passage {
+                        score: 0.0,
+                        ..passage
+                    })
+                    .score += score;
+            }
+        }
+
+        let mut passages: Vec<Passage> = fused.into_values().collect();
+        passages.sort_by(|a, b| b.score.total_cmp(&a.score));
+        passages.truncate(top_k);
+        Ok(passages)
+    }
+}
+
+#[cfg(test)]
+mod hybrid_tests {
+    use super::*;
+    use crate::types::{chat::SourceType, question::QuestionId};
+    use chrono::Utc;
+
+    struct Fixed(Vec<(i32, f32)>);
+
+    #[async_trait]
+    impl Retriever for Fixed {
+        async fn retrieve(
+            &self,
+            _query: &str,
+            top_k: usize,
+            _filter: &RetrievalFilter,
+        ) -> Result<Vec<Passage>, Error> {
+            Ok(self
+                .0
+                .iter()
+                .take(top_k)
+                .map(|&(id, score)| Passage {
+                    source: SourceType::Question,
+                    question_id: Some(QuestionId(id)),
+                    answer_id: None,
+                    document_id: None,
+                    chunk_id: None,
+                    title: format!("Question {}", id),
+                    content: String::new(),
+                    updated_at: Utc::now(),
+                    score,
+                })
+                .collect())
+        }
+    }
+
+    fn hybrid(semantic: f32, keyword: f32) -> HybridRetriever {
+        HybridRetriever::new(
+            Arc::new(Fixed(vec![(1, 0.9), (2, 0.45)])),
+            Arc::new(Fixed(vec![(3, 8.0), (2, 4.0)])),
+            HybridWeights { semantic, keyword },
+        )
+    }
+
+    async fn ranked(retriever: HybridRetriever, top_k: usize) -> Vec<(i32, f32)> {
+        retriever
+            .retrieve("query", top_k, &RetrievalFilter::default())
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|p| (p.key().1, (p.score * 100.0).round() / 100.0))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn fuses_normalized_scores() {
+        // 1: 0.7, 2: 0.7 * 0.5 + 0.3 * 0.5, 3: 0.3
+        assert_eq!(
+            ranked(hybrid(0.7, 0.3), 3).await,
+            vec![(1, 0.7), (2, 0.5), (3, 0.3)]
+        );
+        assert_eq!(ranked(hybrid(0.7, 0.3), 1).await, vec![(1, 0.7)]);
+    }
+
+    #[tokio::test]
+    async fn weights_change_the_order() {
+        assert_eq!(
+            ranked(hybrid(0.2, 0.8), 3).await,
+            vec![(3, 0.8), (2, 0.5), (1, 0.2)]
+        );
+    }
+}
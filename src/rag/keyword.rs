@@ -0,0 +1,31 @@
+use async_trait::async_trait;
+use handle_errors::Error;
+
+use super::Retriever;
+use crate::store::Store;
+use crate::types::chat::{Passage, RetrievalFilter};
+
+/// Postgres 전문 검색으로 공개된 질문과 답변을 찾는다
+pub struct KeywordRetriever {
+    store: Store,
+}
+
+impl KeywordRetriever {
+    pub fn new(store: Store) -> Self {
+        KeywordRetriever { store }
+    }
+}
+
+#[async_trait]
+impl Retriever for KeywordRetriever {
+    async fn retrieve(
+        &self,
+        query: &str,
+        top_k: usize,
+        filter: &RetrievalFilter,
+    ) -> Result<Vec<Passage>, Error> {
+        self.store
+            .search_passages(query, top_k as i64, filter)
+            .await
+    }
+}
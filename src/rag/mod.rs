@@ -0,0 +1,607 @@
+use async_trait::async_trait;
+use futures_util::stream;
+use handle_errors::Error;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use crate::config::{Config, HistoryStrategy, RetrieverKind};
+use crate::embedding::Embedder;
+use crate::llm::{self, LanguageModel, Message, TokenStream};
+use crate::store::Store;
+use crate::types::chat::{ChatResponse, Citation, Passage, RetrievalFilter};
+use crate::types::conversation::{self, Conversation, MessageId};
+use crate::types::question::{Question, SimilarQuestion};
+use crate::vector_store::VectorStore;
+
+mod context;
+mod drafts;
+mod hybrid;
+mod keyword;
+mod similar;
+mod vector;
+
+pub use context::{ContextWindow, History};
+pub use drafts::DraftWorker;
+pub use hybrid::{HybridRetriever, HybridWeights};
+pub use keyword::KeywordRetriever;
+pub use similar::SimilarQuestions;
+pub use vector::VectorRetriever;
+
+/// 프롬프트에 넣는 글 하나의 최대 글자 수
+const MAX_PASSAGE_CHARS: usize = 1_000;
+
+const SYSTEM_PROMPT: &str = "당신은 Q&A 게시판의 도우미입니다. \
+아래 참고 자료만 근거로 답하고, 근거로 쓴 자료는 자료 앞의 [Q<번호>] 나 [D<번호>] 형식으로 인용하세요. \
+참고 자료로 답할 수 없으면 모른다고 말하세요. \
+질문과 같은 언어로 답하세요.";
+
+/// 아무것도 찾지 못했을 때는 모델을 부르지 않고 이 답을 돌려준다
+const NO_CONTEXT_ANSWER: &str =
+    "I couldn't find any questions, answers or documents related to that in the archive.";
+
+/// 질문과 관련된 글을 점수가 높은 순서로 찾는다
+/// 점수의 범위는 구현마다 다르다 (합칠 때는 HybridRetriever 가 맞춘다)
+#[async_trait]
+pub trait Retriever: Send + Sync {
+    async fn retrieve(
+        &self,
+        query: &str,
+        top_k: usize,
+        filter: &RetrievalFilter,
+    ) -> Result<Vec<Passage>, Error>;
+}
+
+/// 찾은 글을 근거로 프롬프트를 만들어 모델에게 답을 받는다
+#[derive(Clone)]
+pub struct RagPipeline {
+    retriever: Arc<dyn Retriever>,
+    llm: Arc<dyn LanguageModel>,
+    top_k: usize,
+    window: ContextWindow,
+    similar: SimilarQuestions,
+}
+
+impl RagPipeline {
+    pub fn new(
+        config: &Config,
+        store: &Store,
+        embedder: Arc<dyn Embedder>,
+        vectors: Arc<dyn VectorStore>,
+    ) -> Self {
+        let retriever: Arc<dyn Retriever> = match config.retriever {
+            RetrieverKind::Keyword => Arc::new(KeywordRetriever::new(store.clone())),
+            RetrieverKind::Vector => {
+                Arc::new(VectorRetriever::new(config, embedder.clone(), vectors))
+            }
+            RetrieverKind::Hybrid => Arc::new(HybridRetriever::new(
+                Arc::new(VectorRetriever::new(config, embedder.clone(), vectors)),
+                Arc::new(KeywordRetriever::new(store.clone())),
+                HybridWeights {
+                    semantic: config.hybrid_semantic_weight,
+                    keyword: config.hybrid_keyword_weight,
+                },
+            )),
+        };
+
+        RagPipeline {
+            similar: SimilarQuestions::new(config, retriever.clone(), embedder),
+            retriever,
+            llm: llm::language_model(config),
+            top_k: config.chat_top_k,
+            window: ContextWindow::new(config),
+        }
+    }
+
+    /// 쓰고 있는 질문과 비슷한 공개된 질문 (같은 검색기로 찾는다)
+    pub async fn similar_questions(
+        &self,
+        title: &str,
+        content: &str,
+        limit: Option<usize>,
+    ) -> Result<Vec<SimilarQuestion>, Error> {
+        self.similar.find(title, content, limit).await
+    }
+
+    /// 새 질문이 중복일 수 있는지 볼 때 쓴다
+    pub async fn possible_duplicates(
+        &self,
+        title: &str,
+        content: &str,
+    ) -> Result<Vec<SimilarQuestion>, Error> {
+        self.similar.duplicates(title, content).await
+    }
+
+    /// history 에 넘길 최근 메시지의 글자 수
+    pub fn history_chars(&self) -> usize {
+        self.window.load_chars()
+    }
+
+    /// 저장한 메시지 중 프롬프트에 넣을 부분을 고른다
+    /// 요약하지 못하면 밀려난 메시지를 버린다 (truncate 와 같다)
+    /// 요약을 새로 만들었으면 대화에 저장할 (요약, 마지막으로 요약한 메시지) 도 돌려준다
+    pub async fn history(
+        &self,
+        conversation: &Conversation,
+        messages: &[conversation::Message],
+    ) -> Result<(History, Option<(String, MessageId)>), Error> {
+        let (dropped, kept) = self.window.split(conversation, messages);
+        let mut history = History {
+            summary: conversation.summary.clone(),
+            messages: kept
+                .iter()
+                .map(|message| Message {
+                    role: message.role,
+                    content: message.content.clone(),
+                })
+                .collect(),
+        };
+
+        let last = match dropped.last() {
+            Some(last) if self.window.strategy == HistoryStrategy::Summarize => last,
+            _ => return Ok((history, None)),
+        };
+        let summary = match self
+            .llm
+            .complete(context::summary_prompt(
+                conversation.summary.as_deref(),
+                dropped,
+            ))
+            .await
+        {
+            Ok(completion) => completion.content,
+            Err(error) => {
+                tracing::event!(
+                    tracing::Level::WARN,
+                    "conversation {} can't be summarized, dropping {} messages: {}",
+                    conversation.id.0,
+                    dropped.len(),
+                    error
+                );
+                return Ok((history, None));
+            }
+        };
+        history.summary = Some(summary.clone());
+        Ok((history, Some((summary, last.id.clone()))))
+    }
+
+    /// 후속 질문은 직전 질문과 함께 검색해서 "그건 어떻게 해?" 같은 질문도 글을 찾게 한다
+    pub async fn ground(
+        &self,
+        question: &str,
+        history: &History,
+        top_k: Option<usize>,
+        filter: &RetrievalFilter,
+    ) -> Result<Grounding, Error> {
+        let query = match history.last_question() {
+            Some(previous) => format!("{}\n{}", previous, question),
+            None => question.to_string(),
+        };
+        let passages = self
+            .retriever
+            .retrieve(&query, top_k.unwrap_or(self.top_k), filter)
+            .await?;
+        let prompt = prompt(question, history, &passages);
+
+        Ok(Grounding { passages, prompt })
+    }
+
+    pub async fn answer(
+        &self,
+        question: &str,
+        history: &History,
+        top_k: Option<usize>,
+        filter: &RetrievalFilter,
+    ) -> Result<ChatResponse, Error> {
+        let grounding = self.ground(question, history, top_k, filter).await?;
+        if grounding.passages.is_empty() {
+            return Ok(grounding.respond(NO_CONTEXT_ANSWER.to_string()));
+        }
+
+        let completion = self.llm.complete(grounding.prompt.clone()).await?;
+        let mut res = grounding.respond(completion.content);
+        res.usage = Some(completion.usage);
+        Ok(res)
+    }
+
+    /// 답변이 없는 질문에 달 답변 초안 (인용 라벨은 지운다)
+    /// 질문 자신은 근거에서 빼고, 근거로 쓸 다른 글이 없으면 모델을 부르지 않고 None 이다
+    pub async fn draft_answer(&self, question: &Question) -> Result<Option<String>, Error> {
+        let query = format!("{}\n{}", question.title, question.content);
+        let passages: Vec<Passage> = self
+            .retriever
+            .retrieve(&query, self.top_k + 1, &RetrievalFilter::default())
+            .await?
+            .into_iter()
+            .filter(|passage| passage.question_id.as_ref() != Some(&question.id))
+            .take(self.top_k)
+            .collect();
+        if passages.is_empty() {
+            return Ok(None);
+        }
+
+        let completion = self
+            .llm
+            .complete(prompt(&query, &History::default(), &passages))
+            .await?;
+        Ok(Some(strip_citations(&completion.content)))
+    }
+
+    /// answer 와 같지만 모델이 만드는 대로 답을 조각으로 보낸다
+    /// 다 받은 답은 Grounding::respond 로 인용을 붙인다
+    pub async fn stream(&self, grounding: &Grounding) -> Result<TokenStream, Error> {
+        if grounding.passages.is_empty() {
+            return Ok(Box::pin(stream::once(async {
+                Ok(NO_CONTEXT_ANSWER.to_string())
+            })));
+        }
+
+        self.llm.stream(grounding.prompt.clone()).await
+    }
+}
+
+/// 질문에 대해 찾은 글과 모델에 보낼 프롬프트
+#[derive(Debug, Clone)]
+pub struct Grounding {
+    passages: Vec<Passage>,
+    prompt: Vec<Message>,
+}
+
+impl Grounding {
+    /// 모델의 답에 찾은 글을 인용 목록으로 붙인다
+    pub fn respond(self, answer: String) -> ChatResponse {
+        let cited = cited_labels(&answer);
+
+        ChatResponse {
+            conversation_id: None,
+            usage: None,
+            citations: self
+                .passages
+                .into_iter()
+                .map(|passage| Citation {
+                    cited: cited.contains(&passage.label()),
+                    question_id: passage.question_id,
+                    answer_id: passage.answer_id,
+                    document_id: passage.document_id,
+                    title: passage.title,
+                    score: passage.score,
+                })
+                .collect(),
+            answer,
+        }
+    }
+}
+
+/// 글마다 `[Q<질문 id>]` 나 `[D<문서 id>]` 라벨을 붙여 참고 자료로 넣는다
+/// 지난 대화는 요약, 최근 메시지 순서로 참고 자료 앞에 둔다
+fn prompt(question: &str, history: &History, passages: &[Passage]) -> Vec<Message> {
+    let context = passages
+        .iter()
+        .map(|passage| {
+            let content: String = passage.content.chars().take(MAX_PASSAGE_CHARS).collect();
+            format!(
+                "[{}] ({}) {}\n{}",
+                passage.label(),
+                passage.source.as_str(),
+                passage.title,
+                content
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n");
+
+    let mut messages = vec![Message::system(SYSTEM_PROMPT)];
+    if let Some(summary) = &history.summary {
+        messages.push(Message::system(format!("이전 대화 요약:\n{}", summary)));
+    }
+    messages.extend(history.messages.iter().cloned());
+    messages.push(Message::user(format!(
+        "참고 자료:\n{}\n\n질문: {}",
+        context, question
+    )));
+    messages
+}
+
+/// 답에서 `[Q12]`, `[D3]` 처럼 인용한 라벨
+fn cited_labels(answer: &str) -> HashSet<String> {
+    answer
+        .split('[')
+        .skip(1)
+        .filter_map(|rest| rest.split_once(']'))
+        .filter(|(label, _)| is_label(label))
+        .map(|(label, _)| label.to_string())
+        .collect()
+}
+
+/// 답에서 인용 라벨과 그 앞의 공백을 지운다 (라벨을 풀어 볼 인용 목록이 없는 곳에 저장할 때 쓴다)
+fn strip_citations(answer: &str) -> String {
+    let mut parts = answer.split('[');
+    let mut stripped = parts.next().unwrap_or_default().to_string();
+    for part in parts {
+        match part.split_once(']') {
+            Some((label, rest)) if is_label(label) => {
+                stripped.truncate(stripped.trim_end().len());
+                stripped.push_str(rest);
+            }
+            _ => {
+                stripped.push('[');
+                stripped.push_str(part);
+            }
+        }
+    }
+    stripped
+}
+
+fn is_label(label: &str) -> bool {
+    label.len() > 1
+        && label.starts_with(['Q', 'D'])
+        && label[1..].chars().all(|c| c.is_ascii_digit())
+}
+
+#[cfg(test)]
+mod rag_tests {
+    use super::*;
+    use crate::embedding::HashingEmbedder;
+    use crate::llm::Completion;
+    use crate::types::{answer::AnswerId, chat::SourceType, question::QuestionId};
+    use chrono::Utc;
+    use clap::Parser;
+    use context::context_tests::turns;
+    use mock_server::{fixtures, MockServer};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    struct Fixed(Vec<Passage>);
+
+    #[async_trait]
+    impl Retriever for Fixed {
+        async fn retrieve(
+            &self,
+            _query: &str,
+            top_k: usize,
+            _filter: &RetrievalFilter,
+        ) -> Result<Vec<Passage>, Error> {
+            Ok(self.0.iter().take(top_k).cloned().collect())
+        }
+    }
+
+    /// 항상 실패한다
+    struct Failing;
+
+    #[async_trait]
+    impl LanguageModel for Failing {
+        async fn complete(&self, _messages: Vec<Message>) -> Result<Completion, Error> {
+            Err(Error::UpstreamTimeout)
+        }
+    }
+
+    /// 불린 횟수만 센다
+    #[derive(Default)]
+    struct Counting(AtomicUsize);
+
+    #[async_trait]
+    impl LanguageModel for Counting {
+        async fn complete(&self, _messages: Vec<Message>) -> Result<Completion, Error> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            Ok(Completion {
+                content: String::new(),
+                usage: Default::default(),
+            })
+        }
+    }
+
+    fn pipeline(
+        config: &Config,
+        retriever: Arc<dyn Retriever>,
+        llm: Arc<dyn LanguageModel>,
+        top_k: usize,
+    ) -> RagPipeline {
+        RagPipeline {
+            similar: SimilarQuestions::new(
+                config,
+                retriever.clone(),
+                Arc::new(HashingEmbedder::new(64)),
+            ),
+            retriever,
+            llm,
+            top_k,
+            window: ContextWindow::new(config),
+        }
+    }
+
+    fn passage(question_id: i32, answer_id: Option<i32>, content: &str) -> Passage {
+        Passage {
+            source: match answer_id {
+                Some(_) => SourceType::Answer,
+                None => SourceType::Question,
+            },
+            question_id: Some(QuestionId(question_id)),
+            answer_id: answer_id.map(AnswerId),
+            document_id: None,
+            chunk_id: None,
+            title: format!("Question {}", question_id),
+            content: content.to_string(),
+            updated_at: Utc::now(),
+            score: 1.0 / question_id as f32,
+        }
+    }
+
+    #[tokio::test]
+    async fn grounds_answer_and_cites_questions() {
+        let server = MockServer::builder()
+            .mock(fixtures::llm::chat("Use warp::path [Q1].").expect(1))
+            .start();
+        let mut config = Config::parse_from(["test"]);
+        config.llm_url = server.url();
+
+        let pipeline = pipeline(
+            &config,
+            Arc::new(Fixed(vec![
+                passage(1, None, "Use warp::path"),
+                passage(2, Some(7), "Try filters"),
+                passage(3, None, "Not needed"),
+            ])),
+            llm::language_model(&config),
+            2,
+        );
+
+        let res = pipeline
+            .answer(
+                "How do I route?",
+                &History::default(),
+                None,
+                &RetrievalFilter::default(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(res.answer, "Use warp::path [Q1].");
+        assert_eq!(res.citations.len(), 2);
+        assert!(res.citations[0].cited);
+        assert_eq!(res.citations[1].answer_id, Some(AnswerId(7)));
+        assert!(!res.citations[1].cited);
+        let body = server.requests()[0].json().unwrap();
+        assert_eq!(body["messages"][0]["role"], "system");
+        let prompt = body["messages"][1]["content"].as_str().unwrap();
+        assert!(prompt.contains("[Q1] (question) Question 1\nUse warp::path"));
+        assert!(prompt.contains("[Q2] (answer) Question 2\nTry filters"));
+        assert!(!prompt.contains("Not needed"));
+        assert!(prompt.ends_with("질문: How do I route?"));
+        server.verify();
+    }
+
+    #[tokio::test]
+    async fn skips_model_without_context() {
+        let llm = Arc::new(Counting::default());
+        let pipeline = pipeline(
+            &Config::parse_from(["test"]),
+            Arc::new(Fixed(Vec::new())),
+            llm.clone(),
+            5,
+        );
+
+        let res = pipeline
+            .answer(
+                "anything",
+                &History::default(),
+                Some(3),
+                &RetrievalFilter::default(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(res.answer, NO_CONTEXT_ANSWER);
+        assert!(res.citations.is_empty());
+        assert_eq!(llm.0.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn summarizes_old_turns_for_follow_ups() {
+        let server = MockServer::builder()
+            .mock(fixtures::llm::chat_fn(|messages| {
+                let system = messages[0]["content"].as_str().unwrap_or_default();
+                if system.starts_with("다음 대화를") {
+                    "Asked about routing".to_string()
+                } else {
+                    "Use path [Q1]".to_string()
+                }
+            }))
+            .start();
+        let mut config = Config::parse_from(["test"]);
+        config.llm_url = server.url();
+        config.chat_history_chars = 20;
+        config.chat_history_strategy = HistoryStrategy::Summarize;
+        let pipeline = pipeline(
+            &config,
+            Arc::new(Fixed(vec![passage(1, None, "Use warp::path")])),
+            llm::language_model(&config),
+            5,
+        );
+        let conversation = Conversation {
+            id: conversation::ConversationId(1),
+            account_id: crate::types::account::AccountId(1),
+            title: "first question".to_string(),
+            summary: None,
+            summarized_until: None,
+            created_on: Utc::now(),
+            updated_at: Utc::now(),
+        };
+        let messages = turns(&["first question", "first answer", "q2", "a2"]);
+
+        let (history, summary) = pipeline.history(&conversation, &messages).await.unwrap();
+        assert_eq!(
+            summary,
+            Some(("Asked about routing".to_string(), MessageId(2)))
+        );
+        assert_eq!(history.last_question(), Some("q2"));
+
+        let res = pipeline
+            .answer("and then?", &history, None, &RetrievalFilter::default())
+            .await
+            .unwrap();
+        assert_eq!(res.answer, "Use path [Q1]");
+        let summarized = server.requests()[0].json().unwrap();
+        assert!(summarized["messages"][1]["content"]
+            .as_str()
+            .unwrap()
+            .ends_with("user: first question\nassistant: first answer"));
+        let body = server.requests()[1].json().unwrap();
+        let roles: Vec<_> = body["messages"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|message| message["role"].as_str().unwrap())
+            .collect();
+        assert_eq!(roles, ["system", "system", "user", "assistant", "user"]);
+        assert_eq!(
+            body["messages"][1]["content"],
+            "이전 대화 요약:\nAsked about routing"
+        );
+        assert_eq!(body["messages"][2]["content"], "q2");
+    }
+
+    #[tokio::test]
+    async fn falls_back_to_truncation_when_summary_fails() {
+        let mut config = Config::parse_from(["test"]);
+        config.chat_history_chars = 20;
+        config.chat_history_strategy = HistoryStrategy::Summarize;
+        let pipeline = pipeline(&config, Arc::new(Fixed(Vec::new())), Arc::new(Failing), 5);
+        let conversation = Conversation {
+            id: conversation::ConversationId(1),
+            account_id: crate::types::account::AccountId(1),
+            title: "first question".to_string(),
+            summary: Some("earlier".to_string()),
+            summarized_until: None,
+            created_on: Utc::now(),
+            updated_at: Utc::now(),
+        };
+        let messages = turns(&["first question", "first answer", "q2", "a2"]);
+
+        let (history, summary) = pipeline.history(&conversation, &messages).await.unwrap();
+
+        // 요약을 저장하지 않고 이전 요약과 창에 들어가는 메시지만 넣는다
+        assert_eq!(summary, None);
+        assert_eq!(history.summary.as_deref(), Some("earlier"));
+        let contents: Vec<_> = history
+            .messages
+            .iter()
+            .map(|message| message.content.as_str())
+            .collect();
+        assert_eq!(contents, ["q2", "a2"]);
+    }
+
+    #[test]
+    fn finds_cited_labels() {
+        let cited = cited_labels("See [Q3], [D7] and [Q12], not [Qx], [Q], [X1] or [Q4");
+        assert_eq!(
+            cited,
+            HashSet::from(["Q3".to_string(), "D7".to_string(), "Q12".to_string()])
+        );
+    }
+
+    #[test]
+    fn strips_cited_labels() {
+        assert_eq!(
+            strip_citations("Use filters [Q3][D7]. Not [Qx] or [X1], see [Q12]\nDone [Q4"),
+            "Use filters. Not [Qx] or [X1], see\nDone [Q4"
+        );
+    }
+}
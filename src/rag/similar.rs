@@ -0,0 +1,226 @@
+use handle_errors::Error;
+use std::sync::Arc;
+
+use super::Retriever;
+use crate::config::Config;
+use crate::embedding::{cosine, Embedder};
+use crate::types::chat::{RetrievalFilter, SourceType};
+use crate::types::question::SimilarQuestion;
+
+/// 다시 매기기 전에 이만큼 더 넉넉히 가져온다
+const CANDIDATE_FACTOR: usize = 3;
+
+/// 설정한 검색기로 후보 질문을 찾은 뒤 임베딩 코사인 유사도로 다시 매긴다
+/// 검색기마다 점수의 범위가 달라서 그대로는 중복인지 가를 수 없기 때문이다
+#[derive(Clone)]
+pub struct SimilarQuestions {
+    retriever: Arc<dyn Retriever>,
+    embedder: Arc<dyn Embedder>,
+    limit: usize,
+    /// 중복을 막지 않으면 None
+    duplicate_min_score: Option<f32>,
+}
+
+impl SimilarQuestions {
+    pub fn new(
+        config: &Config,
+        retriever: Arc<dyn Retriever>,
+        embedder: Arc<dyn Embedder>,
+    ) -> Self {
+        SimilarQuestions {
+            retriever,
+            embedder,
+            limit: config.similar_questions_limit,
+            duplicate_min_score: config
+                .reject_duplicates
+                .then_some(config.duplicate_min_score),
+        }
+    }
+
+    /// 유사도가 높은 순서로 limit 개까지 (없으면 설정값)
+    pub async fn find(
+        &self,
+        title: &str,
+        content: &str,
+        limit: Option<usize>,
+    ) -> Result<Vec<SimilarQuestion>, Error> {
+        let limit = limit.unwrap_or(self.limit);
+        // 저장한 질문을 검색하고 임베딩할 때와 같은 모양으로 만든다
+        let draft = format!("{} {}", title, content);
+        let filter = RetrievalFilter {
+            source_type: Some(vec![SourceType::Question]),
+            updated_after: None,
+        };
+        let candidates = self
+            .retriever
+            .retrieve(&draft, limit.saturating_mul(CANDIDATE_FACTOR), &filter)
+            .await?;
+        if candidates.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut texts = vec![draft];
+        texts.extend(
+            candidates
+                .iter()
+                .map(|passage| format!("{} {}", passage.title, passage.content)),
+        );
+        let mut vectors = self.embedder.embed(texts).await?.into_iter();
+        let draft = match vectors.next() {
+            Some(draft) => draft,
+            None => return Ok(Vec::new()),
+        };
+
+        let mut similar: Vec<SimilarQuestion> = candidates
+            .into_iter()
+            .zip(vectors)
+            .filter_map(|(passage, vector)| {
+                Some(SimilarQuestion {
+                    id: passage.question_id?,
+                    title: passage.title,
+                    score: cosine(&draft, &vector),
+                })
+            })
+            .collect();
+        similar.sort_by(|a, b| b.score.total_cmp(&a.score));
+        similar.truncate(limit);
+        Ok(similar)
+    }
+
+    /// 중복일 수 있을 만큼 비슷한 질문 (중복을 막지 않으면 찾아보지 않고 비어 있다)
+    pub async fn duplicates(
+        &self,
+        title: &str,
+        content: &str,
+    ) -> Result<Vec<SimilarQuestion>, Error> {
+        let Some(min_score) = self.duplicate_min_score else {
+            return Ok(Vec::new());
+        };
+
+        let mut similar = self.find(title, content, None).await?;
+        similar.retain(|question| question.score >= min_score);
+        Ok(similar)
+    }
+}
+
+#[cfg(test)]
+mod similar_tests {
+    use super::*;
+    use crate::embedding::HashingEmbedder;
+    use crate::types::chat::Passage;
+    use crate::types::question::QuestionId;
+    use async_trait::async_trait;
+    use chrono::Utc;
+    use clap::Parser;
+    use std::sync::Mutex;
+
+    /// 정해 둔 질문을 돌려주고 받은 필터를 남긴다
+    struct Fixed {
+        titles: Vec<&'static str>,
+        filters: Mutex<Vec<RetrievalFilter>>,
+    }
+
+    #[async_trait]
+    impl Retriever for Fixed {
+        async fn retrieve(
+            &self,
+            _query: &str,
+            top_k: usize,
+            filter: &RetrievalFilter,
+        ) -> Result<Vec<Passage>, Error> {
+            self.filters.lock().unwrap().push(filter.clone());
+            Ok(self
+                .titles
+                .iter()
+                .enumerate()
+                .take(top_k)
+                .map(|(i, title)| Passage {
+                    source: SourceType::Question,
+                    question_id: Some(QuestionId(i as i32 + 1)),
+                    answer_id: None,
+                    document_id: None,
+                    chunk_id: None,
+                    title: title.to_string(),
+                    content: "How can I test?".to_string(),
+                    updated_at: Utc::now(),
+                    // 검색기 점수는 다시 매기므로 쓰지 않는다
+                    score: 100.0,
+                })
+                .collect())
+        }
+    }
+
+    fn similar(titles: Vec<&'static str>) -> (SimilarQuestions, Arc<Fixed>) {
+        let retriever = Arc::new(Fixed {
+            titles,
+            filters: Mutex::new(Vec::new()),
+        });
+        let similar = SimilarQuestions::new(
+            &Config::parse_from(["test", "--reject-duplicates"]),
+            retriever.clone(),
+            Arc::new(HashingEmbedder::new(256)),
+        );
+        (similar, retriever)
+    }
+
+    #[tokio::test]
+    async fn reranks_questions_by_similarity() {
+        let (similar, retriever) = similar(vec![
+            "Deploying to kubernetes",
+            "Composing warp filters",
+            "Testing warp filters",
+        ]);
+
+        let found = similar
+            .find("Composing warp filters", "How can I test?", Some(2))
+            .await
+            .unwrap();
+
+        let ids: Vec<i32> = found.iter().map(|question| question.id.0).collect();
+        assert_eq!(ids, [2, 3]);
+        assert!(found[0].score > 0.99);
+        assert!(found[1].score < found[0].score);
+        assert_eq!(
+            retriever.filters.lock().unwrap()[0].source_type,
+            Some(vec![SourceType::Question])
+        );
+    }
+
+    #[tokio::test]
+    async fn only_close_questions_are_duplicates() {
+        let (similar, _) = similar(vec!["Deploying to kubernetes", "Composing warp filters"]);
+
+        let duplicates = similar
+            .duplicates("Composing warp filters", "How can I test?")
+            .await
+            .unwrap();
+        assert_eq!(duplicates.len(), 1);
+        assert_eq!(duplicates[0].id, QuestionId(2));
+
+        let duplicates = similar
+            .duplicates("Tuning postgres indexes", "Which index should I add?")
+            .await
+            .unwrap();
+        assert!(duplicates.is_empty());
+    }
+
+    #[tokio::test]
+    async fn duplicates_are_allowed_by_default() {
+        let retriever = Arc::new(Fixed {
+            titles: vec!["Composing warp filters"],
+            filters: Mutex::new(Vec::new()),
+        });
+        let similar = SimilarQuestions::new(
+            &Config::parse_from(["test"]),
+            retriever.clone(),
+            Arc::new(HashingEmbedder::new(256)),
+        );
+
+        let duplicates = similar
+            .duplicates("Composing warp filters", "How can I test?")
+            .await
+            .unwrap();
+        assert!(duplicates.is_empty());
+        assert!(retriever.filters.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,93 @@
+use async_trait::async_trait;
+use handle_errors::Error;
+use std::sync::Arc;
+
+use super::Retriever;
+use crate::config::Config;
+use crate::embedding::Embedder;
+use crate::types::chat::{Passage, RetrievalFilter};
+use crate::vector_store::VectorStore;
+
+/// 질문을 임베딩해서 벡터 저장소에서 가까운 글을 찾는다
+pub struct VectorRetriever {
+    embedder: Arc<dyn Embedder>,
+    vectors: Arc<dyn VectorStore>,
+    min_score: f32,
+}
+
+impl VectorRetriever {
+    pub fn new(
+        config: &Config,
+        embedder: Arc<dyn Embedder>,
+        vectors: Arc<dyn VectorStore>,
+    ) -> Self {
+        VectorRetriever {
+            embedder,
+            vectors,
+            min_score: config.vector_min_score,
+        }
+    }
+}
+
+#[async_trait]
+impl Retriever for VectorRetriever {
+    async fn retrieve(
+        &self,
+        query: &str,
+        top_k: usize,
+        filter: &RetrievalFilter,
+    ) -> Result<Vec<Passage>, Error> {
+        let query = match self.embedder.embed(vec![query.to_string()]).await?.pop() {
+            Some(query) => query,
+            None => return Ok(Vec::new()),
+        };
+        self.vectors
+            .search(self.embedder.model(), &query, top_k, self.min_score, filter)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod vector_tests {
+    use super::*;
+    use crate::embedding::HashingEmbedder;
+    use crate::types::{chat::SourceType, embedding::EmbeddingJob, question::QuestionId};
+    use crate::vector_store::InMemoryVectorStore;
+    use chrono::Utc;
+    use clap::Parser;
+
+    #[tokio::test]
+    async fn embeds_query_with_the_same_model() {
+        let embedder = Arc::new(HashingEmbedder::new(64));
+        let vectors = Arc::new(InMemoryVectorStore::new());
+        let passage = Passage {
+            source: SourceType::Question,
+            question_id: Some(QuestionId(1)),
+            answer_id: None,
+            document_id: None,
+            chunk_id: None,
+            title: "Composing warp filters".to_string(),
+            content: "How?".to_string(),
+            updated_at: Utc::now(),
+            score: 0.0,
+        };
+        let text = "Composing warp filters How?".to_string();
+        let vector = embedder.vectorize(&text);
+        vectors
+            .upsert(
+                embedder.model(),
+                &[(EmbeddingJob { passage, text }, vector)],
+            )
+            .await
+            .unwrap();
+
+        let retriever = VectorRetriever::new(&Config::parse_from(["test"]), embedder, vectors);
+        let found = retriever
+            .retrieve("warp filters", 5, &RetrievalFilter::default())
+            .await
+            .unwrap();
+
+        assert_eq!(found.len(), 1);
+        assert!(found[0].score > 0.5);
+    }
+}
@@ -0,0 +1,674 @@
+//! 외부 API 호출을 위한 서킷 브레이커, 재시도, 벌크헤드
+//!
+//! 세 가지 모두 `Clone` 이 가벼워서 앱 상태에 하나만 만들고 복제해서 쓴다.
+//! 내부 락은 await 너머로 들고 있지 않으므로 tokio 런타임 안에서 안전하게 쓸 수 있다.
+//! 시간은 `tokio::time` 을 쓰므로 테스트에서 멈춘 시계(`start_paused`)로 결정적으로 돌릴 수 있다.
+
+use rand::Rng;
+use std::fmt;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::Duration;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tokio::time::{sleep, timeout, Instant};
+
+/// 보호된 호출이 실패한 이유
+#[derive(Debug)]
+pub enum Failure<E> {
+    /// 서킷이 열려 있어서 호출하지 않았다
+    CircuitOpen,
+    /// 동시 실행 한도가 차서 호출하지 않았다
+    Rejected,
+    /// 시도 한 번이 제한 시간을 넘겼다
+    Timeout,
+    /// 작업이 돌려준 에러
+    Inner(E),
+}
+
+impl<E: fmt::Display> fmt::Display for Failure<E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Failure::CircuitOpen => write!(f, "circuit is open"),
+            Failure::Rejected => write!(f, "too many concurrent calls"),
+            Failure::Timeout => write!(f, "attempt timed out"),
+            Failure::Inner(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    /// 정상 작동 - 호출 허용
+    Closed,
+    /// 실패가 쌓임 - 호출 차단
+    Open,
+    /// 시험 단계 - 제한된 수의 호출만 허용
+    HalfOpen,
+}
+
+impl fmt::Display for CircuitState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CircuitState::Closed => write!(f, "closed"),
+            CircuitState::Open => write!(f, "open"),
+            CircuitState::HalfOpen => write!(f, "half_open"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CircuitBreakerConfig {
+    /// 연속으로 이만큼 실패하면 서킷을 연다
+    pub failure_threshold: u32,
+    /// 서킷을 연 뒤 half-open 으로 바꾸기까지의 시간
+    pub reset_timeout: Duration,
+    /// half-open 상태에서 동시에 보낼 수 있는 시험 호출 수
+    pub half_open_max_probes: u32,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        CircuitBreakerConfig {
+            failure_threshold: 5,
+            reset_timeout: Duration::from_secs(30),
+            half_open_max_probes: 1,
+        }
+    }
+}
+
+/// 지금까지의 카운터 (모니터링용 스냅숏)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CircuitBreakerMetrics {
+    pub state: CircuitState,
+    pub successes: u64,
+    pub failures: u64,
+    /// 서킷이 열려 있어서 거절한 호출 수
+    pub rejected: u64,
+    /// 서킷이 열린 횟수
+    pub opened: u64,
+}
+
+#[derive(Debug)]
+struct BreakerState {
+    state: CircuitState,
+    consecutive_failures: u32,
+    opened_at: Option<Instant>,
+    probes_in_flight: u32,
+    /// half-open 에 들어갈 때마다 증가한다 (이전 구간의 시험 호출을 구별한다)
+    generation: u64,
+}
+
+#[derive(Debug)]
+struct BreakerInner {
+    name: String,
+    config: CircuitBreakerConfig,
+    state: Mutex<BreakerState>,
+    successes: AtomicU64,
+    failures: AtomicU64,
+    rejected: AtomicU64,
+    opened: AtomicU64,
+}
+
+#[derive(Debug, Clone)]
+pub struct CircuitBreaker {
+    inner: Arc<BreakerInner>,
+}
+
+impl CircuitBreaker {
+    pub fn new(name: impl Into<String>, config: CircuitBreakerConfig) -> Self {
+        CircuitBreaker {
+            inner: Arc::new(BreakerInner {
+                name: name.into(),
+                config,
+                state: Mutex::new(BreakerState {
+                    state: CircuitState::Closed,
+                    consecutive_failures: 0,
+                    opened_at: None,
+                    probes_in_flight: 0,
+                    generation: 0,
+                }),
+                successes: AtomicU64::new(0),
+                failures: AtomicU64::new(0),
+                rejected: AtomicU64::new(0),
+                opened: AtomicU64::new(0),
+            }),
+        }
+    }
+
+    /// 현재 상태 (reset_timeout 이 지났으면 half-open 으로 본다)
+    pub fn state(&self) -> CircuitState {
+        let mut state = self.lock();
+        self.refresh(&mut state);
+        state.state
+    }
+
+    pub fn metrics(&self) -> CircuitBreakerMetrics {
+        CircuitBreakerMetrics {
+            state: self.state(),
+            successes: self.inner.successes.load(Ordering::Relaxed),
+            failures: self.inner.failures.load(Ordering::Relaxed),
+            rejected: self.inner.rejected.load(Ordering::Relaxed),
+            opened: self.inner.opened.load(Ordering::Relaxed),
+        }
+    }
+
+    /// 호출해도 되면 결과를 기록할 퍼밋을 돌려준다
+    /// 퍼밋을 기록 없이 버리면(취소된 요청 등) 결과에 반영하지 않고 시험 호출 자리만 돌려준다
+    pub fn try_acquire(&self) -> Option<BreakerPermit> {
+        let mut state = self.lock();
+        self.refresh(&mut state);
+
+        match state.state {
+            CircuitState::Closed => Some(BreakerPermit::new(self.clone(), None)),
+            CircuitState::HalfOpen
+                if state.probes_in_flight < self.inner.config.half_open_max_probes =>
+            {
+                state.probes_in_flight += 1;
+                Some(BreakerPermit::new(self.clone(), Some(state.generation)))
+            }
+            _ => {
+                self.inner.rejected.fetch_add(1, Ordering::Relaxed);
+                tracing::event!(
+                    tracing::Level::DEBUG,
+                    breaker = %self.inner.name,
+                    state = %state.state,
+                    "call rejected by circuit breaker"
+                );
+                None
+            }
+        }
+    }
+
+    /// `is_failure` 가 true 를 돌려주는 에러만 실패로 센다
+    /// (잘못된 요청처럼 상대가 살아 있다는 뜻인 에러는 성공으로 본다)
+    pub async fn call<F, T, E>(
+        &self,
+        operation: F,
+        is_failure: impl Fn(&E) -> bool,
+    ) -> Result<T, Failure<E>>
+    where
+        F: Future<Output = Result<T, Failure<E>>>,
+    {
+        let permit = self.try_acquire().ok_or(Failure::CircuitOpen)?;
+        let result = operation.await;
+        match &result {
+            Err(Failure::Timeout) => permit.failure(),
+            Err(Failure::Inner(e)) if is_failure(e) => permit.failure(),
+            // 호출하지 못했으므로 결과에 반영하지 않는다
+            Err(Failure::CircuitOpen | Failure::Rejected) => drop(permit),
+            _ => permit.success(),
+        }
+        result
+    }
+
+    fn record(&self, probe: Option<u64>, success: bool) {
+        let mut state = self.lock();
+        let probe = self.release_probe(&mut state, probe);
+
+        if success {
+            self.inner.successes.fetch_add(1, Ordering::Relaxed);
+            state.consecutive_failures = 0;
+            // 서킷이 열리기 전에 시작한 호출의 성공으로는 닫지 않는다
+            if state.state == CircuitState::HalfOpen && probe {
+                self.transition(&mut state, CircuitState::Closed);
+            }
+            return;
+        }
+
+        self.inner.failures.fetch_add(1, Ordering::Relaxed);
+        state.consecutive_failures += 1;
+        let trip = match state.state {
+            CircuitState::Closed => {
+                state.consecutive_failures >= self.inner.config.failure_threshold
+            }
+            // 시험 호출이 실패하면 바로 다시 연다
+            CircuitState::HalfOpen => probe,
+            CircuitState::Open => false,
+        };
+        if trip {
+            state.opened_at = Some(Instant::now());
+            self.transition(&mut state, CircuitState::Open);
+        }
+    }
+
+    fn refresh(&self, state: &mut BreakerState) {
+        if state.state != CircuitState::Open {
+            return;
+        }
+        if let Some(opened_at) = state.opened_at {
+            if opened_at.elapsed() >= self.inner.config.reset_timeout {
+                state.probes_in_flight = 0;
+                state.generation += 1;
+                self.transition(state, CircuitState::HalfOpen);
+            }
+        }
+    }
+
+    fn transition(&self, state: &mut BreakerState, to: CircuitState) {
+        let from = state.state;
+        state.state = to;
+        if to == CircuitState::Open {
+            self.inner.opened.fetch_add(1, Ordering::Relaxed);
+        }
+
+        if to == CircuitState::Open {
+            tracing::event!(
+                tracing::Level::WARN,
+                breaker = %self.inner.name,
+                %from,
+                %to,
+                consecutive_failures = state.consecutive_failures,
+                "circuit breaker state changed"
+            );
+        } else {
+            tracing::event!(
+                tracing::Level::INFO,
+                breaker = %self.inner.name,
+                %from,
+                %to,
+                "circuit breaker state changed"
+            );
+        }
+    }
+
+    /// 현재 half-open 구간의 시험 호출이면 자리를 돌려주고 true 를 돌려준다
+    fn release_probe(&self, state: &mut BreakerState, probe: Option<u64>) -> bool {
+        match probe {
+            Some(generation)
+                if generation == state.generation && state.state == CircuitState::HalfOpen =>
+            {
+                state.probes_in_flight = state.probes_in_flight.saturating_sub(1);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// 락을 await 너머로 들고 있지 않으므로 std Mutex 로 충분하다
+    fn lock(&self) -> MutexGuard<'_, BreakerState> {
+        self.inner
+            .state
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// 호출 한 번의 결과를 서킷 브레이커에 기록한다
+#[must_use]
+pub struct BreakerPermit {
+    breaker: CircuitBreaker,
+    /// 시험 호출이면 그 half-open 구간의 번호
+    probe: Option<u64>,
+    recorded: bool,
+}
+
+impl BreakerPermit {
+    fn new(breaker: CircuitBreaker, probe: Option<u64>) -> Self {
+        BreakerPermit {
+            breaker,
+            probe,
+            recorded: false,
+        }
+    }
+
+    pub fn success(mut self) {
+        self.recorded = true;
+        self.breaker.record(self.probe, true);
+    }
+
+    pub fn failure(mut self) {
+        self.recorded = true;
+        self.breaker.record(self.probe, false);
+    }
+}
+
+impl Drop for BreakerPermit {
+    fn drop(&mut self) {
+        if !self.recorded {
+            let mut state = self.breaker.lock();
+            self.breaker.release_probe(&mut state, self.probe);
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// 첫 시도 뒤에 다시 시도할 횟수 (전체 시도 횟수는 max_retries + 1)
+    pub max_retries: u32,
+    /// 첫 재시도 전 대기 시간, 이후 두 배씩 늘어난다
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    /// 시도 한 번의 제한 시간
+    pub attempt_timeout: Option<Duration>,
+    /// 대기 시간을 0..=delay 사이에서 무작위로 고른다 (여러 클라이언트가 동시에 재시도하지 않도록)
+    pub jitter: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_retries: 2,
+            base_delay: Duration::from_millis(200),
+            max_delay: Duration::from_secs(5),
+            attempt_timeout: Some(Duration::from_secs(5)),
+            jitter: true,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// retry 번째 재시도 전에 기다릴 시간 (1부터 시작)
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let exponent = retry.saturating_sub(1).min(31);
+        let delay = self
+            .base_delay
+            .saturating_mul(1u32 << exponent)
+            .min(self.max_delay);
+        if self.jitter {
+            let millis = delay.as_millis() as u64;
+            Duration::from_millis(rand::thread_rng().gen_range(0..=millis))
+        } else {
+            delay
+        }
+    }
+}
+
+/// 작업을 정책에 따라 다시 시도한다
+/// `is_retryable` 이 false 를 돌려주는 에러는 바로 돌려주고, 시간 초과는 항상 다시 시도한다
+pub async fn retry<F, Fut, T, E>(
+    policy: &RetryPolicy,
+    mut operation: F,
+    is_retryable: impl Fn(&E) -> bool,
+) -> Result<T, Failure<E>>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+    E: fmt::Display,
+{
+    let mut retries = 0;
+
+    loop {
+        let result = match policy.attempt_timeout {
+            Some(limit) => match timeout(limit, operation()).await {
+                Ok(result) => result.map_err(Failure::Inner),
+                Err(_) => Err(Failure::Timeout),
+            },
+            None => operation().await.map_err(Failure::Inner),
+        };
+
+        let error = match result {
+            Ok(value) => return Ok(value),
+            Err(Failure::Inner(e)) if !is_retryable(&e) => return Err(Failure::Inner(e)),
+            Err(error) => error,
+        };
+
+        if retries >= policy.max_retries {
+            tracing::event!(
+                tracing::Level::WARN,
+                attempts = retries + 1,
+                "giving up after retries: {}",
+                error
+            );
+            return Err(error);
+        }
+
+        retries += 1;
+        let delay = policy.backoff(retries);
+        tracing::event!(
+            tracing::Level::DEBUG,
+            retry = retries,
+            delay_ms = delay.as_millis() as u64,
+            "retrying after error: {}",
+            error
+        );
+        sleep(delay).await;
+    }
+}
+
+/// 동시 실행 수를 제한해서 느린 외부 API 가 모든 워커를 붙잡지 못하게 한다
+#[derive(Debug, Clone)]
+pub struct Bulkhead {
+    name: Arc<str>,
+    semaphore: Arc<Semaphore>,
+    max_wait: Duration,
+    rejected: Arc<AtomicU64>,
+}
+
+impl Bulkhead {
+    /// 자리가 없으면 max_wait 만큼 기다린 뒤 거절한다
+    pub fn new(name: impl Into<String>, max_concurrent: usize, max_wait: Duration) -> Self {
+        Bulkhead {
+            name: Arc::from(name.into()),
+            semaphore: Arc::new(Semaphore::new(max_concurrent)),
+            max_wait,
+            rejected: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    pub async fn acquire(&self) -> Option<OwnedSemaphorePermit> {
+        match timeout(self.max_wait, self.semaphore.clone().acquire_owned()).await {
+            Ok(Ok(permit)) => Some(permit),
+            // 세마포어를 닫지 않으므로 Ok(Err(_)) 는 나오지 않지만 거절로 처리한다
+            _ => {
+                self.rejected.fetch_add(1, Ordering::Relaxed);
+                tracing::event!(
+                    tracing::Level::WARN,
+                    bulkhead = %self.name,
+                    "call rejected by bulkhead"
+                );
+                None
+            }
+        }
+    }
+
+    pub async fn run<F, T, E>(&self, operation: F) -> Result<T, Failure<E>>
+    where
+        F: Future<Output = Result<T, Failure<E>>>,
+    {
+        let _permit = self.acquire().await.ok_or(Failure::Rejected)?;
+        operation.await
+    }
+
+    pub fn available(&self) -> usize {
+        self.semaphore.available_permits()
+    }
+
+    pub fn rejected(&self) -> u64 {
+        self.rejected.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod resilience_tests {
+    use super::*;
+    use std::sync::atomic::AtomicU32;
+
+    fn breaker(failure_threshold: u32, half_open_max_probes: u32) -> CircuitBreaker {
+        CircuitBreaker::new(
+            "test",
+            CircuitBreakerConfig {
+                failure_threshold,
+                reset_timeout: Duration::from_secs(10),
+                half_open_max_probes,
+            },
+        )
+    }
+
+    fn policy(max_retries: u32) -> RetryPolicy {
+        RetryPolicy {
+            max_retries,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(250),
+            attempt_timeout: Some(Duration::from_secs(1)),
+            jitter: false,
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn breaker_opens_and_recovers_through_half_open() {
+        let breaker = breaker(2, 1);
+
+        breaker.try_acquire().unwrap().failure();
+        assert_eq!(breaker.state(), CircuitState::Closed);
+        breaker.try_acquire().unwrap().failure();
+        assert_eq!(breaker.state(), CircuitState::Open);
+        assert!(breaker.try_acquire().is_none());
+
+        tokio::time::advance(Duration::from_secs(10)).await;
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+
+        let probe = breaker.try_acquire().unwrap();
+        // 시험 호출이 끝나기 전에는 더 보내지 않는다
+        assert!(breaker.try_acquire().is_none());
+        probe.success();
+
+        assert_eq!(breaker.state(), CircuitState::Closed);
+        assert_eq!(
+            breaker.metrics(),
+            CircuitBreakerMetrics {
+                state: CircuitState::Closed,
+                successes: 1,
+                failures: 2,
+                rejected: 2,
+                opened: 1,
+            }
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn failed_probe_reopens() {
+        let breaker = breaker(1, 2);
+        breaker.try_acquire().unwrap().failure();
+        tokio::time::advance(Duration::from_secs(10)).await;
+
+        let first = breaker.try_acquire().unwrap();
+        let second = breaker.try_acquire().unwrap();
+        assert!(breaker.try_acquire().is_none());
+        first.failure();
+        assert_eq!(breaker.state(), CircuitState::Open);
+        // 열린 뒤에 끝난 시험 호출은 상태를 바꾸지 않는다
+        drop(second);
+        assert_eq!(breaker.state(), CircuitState::Open);
+
+        tokio::time::advance(Duration::from_secs(9)).await;
+        assert_eq!(breaker.state(), CircuitState::Open);
+        tokio::time::advance(Duration::from_secs(1)).await;
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn dropped_probe_frees_its_slot() {
+        let breaker = breaker(1, 1);
+        breaker.try_acquire().unwrap().failure();
+        tokio::time::advance(Duration::from_secs(10)).await;
+
+        drop(breaker.try_acquire().unwrap());
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+        assert!(breaker.try_acquire().is_some());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn max_retries_does_not_count_first_attempt() {
+        let attempts = AtomicU32::new(0);
+        let started = Instant::now();
+
+        let result: Result<(), _> = retry(
+            &policy(2),
+            || async {
+                attempts.fetch_add(1, Ordering::SeqCst);
+                Err::<(), _>("boom")
+            },
+            |_| true,
+        )
+        .await;
+
+        assert!(matches!(result, Err(Failure::Inner("boom"))));
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+        // 100ms + 200ms
+        assert_eq!(started.elapsed(), Duration::from_millis(300));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn non_retryable_errors_return_immediately() {
+        let attempts = AtomicU32::new(0);
+
+        let result: Result<(), _> = retry(
+            &policy(5),
+            || async {
+                attempts.fetch_add(1, Ordering::SeqCst);
+                Err::<(), _>("bad request")
+            },
+            |_| false,
+        )
+        .await;
+
+        assert!(matches!(result, Err(Failure::Inner("bad request"))));
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn slow_attempts_time_out_and_are_retried() {
+        let attempts = AtomicU32::new(0);
+
+        let result = retry(
+            &policy(1),
+            || async {
+                if attempts.fetch_add(1, Ordering::SeqCst) == 0 {
+                    sleep(Duration::from_secs(60)).await;
+                }
+                Ok::<_, &str>("done")
+            },
+            |_| true,
+        )
+        .await;
+
+        assert_eq!(result.unwrap(), "done");
+        assert_eq!(attempts.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn backoff_is_capped() {
+        let policy = policy(10);
+        assert_eq!(policy.backoff(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2), Duration::from_millis(200));
+        assert_eq!(policy.backoff(3), Duration::from_millis(250));
+        assert_eq!(policy.backoff(40), Duration::from_millis(250));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn breaker_call_only_counts_classified_failures() {
+        let breaker = breaker(1, 1);
+
+        let result: Result<(), Failure<&str>> = breaker
+            .call(async { Err(Failure::Inner("bad request")) }, |_| false)
+            .await;
+        assert!(matches!(result, Err(Failure::Inner(_))));
+        assert_eq!(breaker.state(), CircuitState::Closed);
+
+        let _: Result<(), Failure<&str>> = breaker
+            .call(async { Err(Failure::Timeout) }, |_| false)
+            .await;
+        assert_eq!(breaker.state(), CircuitState::Open);
+
+        let result: Result<(), Failure<&str>> = breaker.call(async { Ok(()) }, |_| true).await;
+        assert!(matches!(result, Err(Failure::CircuitOpen)));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn bulkhead_rejects_after_waiting() {
+        let bulkhead = Bulkhead::new("test", 1, Duration::from_millis(50));
+        let held = bulkhead.acquire().await.unwrap();
+
+        let started = Instant::now();
+        let result: Result<(), Failure<()>> = bulkhead.run(async { Ok(()) }).await;
+        assert!(matches!(result, Err(Failure::Rejected)));
+        assert_eq!(started.elapsed(), Duration::from_millis(50));
+        assert_eq!(bulkhead.rejected(), 1);
+
+        drop(held);
+        assert_eq!(bulkhead.available(), 1);
+        let result: Result<(), Failure<()>> = bulkhead.run(async { Ok(()) }).await;
+        assert!(result.is_ok());
+    }
+}
@@ -1,10 +1,18 @@
-use sqlx::postgres::{PgPool, PgPoolOptions, PgRow};
-use sqlx::Row;
+use sqlx::postgres::{PgPool, PgPoolOptions, PgRow, Postgres};
+use sqlx::{Executor, Row, Transaction};
 
+use crate::llm::Role;
 use crate::types::{
     account::{Account, AccountId},
     answer::{Answer, AnswerId, NewAnswer},
+    chat::{ChatResponse, Passage, RetrievalFilter, SourceType},
+    conversation::{Conversation, ConversationId, Message, MessageId},
+    document::{ChunkId, Document, DocumentId, NewDocument},
+    embedding::EmbeddingJob,
+    moderation::{Decision, Flag, FlagReason, ModerationStatus, QueueItem, QueueItemId},
+    notification::{Notification, NotificationId},
     question::{NewQuestion, Question, QuestionId},
+    revision::{Revision, RevisionId},
 };
 
 use handle_errors::Error;
@@ -15,15 +23,25 @@ pub struct Store {
 }
 
 impl Store {
-    pub async fn new(db_url: &str) -> Result<Self, sqlx::Error> {
-        let db_pool = match PgPoolOptions::new()
+    /// schema 를 주면 모든 연결의 search_path 를 그 스키마로 바꾼다 (테스트마다 따로 쓸 때)
+    /// pgvector 같은 확장은 public 에 있으므로 public 을 뒤에 붙인다
+    /// 연결하지 못하면 에러를 돌려주어 부른 쪽이 끝낼지 건너뛸지 정하게 한다
+    pub async fn new(db_url: &str, schema: Option<String>) -> Result<Self, sqlx::Error> {
+        let db_pool = PgPoolOptions::new()
             .max_connections(5)
+            .after_connect(move |conn| {
+                let schema = schema.clone();
+                Box::pin(async move {
+                    if let Some(schema) = schema {
+                        let schema = schema.replace('"', "\"\"");
+                        conn.execute(format!("SET search_path TO \"{}\", public", schema).as_str())
+                            .await?;
+                    }
+                    Ok(())
+                })
+            })
             .connect(db_url)
-            .await
-        {
-            Ok(pool) => pool,
-            Err(e) => panic!("DB 연결을 하지 못했습니다: {}", e),
-        };
+            .await?;
 
         Ok(Store {
             connection: db_pool,
@@ -35,7 +53,7 @@ impl Store {
         limit: Option<u32>,
         offset: u32,
     ) -> Result<Vec<Question>, Error> {
-        match sqlx::query("SELECT * from questions LIMIT $1 OFFSET $2")
+        match sqlx::query("SELECT * from questions WHERE deleted_at IS NULL AND status = 'published' LIMIT $1 OFFSET $2")
             .bind(limit)
             .bind(offset)
             .map(|row: PgRow| Question {
@@ -43,6 +61,8 @@ impl Store {
                 title: row.get("title"),
                 content: row.get("content"),
                 tags: row.get("tags"),
+                version: row.get("version"),
+                status: ModerationStatus::from_db(row.get("status")),
             })
             .fetch_all(&self.connection)
             .await
@@ -55,30 +75,76 @@ impl Store {
         }
     }
 
+    pub async fn get_question(&self, question_id: i32) -> Result<Question, Error> {
+        match sqlx::query("SELECT * from questions WHERE id = $1 AND deleted_at IS NULL")
+            .bind(question_id)
+            .map(|row: PgRow| Question {
+                id: QuestionId(row.get("id")),
+                title: row.get("title"),
+                content: row.get("content"),
+                tags: row.get("tags"),
+                version: row.get("version"),
+                status: ModerationStatus::from_db(row.get("status")),
+            })
+            .fetch_one(&self.connection)
+            .await
+        {
+            Ok(question) => Ok(question),
+            Err(sqlx::Error::RowNotFound) => Err(Error::NotFound),
+            Err(error) => {
+                tracing::event!(tracing::Level::ERROR, "{:?}", error);
+                Err(Error::DatabaseQueryError(error))
+            }
+        }
+    }
+
     pub async fn add_question(
         &self,
         new_question: NewQuestion,
         account_id: AccountId,
+        flag: Option<Flag>,
     ) -> Result<Question, Error> {
-        match sqlx::query(
-            "INSERT INTO questions (title, content, tags, account_id)
-             VALUES ($1, $2, $3, $4)
-             RETURNING id, title, content, tags",
+        let status = ModerationStatus::from_flag(&flag);
+        let mut tx = self.connection.begin().await.map_err(|error| {
+            tracing::event!(tracing::Level::ERROR, "{:?}", error);
+            Error::DatabaseQueryError(error)
+        })?;
+
+        let question = match sqlx::query(
+            "INSERT INTO questions (title, content, tags, account_id, status)
+             VALUES ($1, $2, $3, $4, $5)
+             RETURNING id, title, content, tags, version, status",
         )
         .bind(new_question.title)
         .bind(new_question.content)
         .bind(new_question.tags)
         .bind(account_id.0)
+        .bind(status.as_str())
         .map(|row: PgRow| Question {
             id: QuestionId(row.get("id")),
             title: row.get("title"),
             content: row.get("content"),
             tags: row.get("tags"),
+            version: row.get("version"),
+            status: ModerationStatus::from_db(row.get("status")),
         })
-        .fetch_one(&self.connection)
+        .fetch_one(&mut tx)
         .await
         {
-            Ok(question) => Ok(question),
+            Ok(question) => question,
+            Err(error) => {
+                tracing::event!(tracing::Level::ERROR, "{:?}", error);
+                return Err(Error::DatabaseQueryError(error));
+            }
+        };
+
+        Self::add_revision(&mut tx, &question, &account_id, ModerationStatus::Published).await?;
+        if let Some(flag) = flag {
+            Self::enqueue(&mut tx, Some(question.id.0), None, None, flag).await?;
+        }
+
+        match tx.commit().await {
+            Ok(_) => Ok(question),
             Err(error) => {
                 tracing::event!(tracing::Level::ERROR, "{:?}", error);
                 Err(Error::DatabaseQueryError(error))
@@ -86,33 +152,210 @@ impl Store {
         }
     }
 
+    /// 공개한 질문의 수정이 검토 대기열에 가면 질문은 그대로 두고 수정을 pending 이력으로만 남긴다
+    /// 이때 돌려주는 질문은 남긴 수정에 pending 상태이고 버전은 그대로다
     pub async fn update_question(
         &self,
         question: Question,
         question_id: i32,
         account_id: AccountId,
+        expected_version: Option<i32>,
+        flag: Option<Flag>,
     ) -> Result<Question, Error> {
+        let status = ModerationStatus::from_flag(&flag);
+        let mut tx = self.connection.begin().await.map_err(|error| {
+            tracing::event!(tracing::Level::ERROR, "{:?}", error);
+            Error::DatabaseQueryError(error)
+        })?;
+
+        let current = match sqlx::query(
+            "SELECT version, status FROM questions
+             WHERE id = $1 and account_id = $2 and deleted_at IS NULL
+             and ($3::integer IS NULL or version = $3)
+             FOR UPDATE",
+        )
+        .bind(question_id)
+        .bind(account_id.0)
+        .bind(expected_version)
+        .map(|row: PgRow| {
+            (
+                row.get::<i32, _>("version"),
+                ModerationStatus::from_db(row.get("status")),
+            )
+        })
+        .fetch_one(&mut tx)
+        .await
+        {
+            Ok(current) => current,
+            // 질문이 남아 있다면 그 사이 다른 곳에서 버전이 바뀐 것이다
+            Err(sqlx::Error::RowNotFound) => {
+                return match self.question_exists(question_id).await? {
+                    true => Err(Error::PreconditionFailed),
+                    false => Err(Error::NotFound),
+                }
+            }
+            Err(error) => {
+                tracing::event!(tracing::Level::ERROR, "{:?}", error);
+                return Err(Error::DatabaseQueryError(error));
+            }
+        };
+
+        // 새 수정이 들어오면 아직 검토하지 않은 이전 수정은 버린다
+        Self::discard_pending_revisions(&mut tx, question_id).await?;
+
+        let question = match (flag, current) {
+            (Some(flag), (version, ModerationStatus::Published)) => {
+                let question = Question {
+                    id: QuestionId(question_id),
+                    version,
+                    status: ModerationStatus::Pending,
+                    ..question
+                };
+                let revision_id =
+                    Self::add_revision(&mut tx, &question, &account_id, ModerationStatus::Pending)
+                        .await?;
+                Self::enqueue(&mut tx, Some(question_id), None, Some(revision_id), flag).await?;
+                question
+            }
+            (flag, _) => {
+                let question = match sqlx::query(
+                    "UPDATE questions
+                     SET title = $1, content = $2, tags = $3, version = version + 1,
+                         status = CASE WHEN $5 = 'published' THEN status ELSE $5 END
+                     WHERE id = $4
+                     RETURNING id, title, content, tags, version, status",
+                )
+                .bind(question.title)
+                .bind(question.content)
+                .bind(question.tags)
+                .bind(question_id)
+                .bind(status.as_str())
+                .map(|row: PgRow| Question {
+                    id: QuestionId(row.get("id")),
+                    title: row.get("title"),
+                    content: row.get("content"),
+                    tags: row.get("tags"),
+                    version: row.get("version"),
+                    status: ModerationStatus::from_db(row.get("status")),
+                })
+                .fetch_one(&mut tx)
+                .await
+                {
+                    Ok(question) => question,
+                    Err(error) => {
+                        tracing::event!(tracing::Level::ERROR, "{:?}", error);
+                        return Err(Error::DatabaseQueryError(error));
+                    }
+                };
+
+                Self::add_revision(&mut tx, &question, &account_id, ModerationStatus::Published)
+                    .await?;
+                if let Some(flag) = flag {
+                    Self::enqueue(&mut tx, Some(question.id.0), None, None, flag).await?;
+                }
+                question
+            }
+        };
+
+        match tx.commit().await {
+            Ok(_) => Ok(question),
+            Err(error) => {
+                tracing::event!(tracing::Level::ERROR, "{:?}", error);
+                Err(Error::DatabaseQueryError(error))
+            }
+        }
+    }
+
+    /// 검토 대기 중인 수정을 rejected 로 남기고 대기열에서 뺀다
+    async fn discard_pending_revisions(
+        tx: &mut Transaction<'_, Postgres>,
+        question_id: i32,
+    ) -> Result<(), Error> {
+        let result = sqlx::query(
+            "DELETE FROM moderation_queue
+             WHERE question_id = $1 AND revision_id IS NOT NULL AND status = 'pending'",
+        )
+        .bind(question_id)
+        .execute(&mut *tx)
+        .await;
+        if let Err(error) = result {
+            tracing::event!(tracing::Level::ERROR, "{:?}", error);
+            return Err(Error::DatabaseQueryError(error));
+        }
+
         match sqlx::query(
-            "UPDATE questions
-             SET title = $1, content = $2, tags = $3
-             WHERE id = $4 and account_id = $5
-             RETURNING id, title, content, tags",
+            "UPDATE question_revisions SET status = 'rejected'
+             WHERE question_id = $1 AND status = 'pending'",
         )
-        .bind(question.title)
-        .bind(question.content)
-        .bind(question.tags)
         .bind(question_id)
+        .execute(&mut *tx)
+        .await
+        {
+            Ok(_) => Ok(()),
+            Err(error) => {
+                tracing::event!(tracing::Level::ERROR, "{:?}", error);
+                Err(Error::DatabaseQueryError(error))
+            }
+        }
+    }
+
+    /// 질문에 반영한 수정은 published, 검토를 기다리는 수정은 pending 으로 남긴다
+    async fn add_revision(
+        tx: &mut Transaction<'_, Postgres>,
+        question: &Question,
+        account_id: &AccountId,
+        status: ModerationStatus,
+    ) -> Result<i32, Error> {
+        match sqlx::query(
+            "INSERT INTO question_revisions (question_id, title, content, tags, account_id, status)
+             VALUES ($1, $2, $3, $4, $5, $6)
+             RETURNING id",
+        )
+        .bind(question.id.0)
+        .bind(&question.title)
+        .bind(&question.content)
+        .bind(&question.tags)
         .bind(account_id.0)
-        .map(|row: PgRow| Question {
-            id: QuestionId(row.get("id")),
+        .bind(status.as_str())
+        .map(|row: PgRow| row.get("id"))
+        .fetch_one(tx)
+        .await
+        {
+            Ok(id) => Ok(id),
+            Err(error) => {
+                tracing::event!(tracing::Level::ERROR, "{:?}", error);
+                Err(Error::DatabaseQueryError(error))
+            }
+        }
+    }
+
+    /// include_held 가 아니면 질문에 반영한 수정만 돌려준다
+    pub async fn get_question_revisions(
+        &self,
+        question_id: i32,
+        include_held: bool,
+    ) -> Result<Vec<Revision>, Error> {
+        match sqlx::query(
+            "SELECT * from question_revisions
+             WHERE question_id = $1 AND ($2 OR status = 'published')
+             ORDER BY id",
+        )
+        .bind(question_id)
+        .bind(include_held)
+        .map(|row: PgRow| Revision {
+            id: RevisionId(row.get("id")),
+            question_id: QuestionId(row.get("question_id")),
             title: row.get("title"),
             content: row.get("content"),
             tags: row.get("tags"),
+            account_id: AccountId(row.get("account_id")),
+            created_on: row.get("created_on"),
+            status: ModerationStatus::from_db(row.get("status")),
         })
-        .fetch_one(&self.connection)
+        .fetch_all(&self.connection)
         .await
         {
-            Ok(question) => Ok(question),
+            Ok(revisions) => Ok(revisions),
             Err(error) => {
                 tracing::event!(tracing::Level::ERROR, "{:?}", error);
                 Err(Error::DatabaseQueryError(error))
@@ -125,13 +368,33 @@ impl Store {
         question_id: i32,
         account_id: AccountId,
     ) -> Result<bool, Error> {
-        match sqlx::query("DELETE FROM questions WHERE id = $1 AND account_id = $2")
-            .bind(question_id)
-            .bind(account_id.0)
-            .execute(&self.connection)
-            .await
+        match sqlx::query(
+            "UPDATE questions SET deleted_at = NOW()
+             WHERE id = $1 AND account_id = $2 AND deleted_at IS NULL",
+        )
+        .bind(question_id)
+        .bind(account_id.0)
+        .execute(&self.connection)
+        .await
         {
-            Ok(_) => Ok(true),
+            Ok(result) => Ok(result.rows_affected() > 0),
+            Err(error) => {
+                tracing::event!(tracing::Level::ERROR, "{:?}", error);
+                Err(Error::DatabaseQueryError(error))
+            }
+        }
+    }
+
+    pub async fn restore_question(&self, question_id: i32) -> Result<bool, Error> {
+        match sqlx::query(
+            "UPDATE questions SET deleted_at = NULL
+             WHERE id = $1 AND deleted_at IS NOT NULL",
+        )
+        .bind(question_id)
+        .execute(&self.connection)
+        .await
+        {
+            Ok(result) => Ok(result.rows_affected() > 0),
             Err(error) => {
                 tracing::event!(tracing::Level::ERROR, "{:?}", error);
                 Err(Error::DatabaseQueryError(error))
@@ -143,24 +406,120 @@ impl Store {
         &self,
         new_answer: NewAnswer,
         account_id: AccountId,
+        flag: Option<Flag>,
     ) -> Result<Answer, Error> {
-        match sqlx::query(
-            "INSERT INTO answers (content, question_id, account_id)
-             VALUES ($1, $2, $3)
-          ",
+        let status = ModerationStatus::from_flag(&flag);
+        let mut tx = self.connection.begin().await.map_err(|error| {
+            tracing::event!(tracing::Level::ERROR, "{:?}", error);
+            Error::DatabaseQueryError(error)
+        })?;
+
+        let answer = match sqlx::query(
+            "INSERT INTO answers (content, corresponding_question, account_id, status)
+             SELECT $1, id, $3, $4 FROM questions
+             WHERE id = $2 AND deleted_at IS NULL AND status = 'published'
+             RETURNING id, content, corresponding_question, status, is_ai",
         )
         .bind(new_answer.content)
         .bind(new_answer.question_id.0)
         .bind(account_id.0)
-        .map(|row: PgRow| Answer {
-            id: AnswerId(row.get("id")),
+        .bind(status.as_str())
+        .map(answer)
+        .fetch_one(&mut tx)
+        .await
+        {
+            Ok(answer) => answer,
+            Err(sqlx::Error::RowNotFound) => return Err(Error::NotFound),
+            Err(error) => {
+                tracing::event!(tracing::Level::ERROR, "{:?}", error);
+                return Err(Error::DatabaseQueryError(error));
+            }
+        };
+
+        if let Some(flag) = flag {
+            Self::enqueue(&mut tx, None, Some(answer.id.0), None, flag).await?;
+        }
+
+        match tx.commit().await {
+            Ok(_) => Ok(answer),
+            Err(error) => {
+                tracing::event!(tracing::Level::ERROR, "{:?}", error);
+                Err(Error::DatabaseQueryError(error))
+            }
+        }
+    }
+
+    pub async fn delete_answer(
+        &self,
+        answer_id: i32,
+        account_id: AccountId,
+    ) -> Result<bool, Error> {
+        match sqlx::query(
+            "UPDATE answers SET deleted_at = NOW()
+             WHERE id = $1 AND account_id = $2 AND deleted_at IS NULL",
+        )
+        .bind(answer_id)
+        .bind(account_id.0)
+        .execute(&self.connection)
+        .await
+        {
+            Ok(result) => Ok(result.rows_affected() > 0),
+            Err(error) => {
+                tracing::event!(tracing::Level::ERROR, "{:?}", error);
+                Err(Error::DatabaseQueryError(error))
+            }
+        }
+    }
+
+    pub async fn restore_answer(&self, answer_id: i32) -> Result<bool, Error> {
+        match sqlx::query(
+            "UPDATE answers SET deleted_at = NULL
+             WHERE id = $1 AND deleted_at IS NOT NULL",
+        )
+        .bind(answer_id)
+        .execute(&self.connection)
+        .await
+        {
+            Ok(result) => Ok(result.rows_affected() > 0),
+            Err(error) => {
+                tracing::event!(tracing::Level::ERROR, "{:?}", error);
+                Err(Error::DatabaseQueryError(error))
+            }
+        }
+    }
+
+    /// 답변이 없는 채로 older_than_mins 분이 지났고 아직 초안을 만들어 보지 않은 질문
+    /// 덜 실패한 것, 오래된 것부터 돌려주어 실패하는 질문이 다른 질문을 막지 않게 한다
+    pub async fn get_undrafted_questions(
+        &self,
+        older_than_mins: u32,
+        limit: i64,
+    ) -> Result<Vec<Question>, Error> {
+        match sqlx::query(
+            "SELECT * FROM questions q
+             WHERE q.deleted_at IS NULL AND q.status = 'published' AND q.ai_drafted_at IS NULL
+               AND q.created_on < NOW() - make_interval(mins => $1)
+               AND NOT EXISTS (
+                   SELECT 1 FROM answers a
+                   WHERE a.corresponding_question = q.id AND a.deleted_at IS NULL
+               )
+             ORDER BY q.ai_draft_attempts, q.created_on
+             LIMIT $2",
+        )
+        .bind(older_than_mins as i32)
+        .bind(limit)
+        .map(|row: PgRow| Question {
+            id: QuestionId(row.get("id")),
+            title: row.get("title"),
             content: row.get("content"),
-            question_id: QuestionId(row.get("corresponding_question")),
+            tags: row.get("tags"),
+            version: row.get("version"),
+            status: ModerationStatus::from_db(row.get("status")),
         })
-        .fetch_one(&self.connection)
+        .fetch_all(&self.connection)
         .await
         {
-            Ok(answer) => Ok(answer),
+            Ok(questions) => Ok(questions),
             Err(error) => {
                 tracing::event!(tracing::Level::ERROR, "{:?}", error);
                 Err(Error::DatabaseQueryError(error))
@@ -168,44 +527,109 @@ impl Store {
         }
     }
 
-    pub async fn add_account(&self, account: Account) -> Result<bool, Error> {
-        match sqlx::query("INSERT INTO accounts (email, password) VALUES ($1, $2)")
-            .bind(account.email)
-            .bind(account.password)
-            .execute(&self.connection)
-            .await
+    /// 질문에 초안을 만들어 봤다고 남기고, 초안이 있으면 모델 계정의 pending 답변으로 저장해서 질문 작성자에게 알린다
+    /// 다른 워커가 먼저 남겼으면 아무것도 하지 않고 None 이다
+    pub async fn add_ai_draft(
+        &self,
+        question_id: &QuestionId,
+        content: Option<String>,
+    ) -> Result<Option<Answer>, Error> {
+        let mut tx = self.connection.begin().await.map_err(|error| {
+            tracing::event!(tracing::Level::ERROR, "{:?}", error);
+            Error::DatabaseQueryError(error)
+        })?;
+
+        let owner = match sqlx::query(
+            "UPDATE questions SET ai_drafted_at = NOW()
+             WHERE id = $1 AND ai_drafted_at IS NULL
+             RETURNING account_id",
+        )
+        .bind(question_id.0)
+        .map(|row: PgRow| AccountId(row.get("account_id")))
+        .fetch_optional(&mut tx)
+        .await
         {
-            Ok(_) => Ok(true),
+            Ok(owner) => owner,
+            Err(error) => {
+                tracing::event!(tracing::Level::ERROR, "{:?}", error);
+                return Err(Error::DatabaseQueryError(error));
+            }
+        };
+
+        let draft = match (owner, content) {
+            (Some(owner), Some(content)) => {
+                let draft = match sqlx::query(
+                    "INSERT INTO answers (content, corresponding_question, account_id, status, is_ai)
+                     SELECT $1, $2, id, 'pending', TRUE FROM accounts WHERE is_ai
+                     ORDER BY id LIMIT 1
+                     RETURNING id, content, corresponding_question, status, is_ai",
+                )
+                .bind(content)
+                .bind(question_id.0)
+                .map(answer)
+                .fetch_one(&mut tx)
+                .await
+                {
+                    Ok(draft) => draft,
+                    Err(sqlx::Error::RowNotFound) => {
+                        return Err(Error::InvalidConfig(
+                            "no AI account exists to author answer drafts".to_string(),
+                        ));
+                    }
+                    Err(error) => {
+                        tracing::event!(tracing::Level::ERROR, "{:?}", error);
+                        return Err(Error::DatabaseQueryError(error));
+                    }
+                };
+
+                let result = sqlx::query(
+                    "INSERT INTO notifications (account_id, question_id, answer_id, message)
+                     VALUES ($1, $2, $3, $4)",
+                )
+                .bind(owner.0)
+                .bind(question_id.0)
+                .bind(draft.id.0)
+                .bind("An AI-drafted answer to your question is waiting for your review")
+                .execute(&mut tx)
+                .await;
+                if let Err(error) = result {
+                    tracing::event!(tracing::Level::ERROR, "{:?}", error);
+                    return Err(Error::DatabaseQueryError(error));
+                }
+                Some(draft)
+            }
+            _ => None,
+        };
+
+        match tx.commit().await {
+            Ok(_) => Ok(draft),
             Err(error) => {
-                tracing::event!(
-                    tracing::Level::ERROR,
-                    code = error
-                        .as_database_error()
-                        .unwrap()
-                        .code()
-                        .unwrap()
-                        .parse::<i32>()
-                        .unwrap(),
-                    db_message = error.as_database_error().unwrap().message(),
-                    constraint = error.as_database_error().unwrap().constraint().unwrap()
-                );
+                tracing::event!(tracing::Level::ERROR, "{:?}", error);
                 Err(Error::DatabaseQueryError(error))
             }
         }
     }
 
-    pub async fn get_account(self, email: String) -> Result<Account, Error> {
-        match sqlx::query("SELECT * from accounts where email = $1")
-            .bind(email)
-            .map(|row: PgRow| Account {
-                id: Some(AccountId(row.get("id"))),
-                email: row.get("email"),
-                password: row.get("password"),
-            })
-            .fetch_one(&self.connection)
-            .await
+    /// 초안을 만들다 실패한 횟수를 늘리고, max_attempts 번 실패했으면 더는 시도하지 않게 남긴다
+    /// 더는 시도하지 않으면 true 다
+    pub async fn record_ai_draft_failure(
+        &self,
+        question_id: &QuestionId,
+        max_attempts: u32,
+    ) -> Result<bool, Error> {
+        match sqlx::query(
+            "UPDATE questions SET ai_draft_attempts = ai_draft_attempts + 1,
+                 ai_drafted_at = CASE WHEN ai_draft_attempts + 1 >= $2 THEN NOW() END
+             WHERE id = $1 AND ai_drafted_at IS NULL
+             RETURNING ai_drafted_at IS NOT NULL AS given_up",
+        )
+        .bind(question_id.0)
+        .bind(max_attempts as i32)
+        .map(|row: PgRow| row.get("given_up"))
+        .fetch_optional(&self.connection)
+        .await
         {
-            Ok(account) => Ok(account),
+            Ok(given_up) => Ok(given_up.unwrap_or(false)),
             Err(error) => {
                 tracing::event!(tracing::Level::ERROR, "{:?}", error);
                 Err(Error::DatabaseQueryError(error))
@@ -213,22 +637,1317 @@ impl Store {
         }
     }
 
-    pub async fn is_question_owner(
+    /// 내 질문에 달린, 아직 받아들이거나 거절하지 않은 답변 초안 (받아들여 검토를 기다리는 것은 빼고)
+    pub async fn get_ai_drafts(&self, account_id: &AccountId) -> Result<Vec<Answer>, Error> {
+        match sqlx::query(
+            "SELECT a.* FROM answers a JOIN questions q ON q.id = a.corresponding_question
+             WHERE q.account_id = $1 AND q.deleted_at IS NULL
+               AND a.is_ai AND a.status = 'pending' AND a.deleted_at IS NULL
+               AND NOT EXISTS (
+                   SELECT 1 FROM moderation_queue m
+                   WHERE m.answer_id = a.id AND m.status = 'pending'
+               )
+             ORDER BY a.id",
+        )
+        .bind(account_id.0)
+        .map(answer)
+        .fetch_all(&self.connection)
+        .await
+        {
+            Ok(drafts) => Ok(drafts),
+            Err(error) => {
+                tracing::event!(tracing::Level::ERROR, "{:?}", error);
+                Err(Error::DatabaseQueryError(error))
+            }
+        }
+    }
+
+    /// 답변 초안과 그 질문의 작성자 (초안이 아니거나 이미 결정했으면 NotFound)
+    pub async fn get_ai_draft(&self, answer_id: i32) -> Result<(Answer, AccountId), Error> {
+        match sqlx::query(
+            "SELECT a.*, q.account_id AS owner_id
+             FROM answers a JOIN questions q ON q.id = a.corresponding_question
+             WHERE a.id = $1 AND q.deleted_at IS NULL
+               AND a.is_ai AND a.status = 'pending' AND a.deleted_at IS NULL
+               AND NOT EXISTS (
+                   SELECT 1 FROM moderation_queue m
+                   WHERE m.answer_id = a.id AND m.status = 'pending'
+               )",
+        )
+        .bind(answer_id)
+        .map(|row: PgRow| {
+            let owner = AccountId(row.get("owner_id"));
+            (answer(row), owner)
+        })
+        .fetch_one(&self.connection)
+        .await
+        {
+            Ok(draft) => Ok(draft),
+            Err(sqlx::Error::RowNotFound) => Err(Error::NotFound),
+            Err(error) => {
+                tracing::event!(tracing::Level::ERROR, "{:?}", error);
+                Err(Error::DatabaseQueryError(error))
+            }
+        }
+    }
+
+    /// 받아들인 답변 초안을 비속어를 가린 content 로 바꿔 공개한다
+    /// 검토 대기 사유가 있으면 pending 으로 두고 검토 대기열에 넣는다
+    pub async fn accept_ai_draft(
         &self,
-        question_id: i32,
-        account_id: &AccountId,
-    ) -> Result<bool, Error> {
-        match sqlx::query("SELECT * from questions where id = $1 and account_id = $2")
-            .bind(question_id)
-            .bind(account_id.0)
-            .fetch_optional(&self.connection)
-            .await
+        answer_id: &AnswerId,
+        content: String,
+        flag: Option<Flag>,
+    ) -> Result<Answer, Error> {
+        let status = ModerationStatus::from_flag(&flag);
+        let mut tx = self.connection.begin().await.map_err(|error| {
+            tracing::event!(tracing::Level::ERROR, "{:?}", error);
+            Error::DatabaseQueryError(error)
+        })?;
+
+        let answer = match sqlx::query(
+            "UPDATE answers SET content = $2, status = $3
+             WHERE id = $1 AND is_ai AND status = 'pending'
+             RETURNING id, content, corresponding_question, status, is_ai",
+        )
+        .bind(answer_id.0)
+        .bind(content)
+        .bind(status.as_str())
+        .map(answer)
+        .fetch_one(&mut tx)
+        .await
         {
-            Ok(question) => Ok(question.is_some()),
-            Err(e) => {
-                tracing::event!(tracing::Level::ERROR, "{:?}", e);
-                Err(Error::DatabaseQueryError(e))
+            Ok(answer) => answer,
+            Err(sqlx::Error::RowNotFound) => return Err(Error::NotFound),
+            Err(error) => {
+                tracing::event!(tracing::Level::ERROR, "{:?}", error);
+                return Err(Error::DatabaseQueryError(error));
             }
+        };
+
+        if let Some(flag) = flag {
+            Self::enqueue(&mut tx, None, Some(answer.id.0), None, flag).await?;
         }
+
+        match tx.commit().await {
+            Ok(_) => Ok(answer),
+            Err(error) => {
+                tracing::event!(tracing::Level::ERROR, "{:?}", error);
+                Err(Error::DatabaseQueryError(error))
+            }
+        }
+    }
+
+    /// 답변 초안을 거절한다 (rejected)
+    pub async fn dismiss_ai_draft(&self, answer_id: &AnswerId) -> Result<Answer, Error> {
+        match sqlx::query(
+            "UPDATE answers SET status = 'rejected'
+             WHERE id = $1 AND is_ai AND status = 'pending'
+             RETURNING id, content, corresponding_question, status, is_ai",
+        )
+        .bind(answer_id.0)
+        .map(answer)
+        .fetch_one(&self.connection)
+        .await
+        {
+            Ok(answer) => Ok(answer),
+            Err(sqlx::Error::RowNotFound) => Err(Error::NotFound),
+            Err(error) => {
+                tracing::event!(tracing::Level::ERROR, "{:?}", error);
+                Err(Error::DatabaseQueryError(error))
+            }
+        }
+    }
+
+    pub async fn add_account(&self, account: Account) -> Result<bool, Error> {
+        match sqlx::query("INSERT INTO accounts (email, password) VALUES ($1, $2)")
+            .bind(account.email)
+            .bind(account.password)
+            .execute(&self.connection)
+            .await
+        {
+            Ok(_) => Ok(true),
+            Err(error) => {
+                // 연결 에러처럼 DB 가 돌려준 에러가 아닐 수도 있다
+                match error.as_database_error() {
+                    Some(db_error) => tracing::event!(
+                        tracing::Level::ERROR,
+                        code = db_error.code().as_deref(),
+                        db_message = db_error.message(),
+                        constraint = db_error.constraint()
+                    ),
+                    None => tracing::event!(tracing::Level::ERROR, "{:?}", error),
+                }
+                Err(Error::DatabaseQueryError(error))
+            }
+        }
+    }
+
+    pub async fn get_account(self, email: String) -> Result<Account, Error> {
+        // 모델 계정으로는 로그인할 수 없다
+        match sqlx::query("SELECT * from accounts where email = $1 AND NOT is_ai")
+            .bind(email)
+            .map(|row: PgRow| Account {
+                id: Some(AccountId(row.get("id"))),
+                email: row.get("email"),
+                password: row.get("password"),
+            })
+            .fetch_one(&self.connection)
+            .await
+        {
+            Ok(account) => Ok(account),
+            Err(error) => {
+                tracing::event!(tracing::Level::ERROR, "{:?}", error);
+                Err(Error::DatabaseQueryError(error))
+            }
+        }
+    }
+
+    pub async fn is_question_owner(
+        &self,
+        question_id: i32,
+        account_id: &AccountId,
+    ) -> Result<bool, Error> {
+        match sqlx::query("SELECT * from questions where id = $1 and account_id = $2")
+            .bind(question_id)
+            .bind(account_id.0)
+            .fetch_optional(&self.connection)
+            .await
+        {
+            Ok(question) => Ok(question.is_some()),
+            Err(e) => {
+                tracing::event!(tracing::Level::ERROR, "{:?}", e);
+                Err(Error::DatabaseQueryError(e))
+            }
+        }
+    }
+
+    pub async fn question_exists(&self, question_id: i32) -> Result<bool, Error> {
+        match sqlx::query("SELECT id from questions where id = $1 and deleted_at IS NULL")
+            .bind(question_id)
+            .fetch_optional(&self.connection)
+            .await
+        {
+            Ok(question) => Ok(question.is_some()),
+            Err(e) => {
+                tracing::event!(tracing::Level::ERROR, "{:?}", e);
+                Err(Error::DatabaseQueryError(e))
+            }
+        }
+    }
+
+    /// 삭제한 질문도 찾는다 (없으면 None, 있으면 삭제됐는지)
+    /// 삭제했는지와 검토 상태 (삭제한 질문도 돌려준다, 질문이 없으면 None)
+    pub async fn get_question_state(
+        &self,
+        question_id: i32,
+    ) -> Result<Option<(bool, ModerationStatus)>, Error> {
+        match sqlx::query(
+            "SELECT deleted_at IS NOT NULL AS deleted, status from questions where id = $1",
+        )
+        .bind(question_id)
+        .map(|row: PgRow| {
+            (
+                row.get::<bool, _>("deleted"),
+                ModerationStatus::from_db(row.get("status")),
+            )
+        })
+        .fetch_optional(&self.connection)
+        .await
+        {
+            Ok(state) => Ok(state),
+            Err(e) => {
+                tracing::event!(tracing::Level::ERROR, "{:?}", e);
+                Err(Error::DatabaseQueryError(e))
+            }
+        }
+    }
+
+    pub async fn answer_exists(&self, answer_id: i32) -> Result<bool, Error> {
+        match sqlx::query("SELECT id from answers where id = $1 and deleted_at IS NULL")
+            .bind(answer_id)
+            .fetch_optional(&self.connection)
+            .await
+        {
+            Ok(answer) => Ok(answer.is_some()),
+            Err(e) => {
+                tracing::event!(tracing::Level::ERROR, "{:?}", e);
+                Err(Error::DatabaseQueryError(e))
+            }
+        }
+    }
+
+    pub async fn is_answer_owner(
+        &self,
+        answer_id: i32,
+        account_id: &AccountId,
+    ) -> Result<bool, Error> {
+        match sqlx::query("SELECT * from answers where id = $1 and account_id = $2")
+            .bind(answer_id)
+            .bind(account_id.0)
+            .fetch_optional(&self.connection)
+            .await
+        {
+            Ok(answer) => Ok(answer.is_some()),
+            Err(e) => {
+                tracing::event!(tracing::Level::ERROR, "{:?}", e);
+                Err(Error::DatabaseQueryError(e))
+            }
+        }
+    }
+
+    pub async fn is_moderator(&self, account_id: &AccountId) -> Result<bool, Error> {
+        match sqlx::query("SELECT is_moderator from accounts where id = $1")
+            .bind(account_id.0)
+            .map(|row: PgRow| row.get::<bool, _>("is_moderator"))
+            .fetch_optional(&self.connection)
+            .await
+        {
+            Ok(is_moderator) => Ok(is_moderator.unwrap_or(false)),
+            Err(e) => {
+                tracing::event!(tracing::Level::ERROR, "{:?}", e);
+                Err(Error::DatabaseQueryError(e))
+            }
+        }
+    }
+
+    /// 글을 검토 대기열에 넣는다 (이미 대기 중이면 사유와 찾은 단어만 바꾼다)
+    /// revision_id 가 있으면 질문 자체가 아니라 그 질문의 검토 대기 중인 수정이다
+    async fn enqueue(
+        tx: &mut Transaction<'_, Postgres>,
+        question_id: Option<i32>,
+        answer_id: Option<i32>,
+        revision_id: Option<i32>,
+        flag: Flag,
+    ) -> Result<(), Error> {
+        let target = match question_id {
+            Some(_) => "question_id",
+            None => "answer_id",
+        };
+        let query = format!(
+            "INSERT INTO moderation_queue (question_id, answer_id, revision_id, reason, matched_words)
+             VALUES ($1, $2, $3, $4, $5)
+             ON CONFLICT ({}) WHERE status = 'pending'
+             DO UPDATE SET revision_id = EXCLUDED.revision_id, reason = EXCLUDED.reason,
+                 matched_words = EXCLUDED.matched_words",
+            target
+        );
+
+        match sqlx::query(&query)
+            .bind(question_id)
+            .bind(answer_id)
+            .bind(revision_id)
+            .bind(flag.reason.as_str())
+            .bind(flag.words)
+            .execute(tx)
+            .await
+        {
+            Ok(_) => Ok(()),
+            Err(error) => {
+                tracing::event!(tracing::Level::ERROR, "{:?}", error);
+                Err(Error::DatabaseQueryError(error))
+            }
+        }
+    }
+
+    pub async fn get_moderation_queue(&self) -> Result<Vec<QueueItem>, Error> {
+        match sqlx::query(&format!("{} ORDER BY m.id", QUEUE_ITEM_QUERY))
+            .map(queue_item)
+            .fetch_all(&self.connection)
+            .await
+        {
+            Ok(items) => Ok(items),
+            Err(error) => {
+                tracing::event!(tracing::Level::ERROR, "{:?}", error);
+                Err(Error::DatabaseQueryError(error))
+            }
+        }
+    }
+
+    pub async fn get_queue_item(&self, queue_item_id: i32) -> Result<QueueItem, Error> {
+        match sqlx::query(&format!("{} AND m.id = $1", QUEUE_ITEM_QUERY))
+            .bind(queue_item_id)
+            .map(queue_item)
+            .fetch_one(&self.connection)
+            .await
+        {
+            Ok(item) => Ok(item),
+            Err(sqlx::Error::RowNotFound) => Err(Error::NotFound),
+            Err(error) => {
+                tracing::event!(tracing::Level::ERROR, "{:?}", error);
+                Err(Error::DatabaseQueryError(error))
+            }
+        }
+    }
+
+    /// 모더레이터의 결정을 글에 반영하고 작성자에게 알린다
+    pub async fn decide(
+        &self,
+        queue_item_id: i32,
+        moderator_id: AccountId,
+        decision: Decision,
+    ) -> Result<QueueItem, Error> {
+        let mut tx = self.connection.begin().await.map_err(|error| {
+            tracing::event!(tracing::Level::ERROR, "{:?}", error);
+            Error::DatabaseQueryError(error)
+        })?;
+
+        // 두 모더레이터가 같은 항목을 동시에 처리하지 않도록 잠근다
+        let item = match sqlx::query(&format!(
+            "{} AND m.id = $1 FOR UPDATE OF m",
+            QUEUE_ITEM_QUERY
+        ))
+        .bind(queue_item_id)
+        .map(queue_item)
+        .fetch_one(&mut tx)
+        .await
+        {
+            Ok(item) => item,
+            Err(sqlx::Error::RowNotFound) => return Err(Error::NotFound),
+            Err(error) => {
+                tracing::event!(tracing::Level::ERROR, "{:?}", error);
+                return Err(Error::DatabaseQueryError(error));
+            }
+        };
+
+        let (status, title, content, note) = match &decision {
+            Decision::Approve => (ModerationStatus::Published, None, None, None),
+            Decision::Edit { title, content } => (
+                ModerationStatus::Published,
+                title.clone(),
+                content.clone(),
+                None,
+            ),
+            Decision::Reject { note } => (ModerationStatus::Rejected, None, None, note.clone()),
+        };
+
+        let result = match (&item.question_id, &item.answer_id, &item.revision_id) {
+            (Some(question_id), _, Some(revision_id)) => {
+                Self::decide_revision(
+                    &mut tx,
+                    question_id,
+                    revision_id,
+                    status,
+                    (&title, &content),
+                    &moderator_id,
+                )
+                .await
+            }
+            (Some(question_id), _, None) => {
+                let question = sqlx::query(
+                    "UPDATE questions
+                     SET status = $2, title = COALESCE($3, title),
+                         content = COALESCE($4, content), version = version + 1
+                     WHERE id = $1
+                     RETURNING id, title, content, tags, version, status",
+                )
+                .bind(question_id.0)
+                .bind(status.as_str())
+                .bind(&title)
+                .bind(&content)
+                .map(|row: PgRow| Question {
+                    id: QuestionId(row.get("id")),
+                    title: row.get("title"),
+                    content: row.get("content"),
+                    tags: row.get("tags"),
+                    version: row.get("version"),
+                    status: ModerationStatus::from_db(row.get("status")),
+                })
+                .fetch_one(&mut tx)
+                .await;
+
+                match question {
+                    Ok(question) if title.is_some() || content.is_some() => Self::add_revision(
+                        &mut tx,
+                        &question,
+                        &moderator_id,
+                        ModerationStatus::Published,
+                    )
+                    .await
+                    .map(|_| ()),
+                    Ok(_) => Ok(()),
+                    Err(error) => Err(Error::DatabaseQueryError(error)),
+                }
+            }
+            (None, Some(answer_id), _) => sqlx::query(
+                "UPDATE answers SET status = $2, content = COALESCE($3, content) WHERE id = $1",
+            )
+            .bind(answer_id.0)
+            .bind(status.as_str())
+            .bind(&content)
+            .execute(&mut tx)
+            .await
+            .map(|_| ())
+            .map_err(Error::DatabaseQueryError),
+            (None, None, _) => Err(Error::NotFound),
+        };
+        if let Err(error) = result {
+            tracing::event!(tracing::Level::ERROR, "{:?}", error);
+            return Err(error);
+        }
+
+        let subject = match (&item.question_id, &item.revision_id) {
+            (Some(id), Some(_)) => format!("Your edit of question {}", id.0),
+            (Some(id), None) => format!("Your question {}", id.0),
+            (None, _) => "Your answer".to_string(),
+        };
+        let message = match &note {
+            None => format!("{} was {}", subject, decision.outcome()),
+            Some(note) => format!("{} was {}: {}", subject, decision.outcome(), note),
+        };
+        let result = sqlx::query(
+            "UPDATE moderation_queue
+             SET status = $2, moderator_id = $3, note = $4, decided_on = NOW()
+             WHERE id = $1",
+        )
+        .bind(queue_item_id)
+        .bind(decision.outcome())
+        .bind(moderator_id.0)
+        .bind(&note)
+        .execute(&mut tx)
+        .await;
+        if let Err(error) = result {
+            tracing::event!(tracing::Level::ERROR, "{:?}", error);
+            return Err(Error::DatabaseQueryError(error));
+        }
+
+        let result = sqlx::query(
+            "INSERT INTO notifications (account_id, question_id, answer_id, message)
+             VALUES ($1, $2, $3, $4)",
+        )
+        .bind(item.author_id.0)
+        .bind(item.question_id.as_ref().map(|id| id.0))
+        .bind(item.answer_id.as_ref().map(|id| id.0))
+        .bind(message)
+        .execute(&mut tx)
+        .await;
+        if let Err(error) = result {
+            tracing::event!(tracing::Level::ERROR, "{:?}", error);
+            return Err(Error::DatabaseQueryError(error));
+        }
+
+        match tx.commit().await {
+            Ok(_) => Ok(item),
+            Err(error) => {
+                tracing::event!(tracing::Level::ERROR, "{:?}", error);
+                Err(Error::DatabaseQueryError(error))
+            }
+        }
+    }
+
+    /// 검토 대기 중인 수정을 공개하면 질문에 반영하고(모더레이터가 고친 필드는 고친 대로), 거절하면 질문은 그대로 둔다
+    async fn decide_revision(
+        tx: &mut Transaction<'_, Postgres>,
+        question_id: &QuestionId,
+        revision_id: &RevisionId,
+        status: ModerationStatus,
+        (title, content): (&Option<String>, &Option<String>),
+        moderator_id: &AccountId,
+    ) -> Result<(), Error> {
+        if status == ModerationStatus::Published {
+            let question = match sqlx::query(
+                "UPDATE questions q
+                 SET title = COALESCE($3, r.title), content = COALESCE($4, r.content),
+                     tags = r.tags, version = q.version + 1
+                 FROM question_revisions r
+                 WHERE q.id = $1 AND r.id = $2
+                 RETURNING q.id, q.title, q.content, q.tags, q.version, q.status",
+            )
+            .bind(question_id.0)
+            .bind(revision_id.0)
+            .bind(title)
+            .bind(content)
+            .map(|row: PgRow| Question {
+                id: QuestionId(row.get("id")),
+                title: row.get("title"),
+                content: row.get("content"),
+                tags: row.get("tags"),
+                version: row.get("version"),
+                status: ModerationStatus::from_db(row.get("status")),
+            })
+            .fetch_one(&mut *tx)
+            .await
+            {
+                Ok(question) => question,
+                Err(error) => return Err(Error::DatabaseQueryError(error)),
+            };
+            if title.is_some() || content.is_some() {
+                Self::add_revision(tx, &question, moderator_id, ModerationStatus::Published)
+                    .await?;
+            }
+        }
+
+        sqlx::query("UPDATE question_revisions SET status = $2 WHERE id = $1")
+            .bind(revision_id.0)
+            .bind(status.as_str())
+            .execute(&mut *tx)
+            .await
+            .map(|_| ())
+            .map_err(Error::DatabaseQueryError)
+    }
+
+    pub async fn get_notifications(
+        &self,
+        account_id: &AccountId,
+    ) -> Result<Vec<Notification>, Error> {
+        match sqlx::query("SELECT * from notifications WHERE account_id = $1 ORDER BY id DESC")
+            .bind(account_id.0)
+            .map(|row: PgRow| Notification {
+                id: NotificationId(row.get("id")),
+                question_id: row.get::<Option<i32>, _>("question_id").map(QuestionId),
+                answer_id: row.get::<Option<i32>, _>("answer_id").map(AnswerId),
+                message: row.get("message"),
+                created_on: row.get("created_on"),
+            })
+            .fetch_all(&self.connection)
+            .await
+        {
+            Ok(notifications) => Ok(notifications),
+            Err(error) => {
+                tracing::event!(tracing::Level::ERROR, "{:?}", error);
+                Err(Error::DatabaseQueryError(error))
+            }
+        }
+    }
+
+    /// 비속어 검사 결과 (가린 글, 찾은 비속어) 를 내용 해시로 찾는다
+    /// max_age 보다 오래된 결과는 없는 것으로 본다
+    pub async fn get_profanity_cache(
+        &self,
+        content_hash: &str,
+        max_age_secs: u64,
+    ) -> Result<Option<(String, Vec<String>)>, Error> {
+        match sqlx::query(
+            "SELECT censored_content, bad_words FROM profanity_cache
+             WHERE content_hash = $1 AND created_on > NOW() - make_interval(secs => $2)",
+        )
+        .bind(content_hash)
+        .bind(max_age_secs as f64)
+        .map(|row: PgRow| (row.get("censored_content"), row.get("bad_words")))
+        .fetch_optional(&self.connection)
+        .await
+        {
+            Ok(cached) => Ok(cached),
+            Err(error) => {
+                tracing::event!(tracing::Level::ERROR, "{:?}", error);
+                Err(Error::DatabaseQueryError(error))
+            }
+        }
+    }
+
+    /// 결과를 저장하면서 max_age 보다 오래된 결과를 지운다 (테이블이 계속 커지지 않도록)
+    pub async fn put_profanity_cache(
+        &self,
+        content_hash: &str,
+        censored_content: &str,
+        bad_words: &[String],
+        max_age_secs: u64,
+    ) -> Result<bool, Error> {
+        match sqlx::query(
+            "WITH expired AS (
+                 DELETE FROM profanity_cache
+                 WHERE created_on <= NOW() - make_interval(secs => $4) AND content_hash <> $1
+             )
+             INSERT INTO profanity_cache (content_hash, censored_content, bad_words)
+             VALUES ($1, $2, $3)
+             ON CONFLICT (content_hash) DO UPDATE
+             SET censored_content = $2, bad_words = $3, created_on = NOW()",
+        )
+        .bind(content_hash)
+        .bind(censored_content)
+        .bind(bad_words)
+        .bind(max_age_secs as f64)
+        .execute(&self.connection)
+        .await
+        {
+            Ok(_) => Ok(true),
+            Err(error) => {
+                tracing::event!(tracing::Level::ERROR, "{:?}", error);
+                Err(Error::DatabaseQueryError(error))
+            }
+        }
+    }
+
+    /// 문서와 조각을 한 트랜잭션으로 저장한다
+    pub async fn add_document(
+        &self,
+        new_document: NewDocument,
+        account_id: Option<AccountId>,
+    ) -> Result<Document, Error> {
+        let mut tx = self.connection.begin().await.map_err(|error| {
+            tracing::event!(tracing::Level::ERROR, "{:?}", error);
+            Error::DatabaseQueryError(error)
+        })?;
+
+        let chunks = new_document.chunks.len() as i64;
+        let mut document = match sqlx::query(
+            "INSERT INTO documents (title, source_type, filename, account_id)
+             VALUES ($1, $2, $3, $4)
+             RETURNING id, title, source_type, filename, account_id, created_on",
+        )
+        .bind(new_document.title)
+        .bind(new_document.source_type.as_str())
+        .bind(new_document.filename)
+        .bind(account_id.map(|account_id| account_id.0))
+        .map(|row: PgRow| Document {
+            id: DocumentId(row.get("id")),
+            title: row.get("title"),
+            source_type: SourceType::from_db(row.get("source_type")),
+            filename: row.get("filename"),
+            account_id: row.get::<Option<i32>, _>("account_id").map(AccountId),
+            chunks: 0,
+            created_on: row.get("created_on"),
+        })
+        .fetch_one(&mut tx)
+        .await
+        {
+            Ok(document) => document,
+            Err(error) => {
+                tracing::event!(tracing::Level::ERROR, "{:?}", error);
+                return Err(Error::DatabaseQueryError(error));
+            }
+        };
+
+        for (position, chunk) in new_document.chunks.into_iter().enumerate() {
+            if let Err(error) = sqlx::query(
+                "INSERT INTO document_chunks (document_id, position, heading, content)
+                 VALUES ($1, $2, $3, $4)",
+            )
+            .bind(document.id.0)
+            .bind(position as i32)
+            .bind(chunk.heading)
+            .bind(chunk.content)
+            .execute(&mut tx)
+            .await
+            {
+                tracing::event!(tracing::Level::ERROR, "{:?}", error);
+                return Err(Error::DatabaseQueryError(error));
+            }
+        }
+        document.chunks = chunks;
+
+        match tx.commit().await {
+            Ok(_) => Ok(document),
+            Err(error) => {
+                tracing::event!(tracing::Level::ERROR, "{:?}", error);
+                Err(Error::DatabaseQueryError(error))
+            }
+        }
+    }
+
+    /// 문서를 검색에서 뺀다 (이미 지웠거나 없으면 false)
+    pub async fn delete_document(&self, document_id: i32) -> Result<bool, Error> {
+        match sqlx::query(
+            "UPDATE documents SET deleted_at = NOW()
+             WHERE id = $1 AND deleted_at IS NULL",
+        )
+        .bind(document_id)
+        .execute(&self.connection)
+        .await
+        {
+            Ok(result) => Ok(result.rows_affected() > 0),
+            Err(error) => {
+                tracing::event!(tracing::Level::ERROR, "{:?}", error);
+                Err(Error::DatabaseQueryError(error))
+            }
+        }
+    }
+
+    /// 최근에 이어간 대화부터
+    pub async fn get_conversations(
+        &self,
+        account_id: &AccountId,
+        limit: Option<u32>,
+        offset: u32,
+    ) -> Result<Vec<Conversation>, Error> {
+        match sqlx::query(
+            "SELECT * FROM conversations
+             WHERE account_id = $1
+             ORDER BY updated_at DESC, id DESC
+             LIMIT $2 OFFSET $3",
+        )
+        .bind(account_id.0)
+        .bind(limit.map(i64::from))
+        .bind(i64::from(offset))
+        .map(conversation)
+        .fetch_all(&self.connection)
+        .await
+        {
+            Ok(conversations) => Ok(conversations),
+            Err(error) => {
+                tracing::event!(tracing::Level::ERROR, "{:?}", error);
+                Err(Error::DatabaseQueryError(error))
+            }
+        }
+    }
+
+    /// 다른 사람의 대화도 없는 대화처럼 404 로 끝난다
+    /// 대화가 없거나 다른 계정의 대화면 NotFound
+    pub async fn get_conversation(
+        &self,
+        conversation_id: i32,
+        account_id: &AccountId,
+    ) -> Result<Conversation, Error> {
+        match sqlx::query("SELECT * FROM conversations WHERE id = $1 AND account_id = $2")
+            .bind(conversation_id)
+            .bind(account_id.0)
+            .map(conversation)
+            .fetch_one(&self.connection)
+            .await
+        {
+            Ok(conversation) => Ok(conversation),
+            Err(sqlx::Error::RowNotFound) => Err(Error::NotFound),
+            Err(error) => {
+                tracing::event!(tracing::Level::ERROR, "{:?}", error);
+                Err(Error::DatabaseQueryError(error))
+            }
+        }
+    }
+
+    /// 대화의 모든 메시지 (오래된 것부터)
+    pub async fn get_messages(
+        &self,
+        conversation_id: &ConversationId,
+    ) -> Result<Vec<Message>, Error> {
+        match sqlx::query(
+            "SELECT id, role, content, citations::text AS citations, created_on
+             FROM messages WHERE conversation_id = $1
+             ORDER BY id",
+        )
+        .bind(conversation_id.0)
+        .map(message)
+        .fetch_all(&self.connection)
+        .await
+        {
+            Ok(messages) => Ok(messages),
+            Err(error) => {
+                tracing::event!(tracing::Level::ERROR, "{:?}", error);
+                Err(Error::DatabaseQueryError(error))
+            }
+        }
+    }
+
+    /// after 뒤의 메시지 중 최근 것부터 max_chars 글자까지 (오래된 것부터)
+    /// 한도를 넘기는 첫 메시지까지 넣어 밀려나는 메시지가 있는지 알 수 있게 한다
+    pub async fn get_recent_messages(
+        &self,
+        conversation_id: &ConversationId,
+        after: Option<&MessageId>,
+        max_chars: i64,
+    ) -> Result<Vec<Message>, Error> {
+        match sqlx::query(
+            "SELECT id, role, content, citations, created_on
+             FROM (
+                 SELECT id, role, content, citations::text AS citations, created_on,
+                        SUM(char_length(content)) OVER (ORDER BY id DESC) AS used
+                 FROM messages
+                 WHERE conversation_id = $1 AND ($2::integer IS NULL OR id > $2)
+             ) recent
+             WHERE used - char_length(content) < $3
+             ORDER BY id",
+        )
+        .bind(conversation_id.0)
+        .bind(after.map(|id| id.0))
+        .bind(max_chars)
+        .map(message)
+        .fetch_all(&self.connection)
+        .await
+        {
+            Ok(messages) => Ok(messages),
+            Err(error) => {
+                tracing::event!(tracing::Level::ERROR, "{:?}", error);
+                Err(Error::DatabaseQueryError(error))
+            }
+        }
+    }
+
+    /// 질문과 답을 함께 저장하고 대화를 최근으로 올린다
+    /// conversation_id 가 없으면 title 로 새 대화를 만들어 같이 저장하므로 답이 없는 빈 대화는 남지 않는다
+    pub async fn add_exchange(
+        &self,
+        conversation_id: Option<&ConversationId>,
+        account_id: &AccountId,
+        title: &str,
+        question: &str,
+        response: &ChatResponse,
+    ) -> Result<ConversationId, Error> {
+        let mut tx = self.connection.begin().await.map_err(|error| {
+            tracing::event!(tracing::Level::ERROR, "{:?}", error);
+            Error::DatabaseQueryError(error)
+        })?;
+
+        let conversation_id = match conversation_id {
+            Some(id) => id.clone(),
+            None => match sqlx::query(
+                "INSERT INTO conversations (account_id, title)
+                 VALUES ($1, $2)
+                 RETURNING id",
+            )
+            .bind(account_id.0)
+            .bind(title)
+            .map(|row: PgRow| ConversationId(row.get("id")))
+            .fetch_one(&mut tx)
+            .await
+            {
+                Ok(id) => id,
+                Err(error) => {
+                    tracing::event!(tracing::Level::ERROR, "{:?}", error);
+                    return Err(Error::DatabaseQueryError(error));
+                }
+            },
+        };
+
+        // Citation 은 항상 JSON 으로 바꿀 수 있다
+        let citations = serde_json::to_string(&response.citations).unwrap_or_default();
+        for (role, content, citations) in [
+            (Role::User, question, "[]"),
+            (
+                Role::Assistant,
+                response.answer.as_str(),
+                citations.as_str(),
+            ),
+        ] {
+            if let Err(error) = sqlx::query(
+                "INSERT INTO messages (conversation_id, role, content, citations)
+                 VALUES ($1, $2, $3, $4::jsonb)",
+            )
+            .bind(conversation_id.0)
+            .bind(role.as_str())
+            .bind(content)
+            .bind(citations)
+            .execute(&mut tx)
+            .await
+            {
+                tracing::event!(tracing::Level::ERROR, "{:?}", error);
+                return Err(Error::DatabaseQueryError(error));
+            }
+        }
+
+        if let Err(error) = sqlx::query("UPDATE conversations SET updated_at = NOW() WHERE id = $1")
+            .bind(conversation_id.0)
+            .execute(&mut tx)
+            .await
+        {
+            tracing::event!(tracing::Level::ERROR, "{:?}", error);
+            return Err(Error::DatabaseQueryError(error));
+        }
+
+        match tx.commit().await {
+            Ok(_) => Ok(conversation_id),
+            Err(error) => {
+                tracing::event!(tracing::Level::ERROR, "{:?}", error);
+                Err(Error::DatabaseQueryError(error))
+            }
+        }
+    }
+
+    /// until 까지의 메시지를 요약으로 바꿨다고 기록한다
+    pub async fn update_summary(
+        &self,
+        conversation_id: &ConversationId,
+        summary: &str,
+        until: &MessageId,
+    ) -> Result<bool, Error> {
+        match sqlx::query(
+            "UPDATE conversations SET summary = $2, summarized_until = $3
+             WHERE id = $1",
+        )
+        .bind(conversation_id.0)
+        .bind(summary)
+        .bind(until.0)
+        .execute(&self.connection)
+        .await
+        {
+            Ok(result) => Ok(result.rows_affected() > 0),
+            Err(error) => {
+                tracing::event!(tracing::Level::ERROR, "{:?}", error);
+                Err(Error::DatabaseQueryError(error))
+            }
+        }
+    }
+
+    /// 대화와 메시지를 지운다 (이 계정의 대화가 아니면 false)
+    pub async fn delete_conversation(
+        &self,
+        conversation_id: i32,
+        account_id: &AccountId,
+    ) -> Result<bool, Error> {
+        match sqlx::query("DELETE FROM conversations WHERE id = $1 AND account_id = $2")
+            .bind(conversation_id)
+            .bind(account_id.0)
+            .execute(&self.connection)
+            .await
+        {
+            Ok(result) => Ok(result.rows_affected() > 0),
+            Err(error) => {
+                tracing::event!(tracing::Level::ERROR, "{:?}", error);
+                Err(Error::DatabaseQueryError(error))
+            }
+        }
+    }
+
+    /// 공개된 질문과 답변, 문서 조각을 전문 검색해서 점수가 높은 순서로 돌려준다
+    /// 검색어 중 하나만 들어 있어도 찾도록 AND 대신 OR 로 묶는다
+    pub async fn search_passages(
+        &self,
+        query: &str,
+        limit: i64,
+        filter: &RetrievalFilter,
+    ) -> Result<Vec<Passage>, Error> {
+        match sqlx::query(&format!(
+            "SELECT p.*
+             FROM ({}) p
+             WHERE ($3::text[] IS NULL OR p.source = ANY($3))
+               AND ($4::timestamptz IS NULL OR p.updated_at > $4)
+             ORDER BY p.score DESC
+             LIMIT $2",
+            matching_passages()
+        ))
+        .bind(query)
+        .bind(limit)
+        .bind(filter.source_names())
+        .bind(filter.updated_after)
+        .map(passage)
+        .fetch_all(&self.connection)
+        .await
+        {
+            Ok(passages) => Ok(passages),
+            Err(error) => {
+                tracing::event!(tracing::Level::ERROR, "{:?}", error);
+                Err(Error::DatabaseQueryError(error))
+            }
+        }
+    }
+
+    /// 아직 이 모델로 벡터를 만들지 않았거나 그 뒤에 글이 바뀐 글 (오래된 것부터)
+    /// 임베딩 API 가 거절한 글은 그 뒤에 바뀌었을 때만 다시 돌려준다
+    pub async fn get_embedding_jobs(
+        &self,
+        model: &str,
+        limit: i64,
+    ) -> Result<Vec<EmbeddingJob>, Error> {
+        match sqlx::query(&format!(
+            "SELECT p.*, 0::real AS score
+             FROM ({}) p
+             LEFT JOIN embeddings e ON e.source = p.source
+                 AND e.entity_id = p.entity_id
+                 AND e.model = $1
+             LEFT JOIN embedding_failures f ON f.source = p.source
+                 AND f.entity_id = p.entity_id
+                 AND f.model = $1
+             WHERE (e.entity_id IS NULL OR e.source_updated_at < p.updated_at)
+               AND (f.entity_id IS NULL OR f.source_updated_at < p.updated_at)
+             ORDER BY p.updated_at
+             LIMIT $2",
+            PUBLISHED_PASSAGES
+        ))
+        .bind(model)
+        .bind(limit)
+        .map(|row: PgRow| EmbeddingJob {
+            text: row.get("document"),
+            passage: passage(row),
+        })
+        .fetch_all(&self.connection)
+        .await
+        {
+            Ok(jobs) => Ok(jobs),
+            Err(error) => {
+                tracing::event!(tracing::Level::ERROR, "{:?}", error);
+                Err(Error::DatabaseQueryError(error))
+            }
+        }
+    }
+
+    /// 작업마다 만든 벡터를 저장한다 (같은 글, 같은 모델의 이전 벡터는 덮어쓴다)
+    pub async fn put_embeddings(
+        &self,
+        model: &str,
+        embeddings: &[(EmbeddingJob, Vec<f32>)],
+    ) -> Result<bool, Error> {
+        let mut tx = self.connection.begin().await.map_err(|error| {
+            tracing::event!(tracing::Level::ERROR, "{:?}", error);
+            Error::DatabaseQueryError(error)
+        })?;
+
+        for (job, embedding) in embeddings {
+            if let Err(error) = sqlx::query(
+                "INSERT INTO embeddings (source, entity_id, model, embedding, source_updated_at)
+                 VALUES ($1, $2, $3, $4, $5)
+                 ON CONFLICT (source, entity_id, model) DO UPDATE
+                 SET embedding = $4, source_updated_at = $5, created_on = NOW()",
+            )
+            .bind(job.passage.source.as_str())
+            .bind(job.entity_id())
+            .bind(model)
+            .bind(embedding)
+            .bind(job.passage.updated_at)
+            .execute(&mut tx)
+            .await
+            {
+                tracing::event!(tracing::Level::ERROR, "{:?}", error);
+                return Err(Error::DatabaseQueryError(error));
+            }
+        }
+
+        match tx.commit().await {
+            Ok(_) => Ok(true),
+            Err(error) => {
+                tracing::event!(tracing::Level::ERROR, "{:?}", error);
+                Err(Error::DatabaseQueryError(error))
+            }
+        }
+    }
+
+    /// 임베딩 API 가 거절한 글을 기록해 글이 바뀔 때까지 건너뛰게 한다
+    pub async fn put_embedding_failure(
+        &self,
+        model: &str,
+        job: &EmbeddingJob,
+        error: &str,
+    ) -> Result<bool, Error> {
+        match sqlx::query(
+            "INSERT INTO embedding_failures (source, entity_id, model, source_updated_at, error)
+             VALUES ($1, $2, $3, $4, $5)
+             ON CONFLICT (source, entity_id, model) DO UPDATE
+             SET source_updated_at = $4, error = $5, created_on = NOW()",
+        )
+        .bind(job.passage.source.as_str())
+        .bind(job.entity_id())
+        .bind(model)
+        .bind(job.passage.updated_at)
+        .bind(error)
+        .execute(&self.connection)
+        .await
+        {
+            Ok(_) => Ok(true),
+            Err(error) => {
+                tracing::event!(tracing::Level::ERROR, "{:?}", error);
+                Err(Error::DatabaseQueryError(error))
+            }
+        }
+    }
+
+    /// 이 모델로 벡터를 만든 공개 글 (벡터 인덱스를 만들 때, 점수는 0)
+    pub async fn get_embedded_passages(
+        &self,
+        model: &str,
+    ) -> Result<Vec<(Passage, Vec<f32>)>, Error> {
+        match sqlx::query(&format!(
+            "SELECT p.*, 0::real AS score, e.embedding
+             FROM ({}) p
+             JOIN embeddings e ON e.source = p.source
+                 AND e.entity_id = p.entity_id
+             WHERE e.model = $1",
+            PUBLISHED_PASSAGES
+        ))
+        .bind(model)
+        .map(|row: PgRow| {
+            let embedding = row.get("embedding");
+            (passage(row), embedding)
+        })
+        .fetch_all(&self.connection)
+        .await
+        {
+            Ok(passages) => Ok(passages),
+            Err(error) => {
+                tracing::event!(tracing::Level::ERROR, "{:?}", error);
+                Err(Error::DatabaseQueryError(error))
+            }
+        }
+    }
+
+    /// embeddings 테이블에 pgvector 열이 있는지 (마이그레이션이 확장을 찾았을 때만 만든다)
+    /// 열이 있으면 확장도 있다
+    pub async fn has_pgvector(&self) -> Result<bool, Error> {
+        match sqlx::query(
+            "SELECT EXISTS (
+                 SELECT 1 FROM information_schema.columns
+                 WHERE table_schema = current_schema()
+                   AND table_name = 'embeddings' AND column_name = 'vector'
+             )",
+        )
+        .map(|row: PgRow| row.get(0))
+        .fetch_one(&self.connection)
+        .await
+        {
+            Ok(exists) => Ok(exists),
+            Err(error) => {
+                tracing::event!(tracing::Level::ERROR, "{:?}", error);
+                Err(Error::DatabaseQueryError(error))
+            }
+        }
+    }
+
+    /// pgvector 코사인 거리로 가까운 공개 글을 찾는다 (점수는 코사인 유사도)
+    /// 차원 수를 식에 넣어야 마이그레이션이 만든 HNSW 인덱스를 쓴다
+    pub async fn search_embeddings(
+        &self,
+        model: &str,
+        query: &[f32],
+        limit: i64,
+        filter: &RetrievalFilter,
+    ) -> Result<Vec<Passage>, Error> {
+        // 차원 수는 SQL 에 그대로 들어가므로 0 이면 쿼리를 만들지 않는다
+        if query.is_empty() {
+            return Err(Error::InvalidConfig(
+                "can't search pgvector with an empty vector".to_string(),
+            ));
+        }
+        let distance = format!(
+            "e.vector::vector({dims}) <=> $2::real[]::vector({dims})",
+            dims = query.len()
+        );
+        match sqlx::query(&format!(
+            "SELECT p.*, (1 - ({distance}))::real AS score
+             FROM embeddings e
+             JOIN ({passages}) p ON e.source = p.source
+                 AND e.entity_id = p.entity_id
+             WHERE e.model = $1 AND vector_dims(e.vector) = {dims}
+               AND ($4::text[] IS NULL OR p.source = ANY($4))
+               AND ($5::timestamptz IS NULL OR p.updated_at > $5)
+             ORDER BY {distance}
+             LIMIT $3",
+            distance = distance,
+            passages = PUBLISHED_PASSAGES,
+            dims = query.len()
+        ))
+        .bind(model)
+        .bind(query)
+        .bind(limit)
+        .bind(filter.source_names())
+        .bind(filter.updated_after)
+        .map(passage)
+        .fetch_all(&self.connection)
+        .await
+        {
+            Ok(passages) => Ok(passages),
+            Err(error) => {
+                tracing::event!(tracing::Level::ERROR, "{:?}", error);
+                Err(Error::DatabaseQueryError(error))
+            }
+        }
+    }
+}
+
+/// 검색할 수 있는 글 (document 는 임베딩할 텍스트로, matching_passages 의 검색 식과 같다)
+/// 질문은 제목과 본문, 답변은 본문, 문서 조각은 제목 경로와 본문을 검색한다
+/// entity_id 는 embeddings 테이블의 키다 (질문, 답변, 조각 id)
+const PUBLISHED_PASSAGES: &str =
+    "SELECT 'question' AS source, q.id AS entity_id, q.id AS question_id,
+            NULL::integer AS answer_id, NULL::integer AS document_id, NULL::integer AS chunk_id,
+            q.title, q.content, q.title || ' ' || q.content AS document, q.updated_at
+     FROM questions q
+     WHERE q.deleted_at IS NULL AND q.status = 'published'
+     UNION ALL
+     SELECT 'answer', a.id, q.id, a.id, NULL, NULL, q.title, a.content, a.content, a.updated_at
+     FROM answers a JOIN questions q ON q.id = a.corresponding_question
+     WHERE a.deleted_at IS NULL AND a.status = 'published'
+       AND q.deleted_at IS NULL AND q.status = 'published'
+     UNION ALL
+     SELECT d.source_type, c.id, NULL, NULL, d.id, c.id,
+            CASE WHEN c.heading IS NULL THEN d.title ELSE d.title || ' > ' || c.heading END,
+            c.content, COALESCE(c.heading, '') || ' ' || c.content, d.updated_at
+     FROM document_chunks c JOIN documents d ON d.id = c.document_id
+     WHERE d.deleted_at IS NULL";
+
+/// PUBLISHED_PASSAGES 중 검색어($1)의 단어가 하나라도 들어 있는 글과 그 일치도(score)
+/// UNION 밖에서 거르면 전문 검색 인덱스를 쓰지 못하므로 갈래마다 인덱스와 같은 식으로 거른다
+fn matching_passages() -> String {
+    format!(
+        "SELECT 'question' AS source, q.id AS entity_id, q.id AS question_id,
+                NULL::integer AS answer_id, NULL::integer AS document_id, NULL::integer AS chunk_id,
+                q.title, q.content, q.updated_at,
+                ts_rank(to_tsvector('simple', q.title || ' ' || q.content), {query}) AS score
+         FROM questions q
+         WHERE q.deleted_at IS NULL AND q.status = 'published'
+           AND to_tsvector('simple', q.title || ' ' || q.content) @@ {query}
+         UNION ALL
+         SELECT 'answer', a.id, q.id, a.id, NULL, NULL, q.title, a.content, a.updated_at,
+                ts_rank(to_tsvector('simple', a.content), {query})
+         FROM answers a JOIN questions q ON q.id = a.corresponding_question
+         WHERE a.deleted_at IS NULL AND a.status = 'published'
+           AND q.deleted_at IS NULL AND q.status = 'published'
+           AND to_tsvector('simple', a.content) @@ {query}
+         UNION ALL
+         SELECT d.source_type, c.id, NULL, NULL, d.id, c.id,
+                CASE WHEN c.heading IS NULL THEN d.title ELSE d.title || ' > ' || c.heading END,
+                c.content, d.updated_at,
+                ts_rank(to_tsvector('simple', COALESCE(c.heading, '') || ' ' || c.content), {query})
+         FROM document_chunks c JOIN documents d ON d.id = c.document_id
+         WHERE d.deleted_at IS NULL
+           AND to_tsvector('simple', COALESCE(c.heading, '') || ' ' || c.content) @@ {query}",
+        query = "replace(plainto_tsquery('simple', $1)::text, '&', '|')::tsquery"
+    )
+}
+
+fn passage(row: PgRow) -> Passage {
+    Passage {
+        source: SourceType::from_db(row.get("source")),
+        question_id: row.get::<Option<i32>, _>("question_id").map(QuestionId),
+        answer_id: row.get::<Option<i32>, _>("answer_id").map(AnswerId),
+        document_id: row.get::<Option<i32>, _>("document_id").map(DocumentId),
+        chunk_id: row.get::<Option<i32>, _>("chunk_id").map(ChunkId),
+        title: row.get("title"),
+        content: row.get("content"),
+        updated_at: row.get("updated_at"),
+        score: row.get("score"),
+    }
+}
+
+fn answer(row: PgRow) -> Answer {
+    Answer {
+        id: AnswerId(row.get("id")),
+        content: row.get("content"),
+        question_id: QuestionId(row.get("corresponding_question")),
+        status: ModerationStatus::from_db(row.get("status")),
+        is_ai: row.get("is_ai"),
+    }
+}
+
+fn conversation(row: PgRow) -> Conversation {
+    Conversation {
+        id: ConversationId(row.get("id")),
+        account_id: AccountId(row.get("account_id")),
+        title: row.get("title"),
+        summary: row.get("summary"),
+        summarized_until: row.get::<Option<i32>, _>("summarized_until").map(MessageId),
+        created_on: row.get("created_on"),
+        updated_at: row.get("updated_at"),
+    }
+}
+
+fn message(row: PgRow) -> Message {
+    Message {
+        id: MessageId(row.get("id")),
+        role: Role::from_db(row.get("role")),
+        content: row.get("content"),
+        citations: serde_json::from_str(row.get("citations")).unwrap_or_default(),
+        created_on: row.get("created_on"),
+    }
+}
+
+/// 대기 중인 검토 항목과 대상 글 (삭제된 글은 뺀다)
+const QUEUE_ITEM_QUERY: &str = "SELECT m.id, m.question_id, m.answer_id, m.revision_id, m.reason,
+            m.matched_words, m.created_on, COALESCE(r.title, q.title) AS title,
+            COALESCE(r.content, q.content, a.content) AS content,
+            COALESCE(q.account_id, a.account_id) AS author_id
+     FROM moderation_queue m
+     LEFT JOIN questions q ON q.id = m.question_id
+     LEFT JOIN answers a ON a.id = m.answer_id
+     LEFT JOIN question_revisions r ON r.id = m.revision_id
+     WHERE m.status = 'pending' AND q.deleted_at IS NULL AND a.deleted_at IS NULL";
+
+fn queue_item(row: PgRow) -> QueueItem {
+    QueueItem {
+        id: QueueItemId(row.get("id")),
+        question_id: row.get::<Option<i32>, _>("question_id").map(QuestionId),
+        answer_id: row.get::<Option<i32>, _>("answer_id").map(AnswerId),
+        revision_id: row.get::<Option<i32>, _>("revision_id").map(RevisionId),
+        title: row.get("title"),
+        content: row.get("content"),
+        author_id: AccountId(row.get("author_id")),
+        reason: FlagReason::from_db(row.get("reason")),
+        matched_words: row.get("matched_words"),
+        created_on: row.get("created_on"),
     }
 }
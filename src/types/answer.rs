@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 
-use crate::types::question::QuestionId;
+use crate::types::{moderation::ModerationStatus, question::QuestionId};
 
 #[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, Hash)]
 pub struct AnswerId(pub i32);
@@ -10,6 +10,11 @@ pub struct Answer {
     pub id: AnswerId,
     pub content: String,
     pub question_id: QuestionId,
+    #[serde(default)]
+    pub status: ModerationStatus,
+    /// 모델이 쓴 답변 (질문 작성자가 받아들이기 전까지 pending 이다)
+    #[serde(default)]
+    pub is_ai: bool,
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
@@ -0,0 +1,197 @@
+use chrono::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::llm::Usage;
+use crate::types::{
+    answer::AnswerId,
+    conversation::ConversationId,
+    document::{ChunkId, DocumentId},
+    question::QuestionId,
+};
+
+/// `POST /chat` 요청 본문
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct ChatRequest {
+    pub question: String,
+    /// 이어서 물을 대화 (없으면 새 대화를 시작한다)
+    pub conversation_id: Option<ConversationId>,
+    /// 참고할 글 수 (없으면 설정값)
+    pub top_k: Option<usize>,
+    #[serde(default)]
+    pub filter: RetrievalFilter,
+}
+
+/// 검색 대상 글의 종류 (문서는 파일 형식으로 나눈다)
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum SourceType {
+    Question,
+    Answer,
+    #[serde(alias = "md")]
+    Markdown,
+    #[serde(alias = "txt")]
+    Text,
+    Pdf,
+}
+
+impl SourceType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SourceType::Question => "question",
+            SourceType::Answer => "answer",
+            SourceType::Markdown => "markdown",
+            SourceType::Text => "text",
+            SourceType::Pdf => "pdf",
+        }
+    }
+
+    pub fn from_db(value: &str) -> Self {
+        match value {
+            "answer" => SourceType::Answer,
+            "markdown" => SourceType::Markdown,
+            "text" => SourceType::Text,
+            "pdf" => SourceType::Pdf,
+            _ => SourceType::Question,
+        }
+    }
+
+    /// 확장자로 문서 형식을 고른다 (질문과 답변은 파일이 아니다)
+    pub fn from_filename(filename: &str) -> Option<Self> {
+        let (_, extension) = filename.rsplit_once('.')?;
+        match extension.to_ascii_lowercase().as_str() {
+            "md" | "markdown" => Some(SourceType::Markdown),
+            "txt" | "text" => Some(SourceType::Text),
+            "pdf" => Some(SourceType::Pdf),
+            _ => None,
+        }
+    }
+}
+
+/// 검색 결과를 메타데이터로 거른다 (비어 있으면 거르지 않는다)
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+pub struct RetrievalFilter {
+    /// 이 종류의 글만 찾는다
+    pub source_type: Option<Vec<SourceType>>,
+    /// 이 시각 뒤에 고친 글만 찾는다
+    pub updated_after: Option<DateTime<Utc>>,
+}
+
+impl RetrievalFilter {
+    pub fn matches(&self, passage: &Passage) -> bool {
+        self.source_type
+            .as_ref()
+            .is_none_or(|types| types.contains(&passage.source))
+            && self
+                .updated_after
+                .is_none_or(|after| passage.updated_at > after)
+    }
+
+    /// SQL 에 바인드할 종류 목록 (거르지 않으면 None)
+    pub fn source_names(&self) -> Option<Vec<String>> {
+        self.source_type.as_ref().map(|types| {
+            types
+                .iter()
+                .map(|source| source.as_str().to_string())
+                .collect()
+        })
+    }
+}
+
+/// 검색으로 찾은 질문, 답변 또는 문서 조각 하나
+#[derive(Debug, Clone, PartialEq)]
+pub struct Passage {
+    pub source: SourceType,
+    /// 질문과 답변이면 질문 id, 문서 조각이면 None
+    pub question_id: Option<QuestionId>,
+    /// 답변이면 그 id, 질문 본문이면 None
+    pub answer_id: Option<AnswerId>,
+    /// 문서 조각이면 문서와 조각 id
+    pub document_id: Option<DocumentId>,
+    pub chunk_id: Option<ChunkId>,
+    /// 문서 조각이면 `문서 제목 > 제목 경로`
+    pub title: String,
+    pub content: String,
+    pub updated_at: DateTime<Utc>,
+    pub score: f32,
+}
+
+impl Passage {
+    /// 같은 글인지 가리는 키 (여러 검색 결과를 합칠 때, embeddings 테이블의 키)
+    pub fn key(&self) -> (SourceType, i32) {
+        let id = match (&self.chunk_id, &self.answer_id, &self.question_id) {
+            (Some(chunk_id), _, _) => chunk_id.0,
+            (None, Some(answer_id), _) => answer_id.0,
+            (None, None, Some(question_id)) => question_id.0,
+            (None, None, None) => 0,
+        };
+        (self.source, id)
+    }
+
+    /// 프롬프트와 답에서 쓰는 라벨 (질문은 `Q<질문 id>`, 문서는 `D<문서 id>`)
+    pub fn label(&self) -> String {
+        match (&self.document_id, &self.question_id) {
+            (Some(document_id), _) => format!("D{}", document_id.0),
+            (None, Some(question_id)) => format!("Q{}", question_id.0),
+            (None, None) => String::new(),
+        }
+    }
+}
+
+/// 답의 근거로 쓴 글
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Citation {
+    pub question_id: Option<QuestionId>,
+    pub answer_id: Option<AnswerId>,
+    pub document_id: Option<DocumentId>,
+    pub title: String,
+    pub score: f32,
+    /// 모델이 답에서 `[Q<id>]` 나 `[D<id>]` 로 직접 인용했는지
+    pub cited: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ChatResponse {
+    /// 이 질문과 답을 저장한 대화
+    pub conversation_id: Option<ConversationId>,
+    pub answer: String,
+    pub citations: Vec<Citation>,
+    /// 답을 만드는 데 쓴 토큰 수 (모델을 부르지 않았거나 스트리밍이면 없다)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage: Option<Usage>,
+}
+
+/// 스트리밍으로 보내는 답의 조각
+/// SSE 에서는 type 을 이벤트 이름으로도 쓴다
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum ChatEvent {
+    /// 모델이 이어서 만든 글자
+    Token { text: String },
+    /// 저장까지 마친 답 (인용과 conversation_id 를 담는다)
+    Done(ChatResponse),
+    /// problem+json 과 같은 status 와 code
+    Error {
+        status: u16,
+        code: &'static str,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        detail: Option<String>,
+    },
+}
+
+impl ChatEvent {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ChatEvent::Token { .. } => "token",
+            ChatEvent::Done(_) => "done",
+            ChatEvent::Error { .. } => "error",
+        }
+    }
+
+    pub fn error(error: &handle_errors::Error) -> Self {
+        ChatEvent::Error {
+            status: error.status().as_u16(),
+            code: error.code(),
+            detail: error.detail(),
+        }
+    }
+}
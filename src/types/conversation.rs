@@ -0,0 +1,46 @@
+use chrono::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::llm::Role;
+use crate::types::{account::AccountId, chat::Citation};
+
+/// 한 사용자와 챗봇이 주고받은 대화
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Conversation {
+    pub id: ConversationId,
+    pub account_id: AccountId,
+    /// 첫 질문의 앞부분
+    pub title: String,
+    /// 프롬프트에 다 넣지 못한 앞부분 대화의 요약
+    pub summary: Option<String>,
+    /// summary 에 들어간 마지막 메시지
+    #[serde(skip)]
+    pub summarized_until: Option<MessageId>,
+    pub created_on: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ConversationId(pub i32);
+
+/// 대화에 저장한 질문(user) 또는 답(assistant)
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Message {
+    pub id: MessageId,
+    pub role: Role,
+    pub content: String,
+    /// 답의 근거 (질문에는 없다)
+    pub citations: Vec<Citation>,
+    pub created_on: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct MessageId(pub i32);
+
+/// `GET /conversations/{id}` 응답
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ConversationDetail {
+    #[serde(flatten)]
+    pub conversation: Conversation,
+    pub messages: Vec<Message>,
+}
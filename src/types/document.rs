@@ -0,0 +1,42 @@
+use chrono::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::types::{account::AccountId, chat::SourceType};
+
+/// 올린 문서 (조각으로 나눠 검색한다)
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Document {
+    pub id: DocumentId,
+    pub title: String,
+    pub source_type: SourceType,
+    /// 올리거나 가져온 파일 이름
+    pub filename: String,
+    /// CLI 로 가져온 문서는 None
+    pub account_id: Option<AccountId>,
+    /// 나눈 조각 수
+    pub chunks: i64,
+    pub created_on: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DocumentId(pub i32);
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ChunkId(pub i32);
+
+/// 저장할 문서 (조각은 DocumentProcessor 가 만든다)
+#[derive(Debug, Clone, PartialEq)]
+pub struct NewDocument {
+    pub title: String,
+    pub source_type: SourceType,
+    pub filename: String,
+    pub chunks: Vec<Chunk>,
+}
+
+/// 문서의 한 조각
+#[derive(Debug, Clone, PartialEq)]
+pub struct Chunk {
+    /// Markdown 제목 경로 (`설치 > Linux`), 제목 밑이 아니면 None
+    pub heading: Option<String>,
+    pub content: String,
+}
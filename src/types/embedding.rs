@@ -0,0 +1,17 @@
+use crate::types::chat::Passage;
+
+/// 벡터를 새로 만들어야 하는 질문, 답변 또는 문서 조각
+#[derive(Debug, Clone, PartialEq)]
+pub struct EmbeddingJob {
+    /// 이 글의 updated_at 시점으로 만든 벡터다 (글이 다시 바뀌면 또 만든다)
+    pub passage: Passage,
+    /// 임베딩할 글 (질문은 제목과 본문)
+    pub text: String,
+}
+
+impl EmbeddingJob {
+    /// 질문이면 질문 id, 답변이면 답변 id, 문서 조각이면 조각 id
+    pub fn entity_id(&self) -> i32 {
+        self.passage.key().1
+    }
+}
@@ -0,0 +1,103 @@
+use handle_errors::Error;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use crate::types::question::Question;
+
+/// 질문 버전으로 강한(strong) ETag 를 만든다
+pub fn from_version(version: i32) -> String {
+    format!("\"{}\"", version)
+}
+
+/// 질문 목록 전체에 대한 약한(weak) ETag 를 만든다
+/// 목록에 포함된 질문의 id 와 버전이 같으면 같은 값이 나온다
+pub fn from_questions(questions: &[Question]) -> String {
+    let mut hasher = DefaultHasher::new();
+    for question in questions {
+        question.id.hash(&mut hasher);
+        question.version.hash(&mut hasher);
+    }
+    format!("W/\"{:x}\"", hasher.finish())
+}
+
+/// If-Match 헤더에서 수정하려는 질문의 버전을 추출한다
+/// # 예
+/// `If-Match: "3"` 은 Some(3), `If-Match: *` 은 버전을 확인하지 않으므로 None 이다.
+/// 헤더가 없으면 PreconditionRequired, 버전으로 읽을 수 없는 값(약한 ETag 포함)은
+/// 어떤 버전과도 일치하지 않으므로 PreconditionFailed 를 반환한다.
+pub fn extract_if_match(header: Option<String>) -> Result<Option<i32>, Error> {
+    let header = header.ok_or(Error::PreconditionRequired)?;
+    let header = header.trim();
+
+    if header == "*" {
+        return Ok(None);
+    }
+
+    header
+        .strip_prefix('"')
+        .and_then(|h| h.strip_suffix('"'))
+        .and_then(|version| version.parse::<i32>().ok())
+        .map(Some)
+        .ok_or(Error::PreconditionFailed)
+}
+
+/// If-None-Match 헤더가 현재 ETag 와 일치하는지(304 로 응답해도 되는지) 확인한다
+/// GET 요청이므로 약한 비교를 사용한다
+pub fn matches_none_match(header: &Option<String>, etag: &str) -> bool {
+    let header = match header {
+        Some(header) => header,
+        None => return false,
+    };
+
+    header
+        .split(',')
+        .map(str::trim)
+        .any(|candidate| candidate == "*" || strip_weak(candidate) == strip_weak(etag))
+}
+
+fn strip_weak(etag: &str) -> &str {
+    etag.strip_prefix("W/").unwrap_or(etag)
+}
+
+#[cfg(test)]
+mod etag_tests {
+    use super::{extract_if_match, from_version, matches_none_match, Error};
+
+    #[test]
+    fn if_match_with_version() {
+        let expected = extract_if_match(Some(from_version(3)));
+        assert_eq!(expected.unwrap(), Some(3));
+    }
+
+    #[test]
+    fn if_match_wildcard() {
+        assert_eq!(extract_if_match(Some("*".to_string())).unwrap(), None);
+    }
+
+    #[test]
+    fn missing_if_match() {
+        let result = format!("{}", extract_if_match(None).unwrap_err());
+        assert_eq!(result, format!("{}", Error::PreconditionRequired));
+    }
+
+    #[test]
+    fn weak_if_match_never_matches() {
+        let result = format!(
+            "{}",
+            extract_if_match(Some("W/\"3\"".to_string())).unwrap_err()
+        );
+        assert_eq!(result, format!("{}", Error::PreconditionFailed));
+    }
+
+    #[test]
+    fn if_none_match() {
+        let etag = from_version(2);
+        assert!(matches_none_match(
+            &Some("\"1\", W/\"2\"".to_string()),
+            &etag
+        ));
+        assert!(matches_none_match(&Some("*".to_string()), &etag));
+        assert!(!matches_none_match(&Some("\"1\"".to_string()), &etag));
+        assert!(!matches_none_match(&None, &etag));
+    }
+}
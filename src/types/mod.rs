@@ -1,4 +1,12 @@
 pub mod account;
 pub mod answer;
+pub mod chat;
+pub mod conversation;
+pub mod document;
+pub mod embedding;
+pub mod etag;
+pub mod moderation;
+pub mod notification;
 pub mod pagination;
 pub mod question;
+pub mod revision;
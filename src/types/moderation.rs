@@ -0,0 +1,168 @@
+use chrono::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::types::{
+    account::AccountId, answer::AnswerId, question::QuestionId, revision::RevisionId,
+};
+
+/// 질문과 답변의 공개 상태
+/// 비속어가 들어 있거나 검사하지 못한 글은 검토가 끝날 때까지 pending 으로 숨긴다
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ModerationStatus {
+    #[default]
+    Published,
+    Pending,
+    Rejected,
+}
+
+impl ModerationStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ModerationStatus::Published => "published",
+            ModerationStatus::Pending => "pending",
+            ModerationStatus::Rejected => "rejected",
+        }
+    }
+
+    /// 검토 대기 사유가 있으면 pending 이다
+    pub fn from_flag(flag: &Option<Flag>) -> Self {
+        match flag {
+            Some(_) => ModerationStatus::Pending,
+            None => ModerationStatus::Published,
+        }
+    }
+
+    /// DB 의 status 컬럼 값을 읽는다
+    /// 알 수 없는 값은 공개하지 않도록 pending 으로 본다
+    pub fn from_db(value: &str) -> Self {
+        match value {
+            "published" => ModerationStatus::Published,
+            "rejected" => ModerationStatus::Rejected,
+            _ => ModerationStatus::Pending,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum FlagReason {
+    /// 비속어가 들어 있다
+    Profanity,
+    /// 비속어 검사 API 를 쓸 수 없어서 검사하지 못했다
+    Unchecked,
+}
+
+impl FlagReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FlagReason::Profanity => "profanity",
+            FlagReason::Unchecked => "unchecked",
+        }
+    }
+
+    pub fn from_db(value: &str) -> Self {
+        match value {
+            "profanity" => FlagReason::Profanity,
+            _ => FlagReason::Unchecked,
+        }
+    }
+}
+
+/// 글을 검토 대기열에 보내는 이유
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Flag {
+    pub reason: FlagReason,
+    /// 찾은 비속어 (원문 그대로)
+    pub words: Vec<String>,
+}
+
+impl Flag {
+    /// 여러 필드의 검사 결과를 하나로 합친다
+    /// 비속어가 하나라도 있으면 profanity, 검사하지 못한 필드만 있으면 unchecked 이다
+    pub fn merge(flags: impl IntoIterator<Item = Option<Flag>>) -> Option<Flag> {
+        flags.into_iter().flatten().reduce(|mut merged, flag| {
+            if flag.reason == FlagReason::Profanity {
+                merged.reason = FlagReason::Profanity;
+            }
+            for word in flag.words {
+                if !merged.words.contains(&word) {
+                    merged.words.push(word);
+                }
+            }
+            merged
+        })
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct QueueItemId(pub i32);
+
+/// 검토 대기열 항목 (질문 또는 답변 하나)
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct QueueItem {
+    pub id: QueueItemId,
+    pub question_id: Option<QuestionId>,
+    pub answer_id: Option<AnswerId>,
+    /// 공개한 질문의 수정이면 검토를 기다리는 이력 (title, content 는 그 수정의 것이다)
+    pub revision_id: Option<RevisionId>,
+    /// 답변이면 None
+    pub title: Option<String>,
+    pub content: String,
+    pub author_id: AccountId,
+    pub reason: FlagReason,
+    pub matched_words: Vec<String>,
+    pub created_on: DateTime<Utc>,
+}
+
+/// 모더레이터의 결정
+/// `{"decision": "edit", "content": "..."}` 처럼 보낸다
+#[derive(Deserialize, Debug, Clone)]
+#[serde(tag = "decision", rename_all = "lowercase", deny_unknown_fields)]
+pub enum Decision {
+    /// 그대로 공개한다
+    Approve,
+    /// 고친 뒤 공개한다 (빠진 필드는 그대로 둔다)
+    Edit {
+        title: Option<String>,
+        content: Option<String>,
+    },
+    /// 공개하지 않는다
+    Reject { note: Option<String> },
+}
+
+impl Decision {
+    pub fn outcome(&self) -> &'static str {
+        match self {
+            Decision::Approve => "approved",
+            Decision::Edit { .. } => "edited",
+            Decision::Reject { .. } => "rejected",
+        }
+    }
+}
+
+#[cfg(test)]
+mod moderation_tests {
+    use super::{Flag, FlagReason};
+
+    fn flag(reason: FlagReason, words: &[&str]) -> Option<Flag> {
+        Some(Flag {
+            reason,
+            words: words.iter().map(|w| w.to_string()).collect(),
+        })
+    }
+
+    #[test]
+    fn merge_prefers_profanity_and_dedups_words() {
+        assert_eq!(Flag::merge([None, None]), None);
+        assert_eq!(
+            Flag::merge([
+                flag(FlagReason::Unchecked, &[]),
+                None,
+                flag(FlagReason::Profanity, &["shit", "damn"]),
+                flag(FlagReason::Profanity, &["shit"]),
+            ]),
+            flag(FlagReason::Profanity, &["shit", "damn"])
+        );
+    }
+}
@@ -0,0 +1,17 @@
+use chrono::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::types::{answer::AnswerId, question::QuestionId};
+
+/// 작성자에게 보내는 알림 (지금은 검토 결과만 보낸다)
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Notification {
+    pub id: NotificationId,
+    pub question_id: Option<QuestionId>,
+    pub answer_id: Option<AnswerId>,
+    pub message: String,
+    pub created_on: DateTime<Utc>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct NotificationId(pub i32);
@@ -18,11 +18,13 @@ pub struct Pagination {
 /// /questions?start=1&end=10
 /// # 사용 예
 /// ```rust
+/// # use std::collections::HashMap;
+/// # use warp_chatbot::types::pagination::extract_pagination;
 /// let mut query = HashMap::new();
 /// query.insert("limit".to_string(), "1".to_string());
 /// query.insert("offset".to_string(), "10".to_string());
-/// let p = types::pagination::extract_pagination(query).unwrap();
-/// assert_eq!(p.limit, 1);
+/// let p = extract_pagination(query).unwrap();
+/// assert_eq!(p.limit, Some(1));
 /// assert_eq!(p.offset, 10);
 /// ```
 pub fn extract_pagination(params: HashMap<String, String>) -> Result<Pagination, Error> {
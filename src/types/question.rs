@@ -1,4 +1,7 @@
-use serde::{Deserialize, Serialize};
+use handle_errors::DuplicateCandidate;
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::types::moderation::ModerationStatus;
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Question {
@@ -6,6 +9,11 @@ pub struct Question {
     pub title: String,
     pub content: String,
     pub tags: Option<Vec<String>>,
+    /// 수정될 때마다 1씩 증가하며 ETag 로 전달된다
+    #[serde(default)]
+    pub version: i32,
+    #[serde(default)]
+    pub status: ModerationStatus,
 }
 
 #[derive(Debug, Clone, Serialize, PartialEq, Eq, Hash, Deserialize)]
@@ -17,3 +25,96 @@ pub struct NewQuestion {
     pub content: String,
     pub tags: Option<Vec<String>>,
 }
+
+/// POST /questions 쿼리
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct AddQuestionParams {
+    /// 비슷한 질문이 있어도 올린다
+    #[serde(default)]
+    pub force: bool,
+}
+
+/// POST /questions/similar 본문 (아직 올리지 않은 질문)
+#[derive(Deserialize, Debug, Clone)]
+pub struct QuestionDraft {
+    pub title: String,
+    #[serde(default)]
+    pub content: String,
+    /// 돌려받을 질문 수 (없으면 설정값)
+    pub limit: Option<usize>,
+}
+
+/// 쓰고 있는 질문과 비슷한 공개된 질문
+/// `score` 는 두 질문의 임베딩 코사인 유사도다
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct SimilarQuestion {
+    pub id: QuestionId,
+    pub title: String,
+    pub score: f32,
+}
+
+impl From<SimilarQuestion> for DuplicateCandidate {
+    fn from(question: SimilarQuestion) -> Self {
+        DuplicateCandidate {
+            id: question.id.0,
+            title: question.title,
+            score: question.score,
+        }
+    }
+}
+
+/// PATCH /questions/{id} 본문 (JSON Merge Patch)
+/// 빠진 필드는 그대로 두고, `tags` 에 null 을 주면 태그를 지운다.
+/// `title` 과 `content` 는 지울 수 없으므로 null 이면 역직렬화에 실패한다.
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct QuestionPatch {
+    #[serde(default, deserialize_with = "deserialize_present")]
+    pub title: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_present")]
+    pub content: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_present")]
+    pub tags: Option<Option<Vec<String>>>,
+}
+
+/// 본문에 있는 필드만 Some 으로 감싼다 (빠진 필드는 `default` 로 None 이 된다)
+fn deserialize_present<'de, T, D>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    T::deserialize(deserializer).map(Some)
+}
+
+#[cfg(test)]
+mod question_tests {
+    use super::QuestionPatch;
+
+    fn patch(body: &str) -> Result<QuestionPatch, serde_json::Error> {
+        serde_json::from_str(body)
+    }
+
+    #[test]
+    fn missing_fields_are_left_alone() {
+        let empty = patch("{}").unwrap();
+        assert_eq!(empty.title, None);
+        assert_eq!(empty.content, None);
+        assert_eq!(empty.tags, None);
+    }
+
+    #[test]
+    fn null_tags_clear_the_tags() {
+        let cleared = patch(r#"{"tags": null}"#).unwrap();
+        assert_eq!(cleared.tags, Some(None));
+
+        let replaced = patch(r#"{"tags": ["warp"]}"#).unwrap();
+        assert_eq!(replaced.tags, Some(Some(vec!["warp".to_string()])));
+    }
+
+    #[test]
+    fn null_title_or_content_is_rejected() {
+        assert!(patch(r#"{"title": null}"#).is_err());
+        assert!(patch(r#"{"content": null}"#).is_err());
+        assert!(patch(r#"{"version": 2}"#).is_err());
+    }
+}
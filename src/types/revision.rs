@@ -0,0 +1,22 @@
+use chrono::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::types::{account::AccountId, moderation::ModerationStatus, question::QuestionId};
+
+/// 질문이 저장될 때마다 남는 편집 이력
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Revision {
+    pub id: RevisionId,
+    pub question_id: QuestionId,
+    pub title: String,
+    pub content: String,
+    pub tags: Option<Vec<String>>,
+    /// 편집한 계정
+    pub account_id: AccountId,
+    pub created_on: DateTime<Utc>,
+    /// 검토 대기열에 간 수정은 pending 이고, 모더레이터가 거절하면 rejected 로 남는다
+    pub status: ModerationStatus,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RevisionId(pub i32);
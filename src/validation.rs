@@ -0,0 +1,361 @@
+use handle_errors::{Error, FieldError};
+
+use crate::types::{
+    account::Account,
+    answer::NewAnswer,
+    chat::ChatRequest,
+    document::NewDocument,
+    moderation::Decision,
+    question::{NewQuestion, Question, QuestionDraft, QuestionPatch},
+};
+
+/// questions.title 컬럼이 VARCHAR(255) 이다
+const MAX_TITLE_LENGTH: usize = 255;
+const MAX_CONTENT_LENGTH: usize = 10_000;
+/// accounts.email 컬럼이 VARCHAR(255) 이다
+const MAX_EMAIL_LENGTH: usize = 255;
+const MIN_PASSWORD_LENGTH: usize = 8;
+const MAX_PASSWORD_LENGTH: usize = 128;
+const MAX_TAGS: usize = 5;
+const MAX_TAG_LENGTH: usize = 32;
+/// 프롬프트가 너무 길어지지 않도록 챗봇 질문은 짧게 받는다
+const MAX_CHAT_QUESTION_LENGTH: usize = 2_000;
+const MAX_CHAT_TOP_K: usize = 20;
+const MAX_SIMILAR_QUESTIONS: usize = 20;
+
+/// 요청 본문을 DB 에 보내기 전에 검증한다
+/// 첫 번째 실패에서 멈추지 않고 실패한 모든 필드를 ValidationError 로 모아 돌려준다
+pub trait Validate {
+    fn validate(&self) -> Result<(), Error>;
+}
+
+impl Validate for NewQuestion {
+    fn validate(&self) -> Result<(), Error> {
+        let mut v = Validator::default();
+        v.text("title", &self.title, MAX_TITLE_LENGTH);
+        v.text("content", &self.content, MAX_CONTENT_LENGTH);
+        v.tags("tags", &self.tags);
+        v.finish()
+    }
+}
+
+impl Validate for Question {
+    fn validate(&self) -> Result<(), Error> {
+        let mut v = Validator::default();
+        v.text("title", &self.title, MAX_TITLE_LENGTH);
+        v.text("content", &self.content, MAX_CONTENT_LENGTH);
+        v.tags("tags", &self.tags);
+        v.finish()
+    }
+}
+
+/// 본문에 들어 있는 필드만 검증한다
+impl Validate for QuestionPatch {
+    fn validate(&self) -> Result<(), Error> {
+        let mut v = Validator::default();
+        if let Some(title) = &self.title {
+            v.text("title", title, MAX_TITLE_LENGTH);
+        }
+        if let Some(content) = &self.content {
+            v.text("content", content, MAX_CONTENT_LENGTH);
+        }
+        if let Some(tags) = &self.tags {
+            v.tags("tags", tags);
+        }
+        v.finish()
+    }
+}
+
+/// 쓰는 중인 질문이라 본문은 비어 있어도 된다
+impl Validate for QuestionDraft {
+    fn validate(&self) -> Result<(), Error> {
+        let mut v = Validator::default();
+        v.text("title", &self.title, MAX_TITLE_LENGTH);
+        if self.content.chars().count() > MAX_CONTENT_LENGTH {
+            v.fail(
+                "content",
+                format!("must be at most {} characters", MAX_CONTENT_LENGTH),
+            );
+        }
+        if let Some(limit) = self.limit {
+            if !(1..=MAX_SIMILAR_QUESTIONS).contains(&limit) {
+                v.fail(
+                    "limit",
+                    format!("must be between 1 and {}", MAX_SIMILAR_QUESTIONS),
+                );
+            }
+        }
+        v.finish()
+    }
+}
+
+impl Validate for NewAnswer {
+    fn validate(&self) -> Result<(), Error> {
+        let mut v = Validator::default();
+        v.text("content", &self.content, MAX_CONTENT_LENGTH);
+        v.finish()
+    }
+}
+
+impl Validate for ChatRequest {
+    fn validate(&self) -> Result<(), Error> {
+        let mut v = Validator::default();
+        v.text("question", &self.question, MAX_CHAT_QUESTION_LENGTH);
+        if let Some(top_k) = self.top_k {
+            if !(1..=MAX_CHAT_TOP_K).contains(&top_k) {
+                v.fail("top_k", format!("must be between 1 and {}", MAX_CHAT_TOP_K));
+            }
+        }
+        v.finish()
+    }
+}
+
+/// documents.title 컬럼도 VARCHAR(255) 이다
+impl Validate for NewDocument {
+    fn validate(&self) -> Result<(), Error> {
+        let mut v = Validator::default();
+        v.text("title", &self.title, MAX_TITLE_LENGTH);
+        v.finish()
+    }
+}
+
+/// 모더레이터가 고친 필드만 검증한다
+impl Validate for Decision {
+    fn validate(&self) -> Result<(), Error> {
+        let mut v = Validator::default();
+        if let Decision::Edit { title, content } = self {
+            if title.is_none() && content.is_none() {
+                v.fail("content", "edit must change title or content".to_string());
+            }
+            if let Some(title) = title {
+                v.text("title", title, MAX_TITLE_LENGTH);
+            }
+            if let Some(content) = content {
+                v.text("content", content, MAX_CONTENT_LENGTH);
+            }
+        }
+        v.finish()
+    }
+}
+
+/// 회원 가입 요청에만 사용한다
+/// 로그인은 기존 계정의 비밀번호 정책이 바뀌어도 막히지 않아야 한다
+impl Validate for Account {
+    fn validate(&self) -> Result<(), Error> {
+        let mut v = Validator::default();
+        v.email("email", &self.email);
+        v.password("password", &self.password);
+        v.finish()
+    }
+}
+
+#[derive(Default)]
+struct Validator {
+    errors: Vec<FieldError>,
+}
+
+impl Validator {
+    fn fail(&mut self, field: &str, message: String) {
+        self.errors.push(FieldError {
+            field: field.to_string(),
+            message,
+        });
+    }
+
+    fn text(&mut self, field: &str, value: &str, max: usize) {
+        if value.trim().is_empty() {
+            self.fail(field, "must not be empty".to_string());
+        } else if value.chars().count() > max {
+            self.fail(field, format!("must be at most {} characters", max));
+        }
+    }
+
+    fn email(&mut self, field: &str, value: &str) {
+        if value.chars().count() > MAX_EMAIL_LENGTH {
+            self.fail(
+                field,
+                format!("must be at most {} characters", MAX_EMAIL_LENGTH),
+            );
+        } else if !is_email(value) {
+            self.fail(field, "must be a valid email address".to_string());
+        }
+    }
+
+    fn password(&mut self, field: &str, value: &str) {
+        let length = value.chars().count();
+        if length < MIN_PASSWORD_LENGTH {
+            self.fail(
+                field,
+                format!("must be at least {} characters", MIN_PASSWORD_LENGTH),
+            );
+        } else if length > MAX_PASSWORD_LENGTH {
+            self.fail(
+                field,
+                format!("must be at most {} characters", MAX_PASSWORD_LENGTH),
+            );
+        } else if !value.chars().any(char::is_alphabetic)
+            || !value.chars().any(|c| c.is_ascii_digit())
+        {
+            self.fail(
+                field,
+                "must contain at least one letter and one digit".to_string(),
+            );
+        }
+    }
+
+    fn tags(&mut self, field: &str, tags: &Option<Vec<String>>) {
+        let tags = match tags {
+            Some(tags) => tags,
+            None => return,
+        };
+
+        if tags.len() > MAX_TAGS {
+            self.fail(field, format!("must contain at most {} tags", MAX_TAGS));
+        }
+
+        for (i, tag) in tags.iter().enumerate() {
+            let tag_field = format!("{}[{}]", field, i);
+            if !is_tag(tag) {
+                self.fail(
+                    &tag_field,
+                    format!(
+                        "must be 1 to {} lowercase letters, digits or one of - + # .",
+                        MAX_TAG_LENGTH
+                    ),
+                );
+            } else if tags[..i].contains(tag) {
+                self.fail(&tag_field, "must not be repeated".to_string());
+            }
+        }
+    }
+
+    fn finish(self) -> Result<(), Error> {
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::ValidationError(self.errors))
+        }
+    }
+}
+
+/// 로컬 파트와 점이 들어간 도메인이 있는 `local@domain.tld` 형태인지 확인한다
+fn is_email(value: &str) -> bool {
+    if value.chars().any(char::is_whitespace) {
+        return false;
+    }
+
+    match value.split_once('@') {
+        Some((local, domain)) => {
+            !local.is_empty()
+                && !domain.contains('@')
+                && domain.contains('.')
+                && !domain.starts_with('.')
+                && !domain.ends_with('.')
+                && !domain.contains("..")
+        }
+        None => false,
+    }
+}
+
+fn is_tag(tag: &str) -> bool {
+    let length = tag.chars().count();
+    (1..=MAX_TAG_LENGTH).contains(&length)
+        && tag.chars().all(|c| {
+            c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '-' | '+' | '#' | '.')
+        })
+}
+
+#[cfg(test)]
+mod validation_tests {
+    use super::{Account, Error, NewQuestion, QuestionDraft, QuestionPatch, Validate};
+
+    fn failing_fields(result: Result<(), Error>) -> Vec<String> {
+        match result {
+            Err(Error::ValidationError(errors)) => errors.into_iter().map(|e| e.field).collect(),
+            other => panic!("expected validation error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn valid_question() {
+        let question = NewQuestion {
+            title: "How do I use warp filters?".to_string(),
+            content: "I am stuck".to_string(),
+            tags: Some(vec!["rust".to_string(), "c++".to_string()]),
+        };
+        assert!(question.validate().is_ok());
+    }
+
+    #[test]
+    fn reports_every_failing_field() {
+        let question = NewQuestion {
+            title: " ".to_string(),
+            content: "x".repeat(10_001),
+            tags: Some(vec!["Rust".to_string(), "ok".to_string(), "ok".to_string()]),
+        };
+        assert_eq!(
+            failing_fields(question.validate()),
+            vec!["title", "content", "tags[0]", "tags[2]"]
+        );
+    }
+
+    #[test]
+    fn title_longer_than_column() {
+        let question = NewQuestion {
+            title: "가".repeat(256),
+            content: "content".to_string(),
+            tags: None,
+        };
+        assert_eq!(failing_fields(question.validate()), vec!["title"]);
+    }
+
+    #[test]
+    fn patch_only_checks_supplied_fields() {
+        let patch = QuestionPatch {
+            tags: Some(Some(vec!["x".to_string(); 6])),
+            ..Default::default()
+        };
+        assert_eq!(
+            failing_fields(patch.validate()),
+            vec!["tags", "tags[1]", "tags[2]", "tags[3]", "tags[4]", "tags[5]"]
+        );
+        assert!(QuestionPatch::default().validate().is_ok());
+    }
+
+    #[test]
+    fn draft_may_have_no_content() {
+        let draft = QuestionDraft {
+            title: "How do I use warp filters?".to_string(),
+            content: String::new(),
+            limit: None,
+        };
+        assert!(draft.validate().is_ok());
+
+        let draft = QuestionDraft {
+            title: String::new(),
+            limit: Some(0),
+            ..draft
+        };
+        assert_eq!(failing_fields(draft.validate()), vec!["title", "limit"]);
+    }
+
+    #[test]
+    fn account_rules() {
+        let account = Account {
+            id: None,
+            email: "not-an-email".to_string(),
+            password: "a".to_string(),
+        };
+        assert_eq!(
+            failing_fields(account.validate()),
+            vec!["email", "password"]
+        );
+
+        let account = Account {
+            id: None,
+            email: "test@email.com".to_string(),
+            password: "password1".to_string(),
+        };
+        assert!(account.validate().is_ok());
+    }
+}
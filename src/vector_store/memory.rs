@@ -0,0 +1,250 @@
+use async_trait::async_trait;
+use handle_errors::Error;
+use std::collections::HashMap;
+use tokio::sync::{Mutex, RwLock};
+use tokio::time::{Duration, Instant};
+
+use super::VectorStore;
+use crate::embedding::cosine;
+use crate::store::Store;
+use crate::types::{
+    chat::{Passage, RetrievalFilter, SourceType},
+    embedding::EmbeddingJob,
+};
+
+type Entries = HashMap<(SourceType, i32), (Passage, Vec<f32>)>;
+
+/// DB 의 embeddings 테이블과 맞추는 설정
+struct DbSync {
+    store: Store,
+    refresh: Duration,
+    /// 모델마다 마지막으로 DB 에서 읽은 시각
+    loaded: Mutex<HashMap<String, Instant>>,
+}
+
+/// 벡터를 메모리에 두고 전부 비교해서 찾는다
+/// DB 를 붙이면 쓰기는 DB 에도 하고, refresh 간격마다 DB 에서 다시 읽어서
+/// 재시작과 다른 인스턴스의 쓰기, 삭제되거나 숨겨진 글을 반영한다
+pub struct InMemoryVectorStore {
+    models: RwLock<HashMap<String, Entries>>,
+    sync: Option<DbSync>,
+}
+
+impl InMemoryVectorStore {
+    /// 메모리에만 둔다 (테스트용)
+    pub fn new() -> Self {
+        InMemoryVectorStore {
+            models: RwLock::new(HashMap::new()),
+            sync: None,
+        }
+    }
+
+    pub fn with_store(store: Store, refresh: Duration) -> Self {
+        InMemoryVectorStore {
+            models: RwLock::new(HashMap::new()),
+            sync: Some(DbSync {
+                store,
+                refresh,
+                loaded: Mutex::new(HashMap::new()),
+            }),
+        }
+    }
+
+    async fn sync(&self, model: &str) -> Result<(), Error> {
+        let sync = match &self.sync {
+            Some(sync) => sync,
+            None => return Ok(()),
+        };
+
+        // 잠근 채로 읽어서 동시에 들어온 검색이 DB 를 여러 번 읽지 않게 한다
+        let mut loaded = sync.loaded.lock().await;
+        if loaded
+            .get(model)
+            .is_some_and(|at| at.elapsed() < sync.refresh)
+        {
+            return Ok(());
+        }
+
+        let entries: Entries = sync
+            .store
+            .get_embedded_passages(model)
+            .await?
+            .into_iter()
+            .map(|(passage, vector)| (passage.key(), (passage, vector)))
+            .collect();
+        tracing::event!(
+            tracing::Level::DEBUG,
+            "vector store loaded {} passages for {}",
+            entries.len(),
+            model
+        );
+        self.models.write().await.insert(model.to_string(), entries);
+        loaded.insert(model.to_string(), Instant::now());
+        Ok(())
+    }
+}
+
+impl Default for InMemoryVectorStore {
+    fn default() -> Self {
+        InMemoryVectorStore::new()
+    }
+}
+
+#[async_trait]
+impl VectorStore for InMemoryVectorStore {
+    async fn upsert(
+        &self,
+        model: &str,
+        embeddings: &[(EmbeddingJob, Vec<f32>)],
+    ) -> Result<(), Error> {
+        if let Some(sync) = &self.sync {
+            sync.store.put_embeddings(model, embeddings).await?;
+        }
+
+        let mut models = self.models.write().await;
+        let entries = models.entry(model.to_string()).or_default();
+        for (job, vector) in embeddings {
+            entries.insert(job.passage.key(), (job.passage.clone(), vector.clone()));
+        }
+        Ok(())
+    }
+
+    async fn search(
+        &self,
+        model: &str,
+        query: &[f32],
+        top_k: usize,
+        min_score: f32,
+        filter: &RetrievalFilter,
+    ) -> Result<Vec<Passage>, Error> {
+        self.sync(model).await?;
+
+        let models = self.models.read().await;
+        let entries = match models.get(model) {
+            Some(entries) => entries,
+            None => return Ok(Vec::new()),
+        };
+        let mut found: Vec<Passage> = entries
+            .values()
+            .filter(|(passage, _)| filter.matches(passage))
+            .map(|(passage, vector)| Passage {
+                score: cosine(query, vector),
+                ..passage.clone()
+            })
+            .filter(|passage| passage.score > min_score)
+            .collect();
+        found.sort_by(|a, b| b.score.total_cmp(&a.score));
+        found.truncate(top_k);
+        Ok(found)
+    }
+}
+
+#[cfg(test)]
+mod memory_tests {
+    use super::*;
+    use crate::embedding::HashingEmbedder;
+    use crate::types::{answer::AnswerId, question::QuestionId};
+    use chrono::{TimeZone, Utc};
+
+    fn job(id: i32, answer: bool, content: &str, year: i32) -> (EmbeddingJob, Vec<f32>) {
+        let passage = Passage {
+            source: if answer {
+                SourceType::Answer
+            } else {
+                SourceType::Question
+            },
+            question_id: Some(QuestionId(id)),
+            answer_id: answer.then_some(AnswerId(id * 10)),
+            document_id: None,
+            chunk_id: None,
+            title: String::new(),
+            content: content.to_string(),
+            updated_at: Utc.with_ymd_and_hms(year, 1, 1, 0, 0, 0).unwrap(),
+            score: 0.0,
+        };
+        let vector = HashingEmbedder::new(512).vectorize(content);
+        (
+            EmbeddingJob {
+                passage,
+                text: content.to_string(),
+            },
+            vector,
+        )
+    }
+
+    fn query(text: &str) -> Vec<f32> {
+        HashingEmbedder::new(512).vectorize(text)
+    }
+
+    #[tokio::test]
+    async fn ranks_by_similarity() {
+        let vectors = InMemoryVectorStore::new();
+        vectors
+            .upsert(
+                "m",
+                &[
+                    job(1, false, "cooking pasta at home", 2025),
+                    job(2, false, "warp filters and routes", 2025),
+                    job(3, true, "compose warp filters with and", 2025),
+                ],
+            )
+            .await
+            .unwrap();
+
+        let found = vectors
+            .search(
+                "m",
+                &query("Warp filters"),
+                5,
+                0.1,
+                &RetrievalFilter::default(),
+            )
+            .await
+            .unwrap();
+
+        let ids: Vec<i32> = found
+            .iter()
+            .map(|p| p.question_id.clone().unwrap().0)
+            .collect();
+        assert_eq!(ids, vec![2, 3]);
+        assert!(found[0].score > found[1].score);
+        let other = vectors
+            .search("other", &query("warp"), 5, 0.0, &RetrievalFilter::default())
+            .await
+            .unwrap();
+        assert!(other.is_empty());
+    }
+
+    #[tokio::test]
+    async fn upsert_replaces_and_filters_apply() {
+        let vectors = InMemoryVectorStore::new();
+        vectors
+            .upsert(
+                "m",
+                &[
+                    job(1, false, "warp filters", 2024),
+                    job(2, true, "warp filters", 2024),
+                    job(3, true, "cooking pasta", 2026),
+                ],
+            )
+            .await
+            .unwrap();
+        vectors
+            .upsert("m", &[job(3, true, "warp filters", 2026)])
+            .await
+            .unwrap();
+        let filter = RetrievalFilter {
+            source_type: Some(vec![SourceType::Answer]),
+            updated_after: Some(Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap()),
+        };
+
+        let found = vectors
+            .search("m", &query("warp"), 5, 0.0, &filter)
+            .await
+            .unwrap();
+
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].question_id, Some(QuestionId(3)));
+        assert_eq!(found[0].content, "warp filters");
+    }
+}
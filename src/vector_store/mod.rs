@@ -0,0 +1,62 @@
+use async_trait::async_trait;
+use handle_errors::Error;
+use std::sync::Arc;
+use tokio::time::Duration;
+
+use crate::config::{Config, VectorStoreKind};
+use crate::store::Store;
+use crate::types::{
+    chat::{Passage, RetrievalFilter},
+    embedding::EmbeddingJob,
+};
+
+mod memory;
+mod pgvector;
+
+pub use memory::InMemoryVectorStore;
+pub use pgvector::PgVectorStore;
+
+/// 글의 벡터를 모델별로 저장하고 코사인 유사도로 찾는다
+#[async_trait]
+pub trait VectorStore: Send + Sync {
+    /// 같은 글, 같은 모델의 벡터는 덮어쓴다
+    async fn upsert(
+        &self,
+        model: &str,
+        embeddings: &[(EmbeddingJob, Vec<f32>)],
+    ) -> Result<(), Error>;
+
+    /// min_score 보다 가까운 글을 유사도가 높은 순서로 top_k 개 돌려준다
+    async fn search(
+        &self,
+        model: &str,
+        query: &[f32],
+        top_k: usize,
+        min_score: f32,
+        filter: &RetrievalFilter,
+    ) -> Result<Vec<Passage>, Error>;
+}
+
+/// 설정에서 고른 벡터 저장소
+/// pgvector 를 골랐는데 DB 에 확장이 없으면 메모리 저장소로 대신하지 않고 에러를 낸다
+pub async fn from_config(config: &Config, store: &Store) -> Result<Arc<dyn VectorStore>, Error> {
+    let memory = || {
+        Arc::new(InMemoryVectorStore::with_store(
+            store.clone(),
+            Duration::from_secs(config.vector_refresh_secs),
+        ))
+    };
+
+    match config.vector_store {
+        VectorStoreKind::Memory => Ok(memory()),
+        VectorStoreKind::Pgvector if config.embedding_dimensions == 0 => Err(Error::InvalidConfig(
+            "embedding_dimensions must be at least 1".to_string(),
+        )),
+        VectorStoreKind::Pgvector if store.has_pgvector().await? => Ok(Arc::new(
+            PgVectorStore::new(store.clone(), config.embedding_dimensions),
+        )),
+        VectorStoreKind::Pgvector => Err(Error::InvalidConfig(
+            "VECTOR_STORE is pgvector but the pgvector extension is not installed".to_string(),
+        )),
+    }
+}
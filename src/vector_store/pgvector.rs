@@ -0,0 +1,86 @@
+use async_trait::async_trait;
+use handle_errors::Error;
+
+use super::VectorStore;
+use crate::store::Store;
+use crate::types::{
+    chat::{Passage, RetrievalFilter},
+    embedding::EmbeddingJob,
+};
+
+/// embeddings 테이블의 pgvector 열에서 찾는다
+/// vector 열은 embedding 에서 만드는 생성 열이라 쓰기는 메모리 저장소와 같다
+pub struct PgVectorStore {
+    store: Store,
+    /// 임베더가 만드는 차원 수 (다른 차원의 질의는 설정이 어긋난 것이다)
+    dimensions: usize,
+}
+
+impl PgVectorStore {
+    pub fn new(store: Store, dimensions: usize) -> Self {
+        PgVectorStore { store, dimensions }
+    }
+}
+
+#[async_trait]
+impl VectorStore for PgVectorStore {
+    async fn upsert(
+        &self,
+        model: &str,
+        embeddings: &[(EmbeddingJob, Vec<f32>)],
+    ) -> Result<(), Error> {
+        self.store.put_embeddings(model, embeddings).await?;
+        Ok(())
+    }
+
+    async fn search(
+        &self,
+        model: &str,
+        query: &[f32],
+        top_k: usize,
+        min_score: f32,
+        filter: &RetrievalFilter,
+    ) -> Result<Vec<Passage>, Error> {
+        if query.len() != self.dimensions {
+            return Err(Error::InvalidConfig(format!(
+                "query has {} dimensions, the vector store expects {}",
+                query.len(),
+                self.dimensions
+            )));
+        }
+        let mut found = self
+            .store
+            .search_embeddings(model, query, top_k as i64, filter)
+            .await?;
+        found.retain(|passage| passage.score > min_score);
+        Ok(found)
+    }
+}
+
+#[cfg(test)]
+mod pgvector_tests {
+    use super::*;
+    use sqlx::postgres::PgPoolOptions;
+
+    /// 차원 검사는 DB 에 가기 전에 하므로 연결하지 않는 풀로 충분하다
+    fn vectors(dimensions: usize) -> PgVectorStore {
+        let connection = PgPoolOptions::new()
+            .connect_lazy("postgres://localhost/unused")
+            .unwrap();
+        PgVectorStore::new(Store { connection }, dimensions)
+    }
+
+    #[tokio::test]
+    async fn rejects_queries_with_other_dimensions() {
+        let filter = RetrievalFilter::default();
+
+        match vectors(4).search("model", &[1.0; 3], 5, 0.0, &filter).await {
+            Err(Error::InvalidConfig(message)) => assert!(message.contains("3 dimensions")),
+            other => panic!("expected invalid config, got {:?}", other),
+        }
+        match vectors(0).search("model", &[], 5, 0.0, &filter).await {
+            Err(Error::InvalidConfig(message)) => assert!(message.contains("empty vector")),
+            other => panic!("expected invalid config, got {:?}", other),
+        }
+    }
+}